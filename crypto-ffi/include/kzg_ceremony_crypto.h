@@ -0,0 +1,131 @@
+/*
+ * C interface to kzg-ceremony-crypto, the cryptography of the Ethereum KZG
+ * ceremony.
+ *
+ * Memory
+ * ------
+ *
+ * - Pointer arguments are borrowed for the duration of the call only. The
+ *   library keeps no references to them after it returns.
+ * - Objects and strings returned through `out` arguments are owned by the
+ *   caller, and must be released exactly once with the matching free
+ *   function: `kzg_transcript_free`, `kzg_contribution_free` or
+ *   `kzg_string_free`. Do not release them with `free`.
+ * - All free functions accept NULL.
+ * - On error, nothing is written to `out`.
+ *
+ * Errors
+ * ------
+ *
+ * Every fallible function returns NULL on success, or a `KzgError` owned by
+ * the caller, to be released with `kzg_error_free`. `kzg_error_code` returns
+ * the same code the sequencer reports, such as
+ * `CeremonyError::G1PairingFailed`. Misuse of this interface is reported
+ * with the `FfiError::*` codes.
+ *
+ * Threads
+ * -------
+ *
+ * Functions may be called from any thread, and use a thread pool internally.
+ * An object must not be modified while another thread uses it.
+ *
+ * JSON values are in the format of the sequencer API. Strings are UTF-8 and
+ * NUL terminated.
+ */
+
+#ifndef KZG_CEREMONY_CRYPTO_H
+#define KZG_CEREMONY_CRYPTO_H
+
+#include <stdbool.h>
+#include <stdint.h>
+
+#ifdef __cplusplus
+extern "C" {
+#endif
+
+/* Error with a code and message. */
+typedef struct KzgError KzgError;
+
+/* A transcript of all sub-ceremonies. */
+typedef struct KzgTranscript KzgTranscript;
+
+/* A contribution to all sub-ceremonies. */
+typedef struct KzgContribution KzgContribution;
+
+/* Signatures a contribution must carry to be accepted. */
+typedef struct KzgSignaturePolicy {
+    /* Require a valid BLS signature of the identity in every sub-ceremony. */
+    bool require_bls;
+    /* Require a valid EIP-712 signature from Ethereum participants. */
+    bool require_ecdsa;
+} KzgSignaturePolicy;
+
+/* Returns the error code. The string is owned by `error`. */
+const char *kzg_error_code(const KzgError *error);
+
+/* Returns the error message. The string is owned by `error`. */
+const char *kzg_error_message(const KzgError *error);
+
+void kzg_error_free(KzgError *error);
+
+/* Releases a string returned by this library. */
+void kzg_string_free(char *string);
+
+/* Parses a transcript from JSON. */
+KzgError *kzg_transcript_parse(const char *json, KzgTranscript **out);
+
+/* Serializes a transcript to JSON. */
+KzgError *kzg_transcript_serialize(const KzgTranscript *transcript, char **out);
+
+/* Creates the start of a new contribution to `transcript`, as returned by
+ * `/lobby/try_contribute`. */
+KzgError *kzg_transcript_contribution(const KzgTranscript *transcript,
+                                      KzgContribution **out);
+
+/* Verifies `contribution` by `identity`, such as `eth|0x…` or
+ * `git|1234|username`, and adds it to `transcript`. The transcript is
+ * unchanged if an error is returned. */
+KzgError *kzg_transcript_verify_add(KzgTranscript *transcript,
+                                    const KzgContribution *contribution,
+                                    const char *identity,
+                                    KzgSignaturePolicy policy);
+
+void kzg_transcript_free(KzgTranscript *transcript);
+
+/* Parses a contribution from JSON. */
+KzgError *kzg_contribution_parse(const char *json, KzgContribution **out);
+
+/* Serializes a contribution to JSON. */
+KzgError *kzg_contribution_serialize(const KzgContribution *contribution,
+                                     char **out);
+
+/* Adds 32 bytes of `entropy` to `contribution` and signs `identity` with it.
+ * `parameters` is the JSON of the ceremony parameters, or NULL for mainnet. */
+KzgError *kzg_contribution_add_entropy(KzgContribution *contribution,
+                                       const uint8_t entropy[32],
+                                       const char *identity,
+                                       const char *parameters);
+
+void kzg_contribution_free(KzgContribution *contribution);
+
+/* Returns the JSON array of `potPubkey`s that `kzg_contribution_add_entropy`
+ * derives from `entropy`. */
+KzgError *kzg_get_pot_pubkeys(const uint8_t entropy[32], char **out);
+
+/* Checks that the JSON `receipt` is signed with the hex encoded `signature`
+ * by `sequencer`, the `sequencer_address` reported at `/info/status`.
+ * `parameters` is the JSON of the ceremony parameters, or NULL for mainnet. */
+KzgError *kzg_receipt_verify(const char *receipt, const char *signature,
+                             const uint8_t sequencer[20],
+                             const char *parameters);
+
+/* Checks a receipt in the legacy format, where the exact string `message` is
+ * signed with `personal_sign`. */
+KzgError *kzg_legacy_receipt_verify(const char *message, const char *signature,
+                                    const uint8_t sequencer[20]);
+
+#ifdef __cplusplus
+}
+#endif
+
+#endif /* KZG_CEREMONY_CRYPTO_H */
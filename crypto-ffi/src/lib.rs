@@ -0,0 +1,430 @@
+//! C interface to `kzg-ceremony-crypto`.
+//!
+//! The interface is declared in `include/kzg_ceremony_crypto.h`, which also
+//! documents the ownership rules. In short:
+//!
+//! * Pointer arguments are borrowed for the duration of the call.
+//! * Objects and strings returned through `out` arguments are owned by the
+//!   caller, and must be released with the matching `kzg_*_free` function.
+//! * Every fallible function returns `NULL` on success, or a [`KzgError`] owned
+//!   by the caller. Its code matches [`ErrorCode::to_error_code`] of the Rust
+//!   error.
+//!
+//! Contributions are verified with the [`DefaultEngine`].
+#![warn(clippy::all, clippy::pedantic, clippy::cargo, clippy::nursery)]
+#![allow(clippy::module_name_repetitions)]
+
+use ethers_core::types::{Address, Signature};
+use kzg_ceremony_crypto::{
+    get_pot_pubkeys,
+    signature::receipt::{verify_legacy_receipt, ContributionReceipt},
+    BatchContribution, BatchTranscript, CeremonyParameters, DefaultEngine, ErrorCode, Identity,
+    Secret, SignaturePolicy,
+};
+use std::{
+    ffi::{CStr, CString},
+    fmt::Display,
+    os::raw::c_char,
+    panic::{self, UnwindSafe},
+    ptr,
+};
+use strum::IntoStaticStr;
+use thiserror::Error;
+
+/// Errors in the use of the C interface itself.
+#[derive(Clone, Debug, Error, IntoStaticStr)]
+pub enum FfiError {
+    #[error("argument `{0}` is NULL")]
+    NullPointer(&'static str),
+    #[error("argument `{0}` is not valid UTF-8")]
+    InvalidUtf8(&'static str),
+    #[error("invalid JSON: {0}")]
+    InvalidJson(String),
+    #[error("invalid signature: {0}")]
+    InvalidSignature(String),
+    #[error("unexpected panic")]
+    Panic,
+}
+
+impl ErrorCode for FfiError {
+    fn to_error_code(&self) -> String {
+        format!("FfiError::{}", <&str>::from(self))
+    }
+}
+
+impl From<serde_json::Error> for FfiError {
+    fn from(error: serde_json::Error) -> Self {
+        Self::InvalidJson(error.to_string())
+    }
+}
+
+/// An error returned to C, with the error code and message of a Rust error.
+pub struct KzgError {
+    code:    CString,
+    message: CString,
+}
+
+impl<E: ErrorCode + Display> From<E> for KzgError {
+    fn from(error: E) -> Self {
+        Self {
+            code:    c_string(error.to_error_code()),
+            message: c_string(error.to_string()),
+        }
+    }
+}
+
+/// Opaque handle to a [`BatchTranscript`].
+pub struct KzgTranscript(BatchTranscript);
+
+/// Opaque handle to a [`BatchContribution`].
+pub struct KzgContribution(BatchContribution);
+
+/// The [`SignaturePolicy`] of [`kzg_transcript_verify_add`].
+#[repr(C)]
+#[derive(Clone, Copy, Debug)]
+pub struct KzgSignaturePolicy {
+    pub require_bls:   bool,
+    pub require_ecdsa: bool,
+}
+
+impl From<KzgSignaturePolicy> for SignaturePolicy {
+    fn from(policy: KzgSignaturePolicy) -> Self {
+        Self {
+            require_bls:   policy.require_bls,
+            require_ecdsa: policy.require_ecdsa,
+        }
+    }
+}
+
+/// Converts a string without interior NUL bytes, which JSON and error
+/// messages do not contain.
+fn c_string(string: String) -> CString {
+    CString::new(string).unwrap_or_default()
+}
+
+/// Runs `f`, returning its error or panic as an owned [`KzgError`].
+fn guard<F>(f: F) -> *mut KzgError
+where
+    F: FnOnce() -> Result<(), KzgError> + UnwindSafe,
+{
+    match panic::catch_unwind(f) {
+        Ok(Ok(())) => ptr::null_mut(),
+        Ok(Err(error)) => Box::into_raw(Box::new(error)),
+        Err(_) => Box::into_raw(Box::new(FfiError::Panic.into())),
+    }
+}
+
+unsafe fn str_arg<'a>(string: *const c_char, name: &'static str) -> Result<&'a str, FfiError> {
+    if string.is_null() {
+        return Err(FfiError::NullPointer(name));
+    }
+    CStr::from_ptr(string)
+        .to_str()
+        .map_err(|_| FfiError::InvalidUtf8(name))
+}
+
+unsafe fn ref_arg<'a, T>(value: *const T, name: &'static str) -> Result<&'a T, FfiError> {
+    value.as_ref().ok_or(FfiError::NullPointer(name))
+}
+
+unsafe fn mut_arg<'a, T>(value: *mut T, name: &'static str) -> Result<&'a mut T, FfiError> {
+    value.as_mut().ok_or(FfiError::NullPointer(name))
+}
+
+unsafe fn bytes_arg<'a, const N: usize>(
+    bytes: *const u8,
+    name: &'static str,
+) -> Result<&'a [u8; N], FfiError> {
+    if bytes.is_null() {
+        return Err(FfiError::NullPointer(name));
+    }
+    Ok(&*bytes.cast::<[u8; N]>())
+}
+
+/// Moves `value` to the heap and stores the owning pointer in `out`.
+unsafe fn write_out<T>(out: *mut *mut T, value: T) -> Result<(), FfiError> {
+    if out.is_null() {
+        return Err(FfiError::NullPointer("out"));
+    }
+    *out = Box::into_raw(Box::new(value));
+    Ok(())
+}
+
+unsafe fn write_string(out: *mut *mut c_char, string: String) -> Result<(), FfiError> {
+    if out.is_null() {
+        return Err(FfiError::NullPointer("out"));
+    }
+    *out = c_string(string).into_raw();
+    Ok(())
+}
+
+unsafe fn free<T>(value: *mut T) {
+    if !value.is_null() {
+        drop(Box::from_raw(value));
+    }
+}
+
+/// Returns the error code, such as `CeremonyError::G1PairingFailed`. The string
+/// is owned by `error`.
+///
+/// # Safety
+/// `error` must be an error returned by this library that was not freed.
+#[no_mangle]
+pub unsafe extern "C" fn kzg_error_code(error: *const KzgError) -> *const c_char {
+    (*error).code.as_ptr()
+}
+
+/// Returns the human readable error message. The string is owned by `error`.
+///
+/// # Safety
+/// `error` must be an error returned by this library that was not freed.
+#[no_mangle]
+pub unsafe extern "C" fn kzg_error_message(error: *const KzgError) -> *const c_char {
+    (*error).message.as_ptr()
+}
+
+/// # Safety
+/// `error` must be `NULL` or an error returned by this library that was not
+/// freed.
+#[no_mangle]
+pub unsafe extern "C" fn kzg_error_free(error: *mut KzgError) {
+    free(error);
+}
+
+/// # Safety
+/// `string` must be `NULL` or a string returned by this library that was not
+/// freed.
+#[no_mangle]
+pub unsafe extern "C" fn kzg_string_free(string: *mut c_char) {
+    if !string.is_null() {
+        drop(CString::from_raw(string));
+    }
+}
+
+/// Parses a transcript from JSON.
+///
+/// # Safety
+/// `json` must be a NUL terminated string and `out` must be writable.
+#[no_mangle]
+pub unsafe extern "C" fn kzg_transcript_parse(
+    json: *const c_char,
+    out: *mut *mut KzgTranscript,
+) -> *mut KzgError {
+    guard(|| {
+        let transcript = serde_json::from_str(str_arg(json, "json")?).map_err(FfiError::from)?;
+        Ok(write_out(out, KzgTranscript(transcript))?)
+    })
+}
+
+/// Serializes a transcript to JSON.
+///
+/// # Safety
+/// `transcript` must be a live transcript and `out` must be writable.
+#[no_mangle]
+pub unsafe extern "C" fn kzg_transcript_serialize(
+    transcript: *const KzgTranscript,
+    out: *mut *mut c_char,
+) -> *mut KzgError {
+    guard(|| {
+        let transcript = ref_arg(transcript, "transcript")?;
+        let json = serde_json::to_string(&transcript.0).map_err(FfiError::from)?;
+        Ok(write_string(out, json)?)
+    })
+}
+
+/// Creates the start of a new contribution to `transcript`, as returned by
+/// `/lobby/try_contribute`.
+///
+/// # Safety
+/// `transcript` must be a live transcript and `out` must be writable.
+#[no_mangle]
+pub unsafe extern "C" fn kzg_transcript_contribution(
+    transcript: *const KzgTranscript,
+    out: *mut *mut KzgContribution,
+) -> *mut KzgError {
+    guard(|| {
+        let transcript = ref_arg(transcript, "transcript")?;
+        Ok(write_out(
+            out,
+            KzgContribution(transcript.0.contribution()),
+        )?)
+    })
+}
+
+/// Verifies `contribution` by `identity` and adds it to `transcript`. The
+/// transcript is unchanged if an error is returned.
+///
+/// # Safety
+/// `transcript` and `contribution` must be live objects and `identity` a NUL
+/// terminated string.
+#[no_mangle]
+pub unsafe extern "C" fn kzg_transcript_verify_add(
+    transcript: *mut KzgTranscript,
+    contribution: *const KzgContribution,
+    identity: *const c_char,
+    policy: KzgSignaturePolicy,
+) -> *mut KzgError {
+    guard(|| {
+        let transcript = mut_arg(transcript, "transcript")?;
+        let contribution = ref_arg(contribution, "contribution")?;
+        let identity: Identity = str_arg(identity, "identity")?.parse()?;
+        transcript.0.verify_add::<DefaultEngine>(
+            contribution.0.clone(),
+            identity,
+            policy.into(),
+        )?;
+        Ok(())
+    })
+}
+
+/// # Safety
+/// `transcript` must be `NULL` or a transcript returned by this library that
+/// was not freed.
+#[no_mangle]
+pub unsafe extern "C" fn kzg_transcript_free(transcript: *mut KzgTranscript) {
+    free(transcript);
+}
+
+/// Parses a contribution from JSON.
+///
+/// # Safety
+/// `json` must be a NUL terminated string and `out` must be writable.
+#[no_mangle]
+pub unsafe extern "C" fn kzg_contribution_parse(
+    json: *const c_char,
+    out: *mut *mut KzgContribution,
+) -> *mut KzgError {
+    guard(|| {
+        let contribution = serde_json::from_str(str_arg(json, "json")?).map_err(FfiError::from)?;
+        Ok(write_out(out, KzgContribution(contribution))?)
+    })
+}
+
+/// Serializes a contribution to JSON.
+///
+/// # Safety
+/// `contribution` must be a live contribution and `out` must be writable.
+#[no_mangle]
+pub unsafe extern "C" fn kzg_contribution_serialize(
+    contribution: *const KzgContribution,
+    out: *mut *mut c_char,
+) -> *mut KzgError {
+    guard(|| {
+        let contribution = ref_arg(contribution, "contribution")?;
+        let json = serde_json::to_string(&contribution.0).map_err(FfiError::from)?;
+        Ok(write_string(out, json)?)
+    })
+}
+
+/// Adds 32 bytes of `entropy` to `contribution` and signs `identity` with it.
+/// `parameters` is the JSON of the ceremony parameters, or `NULL` for mainnet.
+///
+/// # Safety
+/// `contribution` must be a live contribution, `entropy` must point to 32
+/// bytes, and `identity` and `parameters`, unless `NULL`, must be NUL
+/// terminated strings.
+#[no_mangle]
+pub unsafe extern "C" fn kzg_contribution_add_entropy(
+    contribution: *mut KzgContribution,
+    entropy: *const u8,
+    identity: *const c_char,
+    parameters: *const c_char,
+) -> *mut KzgError {
+    guard(|| {
+        let contribution = mut_arg(contribution, "contribution")?;
+        let entropy = Secret::new(*bytes_arg::<32>(entropy, "entropy")?);
+        let identity: Identity = str_arg(identity, "identity")?.parse()?;
+        let parameters = parameters_arg(parameters)?;
+        contribution
+            .0
+            .add_entropy::<DefaultEngine>(&entropy, &identity, &parameters)?;
+        Ok(())
+    })
+}
+
+/// # Safety
+/// `contribution` must be `NULL` or a contribution returned by this library
+/// that was not freed.
+#[no_mangle]
+pub unsafe extern "C" fn kzg_contribution_free(contribution: *mut KzgContribution) {
+    free(contribution);
+}
+
+/// Returns the JSON array of `potPubkey`s that
+/// [`kzg_contribution_add_entropy`] derives from 32 bytes of `entropy`.
+///
+/// # Safety
+/// `entropy` must point to 32 bytes and `out` must be writable.
+#[no_mangle]
+pub unsafe extern "C" fn kzg_get_pot_pubkeys(
+    entropy: *const u8,
+    out: *mut *mut c_char,
+) -> *mut KzgError {
+    guard(|| {
+        let entropy = Secret::new(*bytes_arg::<32>(entropy, "entropy")?);
+        let pubkeys = get_pot_pubkeys::<DefaultEngine>(&entropy);
+        let json = serde_json::to_string(&pubkeys).map_err(FfiError::from)?;
+        Ok(write_string(out, json)?)
+    })
+}
+
+/// Checks that the JSON `receipt` is signed with the hex encoded `signature`
+/// by the 20 byte address `sequencer`, in the ceremony with `parameters`.
+/// `parameters` is the JSON of the ceremony parameters, or `NULL` for mainnet.
+///
+/// # Safety
+/// `receipt` and `signature` must be NUL terminated strings, `sequencer`
+/// must point to 20 bytes, and `parameters` must be `NULL` or a NUL
+/// terminated string.
+#[no_mangle]
+pub unsafe extern "C" fn kzg_receipt_verify(
+    receipt: *const c_char,
+    signature: *const c_char,
+    sequencer: *const u8,
+    parameters: *const c_char,
+) -> *mut KzgError {
+    guard(|| {
+        let receipt: ContributionReceipt =
+            serde_json::from_str(str_arg(receipt, "receipt")?).map_err(FfiError::from)?;
+        let signature = signature_arg(signature)?;
+        let sequencer = Address::from(*bytes_arg::<20>(sequencer, "sequencer")?);
+        let parameters = parameters_arg(parameters)?;
+        receipt.verify(&signature, sequencer, &parameters)?;
+        Ok(())
+    })
+}
+
+/// Checks a receipt in the legacy format, where the exact string `message` is
+/// signed with `personal_sign`.
+///
+/// # Safety
+/// `message` and `signature` must be NUL terminated strings and `sequencer`
+/// must point to 20 bytes.
+#[no_mangle]
+pub unsafe extern "C" fn kzg_legacy_receipt_verify(
+    message: *const c_char,
+    signature: *const c_char,
+    sequencer: *const u8,
+) -> *mut KzgError {
+    guard(|| {
+        let message = str_arg(message, "message")?;
+        let signature = signature_arg(signature)?;
+        let sequencer = Address::from(*bytes_arg::<20>(sequencer, "sequencer")?);
+        verify_legacy_receipt(message, &signature, sequencer)?;
+        Ok(())
+    })
+}
+
+unsafe fn parameters_arg(parameters: *const c_char) -> Result<CeremonyParameters, FfiError> {
+    if parameters.is_null() {
+        Ok(CeremonyParameters::mainnet())
+    } else {
+        Ok(serde_json::from_str(str_arg(parameters, "parameters")?)?)
+    }
+}
+
+unsafe fn signature_arg(signature: *const c_char) -> Result<Signature, FfiError> {
+    str_arg(signature, "signature")?
+        .parse()
+        .map_err(|e: ethers_core::types::SignatureError| FfiError::InvalidSignature(e.to_string()))
+}
@@ -0,0 +1,184 @@
+/*
+ * Contributes to and verifies a small transcript through the C interface.
+ *
+ * Usage: c_api <transcript.json>
+ */
+
+#include "kzg_ceremony_crypto.h"
+
+#include <stdio.h>
+#include <stdlib.h>
+#include <string.h>
+
+/* Fails the test if `call` returns an error. */
+#define CHECK_OK(call)                                                         \
+    do {                                                                       \
+        KzgError *error = (call);                                              \
+        if (error != NULL) {                                                   \
+            fprintf(stderr, "%s:%d: %s failed with %s: %s\n", __FILE__,        \
+                    __LINE__, #call, kzg_error_code(error),                    \
+                    kzg_error_message(error));                                 \
+            kzg_error_free(error);                                             \
+            exit(1);                                                           \
+        }                                                                      \
+    } while (0)
+
+/* Fails the test unless `call` returns an error with code `expected`. */
+#define CHECK_ERROR(call, expected)                                            \
+    do {                                                                       \
+        KzgError *error = (call);                                              \
+        if (error == NULL) {                                                   \
+            fprintf(stderr, "%s:%d: %s succeeded, expected %s\n", __FILE__,    \
+                    __LINE__, #call, expected);                                \
+            exit(1);                                                           \
+        }                                                                      \
+        if (strcmp(kzg_error_code(error), expected) != 0) {                    \
+            fprintf(stderr, "%s:%d: %s failed with %s, expected %s\n",         \
+                    __FILE__, __LINE__, #call, kzg_error_code(error),          \
+                    expected);                                                 \
+            exit(1);                                                           \
+        }                                                                      \
+        kzg_error_free(error);                                                 \
+    } while (0)
+
+#define CHECK(condition)                                                       \
+    do {                                                                       \
+        if (!(condition)) {                                                    \
+            fprintf(stderr, "%s:%d: %s is false\n", __FILE__, __LINE__,        \
+                    #condition);                                               \
+            exit(1);                                                           \
+        }                                                                      \
+    } while (0)
+
+static const char *IDENTITY = "git|1234|test_user";
+
+/* Receipt signed by the key 0x00…01. */
+static const char *RECEIPT =
+    "{\"identity\":\"git|1234|test_user\",\"potPubkeys\":[\"0x93e02b6052719f"
+    "607dacd3a088274f65596bd0d09920b61ab5da61bbdc7f5049334cf11213945d57e5ac7d"
+    "055d042b7e024aa2b2f08f0a91260805272dc51051c6e47ad4fa403b02b4510b647ae3d1"
+    "770bac0326a805bbefd48056c8c121bdb8\"],\"transcriptIndex\":1,"
+    "\"transcriptHash\":\"0xabababababababababababababababababababababababab"
+    "abababababababab\",\"timestamp\":1667000000}";
+static const char *RECEIPT_SIGNATURE =
+    "ed2c11b6fbb2d06d910e1ce6e1c6f40524d2544b2d923bd3fbe56a5280c7988d43106b37"
+    "b90d87f78ae22b2452d2b505855daffbac792c7d4ab6ec00c1b194901b";
+static const char *TESTNET_PARAMETERS =
+    "{\"eip712Name\":\"Ethereum KZG Ceremony Testnet\",\"eip712Version\":"
+    "\"1.0\",\"chainId\":5,\"blsCipherSuite\":"
+    "\"BLS_SIG_BLS12381G1_XMD:SHA-256_SSWU_RO_POP_\"}";
+static const uint8_t SEQUENCER[20] = {0x7e, 0x5f, 0x45, 0x52, 0x09, 0x1a, 0x69,
+                                      0x12, 0x5d, 0x5d, 0xfc, 0xb7, 0xb8, 0xc2,
+                                      0x65, 0x90, 0x29, 0x39, 0x5b, 0xdf};
+
+static char *read_file(const char *path) {
+    FILE *file = fopen(path, "rb");
+    CHECK(file != NULL);
+    CHECK(fseek(file, 0, SEEK_END) == 0);
+    long size = ftell(file);
+    CHECK(size >= 0);
+    rewind(file);
+    char *contents = malloc((size_t)size + 1);
+    CHECK(contents != NULL);
+    CHECK(fread(contents, 1, (size_t)size, file) == (size_t)size);
+    contents[size] = '\0';
+    fclose(file);
+    return contents;
+}
+
+static void test_contribute(const char *transcript_json) {
+    KzgTranscript *transcript = NULL;
+    CHECK_OK(kzg_transcript_parse(transcript_json, &transcript));
+
+    uint8_t entropy[32];
+    for (int i = 0; i < 32; i++) {
+        entropy[i] = (uint8_t)i;
+    }
+    char *pubkeys = NULL;
+    CHECK_OK(kzg_get_pot_pubkeys(entropy, &pubkeys));
+    CHECK(pubkeys[0] == '[');
+
+    /* Contribute, and round trip the contribution through JSON. */
+    KzgContribution *contribution = NULL;
+    CHECK_OK(kzg_transcript_contribution(transcript, &contribution));
+    CHECK_OK(kzg_contribution_add_entropy(contribution, entropy, IDENTITY, NULL));
+    char *contribution_json = NULL;
+    CHECK_OK(kzg_contribution_serialize(contribution, &contribution_json));
+    kzg_contribution_free(contribution);
+    contribution = NULL;
+    CHECK_OK(kzg_contribution_parse(contribution_json, &contribution));
+
+    /* The pubkeys are those derived from the entropy. */
+    char first_pubkey[2 + 2 * 96 + 1];
+    CHECK(strlen(pubkeys) > sizeof(first_pubkey));
+    memcpy(first_pubkey, pubkeys + 2, sizeof(first_pubkey) - 1);
+    first_pubkey[sizeof(first_pubkey) - 1] = '\0';
+    CHECK(strstr(contribution_json, first_pubkey) != NULL);
+
+    KzgSignaturePolicy strict = {.require_bls = true, .require_ecdsa = true};
+    CHECK_OK(kzg_transcript_verify_add(transcript, contribution, IDENTITY, strict));
+
+    /* The same entropy can not be added twice. */
+    CHECK_ERROR(kzg_transcript_verify_add(transcript, contribution, IDENTITY, strict),
+                "CeremonyError::ReusedPubkey");
+
+    char *serialized = NULL;
+    CHECK_OK(kzg_transcript_serialize(transcript, &serialized));
+    CHECK(strstr(serialized, IDENTITY) != NULL);
+
+    kzg_string_free(serialized);
+    kzg_string_free(contribution_json);
+    kzg_string_free(pubkeys);
+    kzg_contribution_free(contribution);
+    kzg_transcript_free(transcript);
+}
+
+static void test_errors(const char *transcript_json) {
+    KzgTranscript *transcript = NULL;
+    CHECK_ERROR(kzg_transcript_parse("{}", &transcript), "FfiError::InvalidJson");
+    CHECK(transcript == NULL);
+    CHECK_ERROR(kzg_transcript_parse(NULL, &transcript), "FfiError::NullPointer");
+
+    CHECK_OK(kzg_transcript_parse(transcript_json, &transcript));
+    KzgContribution *contribution = NULL;
+    CHECK_OK(kzg_transcript_contribution(transcript, &contribution));
+    uint8_t entropy[32] = {0};
+    CHECK_ERROR(kzg_contribution_add_entropy(contribution, entropy, "foo|bar", NULL),
+                "IdentityError::UnsupportedType");
+    KzgSignaturePolicy permissive = {.require_bls = false, .require_ecdsa = false};
+    CHECK_ERROR(kzg_transcript_verify_add(transcript, contribution, IDENTITY, permissive),
+                "CeremoniesError::DuplicatePubkey");
+
+    kzg_contribution_free(contribution);
+    kzg_transcript_free(transcript);
+    kzg_error_free(NULL);
+    kzg_string_free(NULL);
+}
+
+static void test_receipt(void) {
+    CHECK_OK(kzg_receipt_verify(RECEIPT, RECEIPT_SIGNATURE, SEQUENCER, NULL));
+
+    uint8_t other[20] = {0};
+    CHECK_ERROR(kzg_receipt_verify(RECEIPT, RECEIPT_SIGNATURE, other, NULL),
+                "ReceiptError::InvalidSignature");
+    CHECK_ERROR(kzg_receipt_verify(RECEIPT, "0x1234", SEQUENCER, NULL),
+                "FfiError::InvalidSignature");
+    CHECK_ERROR(kzg_receipt_verify(RECEIPT, RECEIPT_SIGNATURE, SEQUENCER,
+                                   TESTNET_PARAMETERS),
+                "ReceiptError::InvalidSignature");
+    CHECK_ERROR(kzg_legacy_receipt_verify(RECEIPT, RECEIPT_SIGNATURE, SEQUENCER),
+                "ReceiptError::InvalidSignature");
+}
+
+int main(int argc, char **argv) {
+    if (argc != 2) {
+        fprintf(stderr, "usage: %s <transcript.json>\n", argv[0]);
+        return 2;
+    }
+    char *transcript_json = read_file(argv[1]);
+    test_contribute(transcript_json);
+    test_errors(transcript_json);
+    test_receipt();
+    free(transcript_json);
+    return 0;
+}
@@ -0,0 +1,44 @@
+//! Compiles `c_api.c` against the shared library and runs it.
+#![cfg(target_os = "linux")]
+
+use std::{env, path::Path, process::Command};
+
+#[test]
+fn c_api() {
+    let manifest_dir = Path::new(env!("CARGO_MANIFEST_DIR"));
+    let program = Path::new(env!("CARGO_TARGET_TMPDIR")).join("c_api");
+
+    // Cargo does not build the shared library for tests. The test binary is
+    // in `target/<profile>/deps`, the library goes to `target/<profile>`.
+    let exe = env::current_exe().unwrap();
+    let lib_dir = exe.parent().unwrap().parent().unwrap();
+    let mut cargo = Command::new(env!("CARGO"));
+    cargo
+        .args(["build", "--lib", "--manifest-path"])
+        .arg(manifest_dir.join("Cargo.toml"));
+    if lib_dir.ends_with("release") {
+        cargo.arg("--release");
+    }
+    let status = cargo.status().unwrap();
+    assert!(status.success(), "building the library failed");
+
+    let status = Command::new(env::var("CC").unwrap_or_else(|_| "cc".to_string()))
+        .args(["-std=c99", "-Wall", "-Wextra", "-Werror", "-I"])
+        .arg(manifest_dir.join("include"))
+        .arg(manifest_dir.join("tests/c_api.c"))
+        .arg("-L")
+        .arg(lib_dir)
+        .arg(format!("-Wl,-rpath,{}", lib_dir.display()))
+        .arg("-lkzg_ceremony_crypto_ffi")
+        .arg("-o")
+        .arg(&program)
+        .status()
+        .expect("C compiler should run");
+    assert!(status.success(), "compiling c_api.c failed");
+
+    let status = Command::new(&program)
+        .arg(manifest_dir.join("tests/transcript.json"))
+        .status()
+        .unwrap();
+    assert!(status.success(), "c_api failed");
+}
@@ -1,10 +1,13 @@
 use crate::{
+    beacon::beacon_taus,
+    parallel::prelude::*,
+    progress::{Stage, Tracker},
     signature::{identity::Identity, EcdsaSignature},
-    CeremoniesError, Contribution, Engine, Entropy, Tau, G2,
+    CancellationToken, CeremoniesError, CeremonyParameters, Contribution, Engine, Entropy,
+    Progress, ProgressSink, Tau, G2,
 };
 use rand::{Rng, SeedableRng};
 use rand_chacha::ChaCha20Rng;
-use rayon::prelude::*;
 use secrecy::{ExposeSecret, Secret};
 use serde::{Deserialize, Serialize};
 use tracing::instrument;
@@ -22,12 +25,49 @@ impl BatchContribution {
         self.contributions.iter().map(|c| c.pot_pubkey).collect()
     }
 
-    #[instrument(level = "info", skip_all, fields(n=self.contributions.len()))]
+    /// Adds entropy to all contributions and signs `identity` with the BLS
+    /// ciphersuite of `parameters`.
+    ///
+    /// # Errors
+    /// Returns an error if any of the powers is not a valid curve point.
     pub fn add_entropy<E: Engine>(
         &mut self,
         entropy: &Entropy,
         identity: &Identity,
+        parameters: &CeremonyParameters,
+    ) -> Result<(), CeremoniesError> {
+        self.add_entropy_with::<E>(
+            entropy,
+            identity,
+            parameters,
+            &|_: Progress| (),
+            &CancellationToken::default(),
+        )
+    }
+
+    /// Like [`BatchContribution::add_entropy`], but reports the number of
+    /// powers processed across all contributions to `progress`, and stops once
+    /// `cancel` is cancelled. A cancelled contribution is left partially
+    /// updated and must be discarded.
+    ///
+    /// # Errors
+    /// Returns [`CeremoniesError::Cancelled`] if `cancel` is cancelled, or an
+    /// error if any of the powers is not a valid curve point.
+    #[instrument(level = "info", skip_all, fields(n=self.contributions.len()))]
+    pub fn add_entropy_with<E: Engine>(
+        &mut self,
+        entropy: &Entropy,
+        identity: &Identity,
+        parameters: &CeremonyParameters,
+        progress: &dyn ProgressSink,
+        cancel: &CancellationToken,
     ) -> Result<(), CeremoniesError> {
+        let total = self
+            .contributions
+            .iter()
+            .map(Contribution::num_points)
+            .sum();
+        let tracker = Tracker::new(Stage::AddTau, total, progress, cancel);
         let taus = derive_taus::<E>(entropy, self.contributions.len());
         let res = self
             .contributions
@@ -36,8 +76,37 @@ impl BatchContribution {
             .enumerate()
             .try_for_each(|(i, (contribution, tau))| {
                 contribution
-                    .add_tau::<E>(tau, identity)
-                    .map_err(|e| CeremoniesError::InvalidCeremony(i, e))
+                    .add_tau_tracked::<E>(tau, identity, parameters, &tracker)
+                    .map_err(|e| CeremoniesError::in_ceremony(i, e))
+            });
+        res
+    }
+
+    /// Finalizes the ceremony with a random beacon: adds the $τ$ derived from
+    /// `beacon` to all contributions, see [`crate::beacon`], and signs the
+    /// beacon identity with the BLS ciphersuite of `parameters`.
+    ///
+    /// # Errors
+    /// Returns [`CeremoniesError::NotABeacon`] if `beacon` is not an
+    /// [`Identity::Beacon`], or an error if any of the powers is not a valid
+    /// curve point.
+    #[instrument(level = "info", skip_all, fields(n=self.contributions.len()))]
+    pub fn add_beacon<E: Engine>(
+        &mut self,
+        beacon: &Identity,
+        parameters: &CeremonyParameters,
+    ) -> Result<(), CeremoniesError> {
+        let taus =
+            beacon_taus(beacon, self.contributions.len()).ok_or(CeremoniesError::NotABeacon)?;
+        let res = self
+            .contributions
+            .par_iter_mut()
+            .zip(&taus)
+            .enumerate()
+            .try_for_each(|(i, (contribution, tau))| {
+                contribution
+                    .add_tau::<E>(tau, beacon, parameters)
+                    .map_err(|e| CeremoniesError::in_ceremony(i, e))
             });
         res
     }
@@ -92,12 +161,14 @@ pub mod tests {
         engine::tests::arb_entropy,
         get_pot_pubkeys,
         signature::EcdsaSignature,
-        BatchContribution, CeremoniesError, DefaultEngine, G2,
+        BatchContribution, BatchTranscript, CancellationToken, CeremoniesError, DefaultEngine,
+        Identity, Progress, G2,
     };
     use ark_bls12_381::{Fr, G2Affine};
     use ark_ec::{AffineCurve, ProjectiveCurve};
     use proptest::proptest;
     use secrecy::{ExposeSecret, Secret};
+    use std::sync::atomic::{AtomicUsize, Ordering};
 
     #[test]
     fn test_validate() {
@@ -121,6 +192,46 @@ pub mod tests {
         assert!(valid.validate::<DefaultEngine>().is_ok());
     }
 
+    #[test]
+    fn test_add_entropy_with() {
+        let transcript = BatchTranscript::new([(4, 2), (8, 2)].iter());
+        let entropy = Secret::new([3; 32]);
+        let mut expected = transcript.contribution();
+        expected
+            .add_entropy::<DefaultEngine>(&entropy, &Identity::None, &transcript.parameters)
+            .unwrap();
+
+        let done = AtomicUsize::new(0);
+        let mut contribution = transcript.contribution();
+        contribution
+            .add_entropy_with::<DefaultEngine>(
+                &entropy,
+                &Identity::None,
+                &transcript.parameters,
+                &|progress: Progress| {
+                    assert_eq!(progress.total, 16);
+                    done.fetch_max(progress.done, Ordering::Relaxed);
+                },
+                &CancellationToken::new(),
+            )
+            .unwrap();
+        assert_eq!(contribution, expected);
+        assert_eq!(done.into_inner(), 16);
+
+        let cancel = CancellationToken::new();
+        cancel.cancel();
+        assert_eq!(
+            transcript.contribution().add_entropy_with::<DefaultEngine>(
+                &entropy,
+                &Identity::None,
+                &transcript.parameters,
+                &|_: Progress| (),
+                &cancel,
+            ),
+            Err(CeremoniesError::Cancelled)
+        );
+    }
+
     #[test]
     fn test_get_pot_pubkeys() {
         proptest!(|(entropy in arb_entropy())| {
@@ -150,8 +261,8 @@ pub mod bench {
     use super::*;
     use crate::{
         bench::{rand_entropy, BATCH_SIZE},
-        signature::identity::Identity,
-        Arkworks, BatchTranscript, Both, BLST,
+        signature::{identity::Identity, SignaturePolicy},
+        Arkworks, BatchTranscript, Both, CeremonyParameters, BLST,
     };
     use criterion::{BatchSize, Criterion};
 
@@ -170,14 +281,15 @@ pub mod bench {
             let mut transcript = BatchTranscript::new(BATCH_SIZE.iter());
             let mut contribution = transcript.contribution();
             contribution
-                .add_entropy::<E>(&rand_entropy(), &Identity::None)
+                .add_entropy::<E>(&rand_entropy(), &Identity::None, &transcript.parameters)
                 .unwrap();
             transcript
-                .verify_add::<E>(contribution, Identity::None)
+                .verify_add::<E>(contribution, Identity::None, SignaturePolicy::default())
                 .unwrap();
             transcript
         };
 
+        let parameters = CeremonyParameters::default();
         criterion.bench_function(
             &format!("batch_contribution/{name}/add_tau"),
             move |bencher| {
@@ -185,7 +297,7 @@ pub mod bench {
                     || (transcript.contribution(), rand_entropy()),
                     |(mut contribution, entropy)| {
                         contribution
-                            .add_entropy::<E>(&entropy, &Identity::None)
+                            .add_entropy::<E>(&entropy, &Identity::None, &parameters)
                             .unwrap();
                     },
                     BatchSize::LargeInput,
@@ -1,9 +1,16 @@
 use crate::{
-    signature::{identity::Identity, ContributionTypedData, EcdsaSignature},
-    BatchContribution, CeremoniesError, Engine, Transcript,
+    beacon::beacon_taus,
+    parallel::prelude::*,
+    progress::{Stage, Tracker},
+    signature::{
+        identity::Identity, report::SignatureReport, ContributionTypedData, EcdsaSignature,
+        SignaturePolicy,
+    },
+    BatchContribution, CancellationToken, CeremoniesError, CeremonyError, CeremonyParameters,
+    Engine, Powers, Progress, ProgressSink, Transcript, G2,
 };
-use rayon::prelude::*;
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 use tracing::instrument;
 
 #[derive(Clone, PartialEq, Eq, Debug, Serialize, Deserialize)]
@@ -12,29 +19,102 @@ pub struct BatchTranscript {
     pub transcripts:                  Vec<Transcript>,
     pub participant_ids:              Vec<Identity>,
     pub participant_ecdsa_signatures: Vec<EcdsaSignature>,
+    /// The parameters all signatures in the transcript are bound to. Omitted
+    /// for the mainnet parameters, so mainnet transcripts keep the format of
+    /// the specification.
+    #[serde(default, skip_serializing_if = "CeremonyParameters::is_mainnet")]
+    pub parameters:                   CeremonyParameters,
 }
 
 impl BatchTranscript {
+    /// Creates an empty transcript for the mainnet ceremony.
     pub fn new<'a, I>(iter: I) -> Self
+    where
+        I: IntoIterator<Item = &'a (usize, usize)> + 'a,
+    {
+        Self::with_parameters(iter, CeremonyParameters::mainnet())
+    }
+
+    /// Creates an empty transcript for a ceremony with `parameters`.
+    pub fn with_parameters<'a, I>(iter: I, parameters: CeremonyParameters) -> Self
     where
         I: IntoIterator<Item = &'a (usize, usize)> + 'a,
     {
         Self {
-            transcripts:                  iter
+            transcripts: iter
                 .into_iter()
                 .map(|(num_g1, num_g2)| Transcript::new(*num_g1, *num_g2))
                 .collect(),
-            participant_ids:              vec![Identity::None],
+            participant_ids: vec![Identity::None],
             participant_ecdsa_signatures: vec![EcdsaSignature::empty()],
+            parameters,
         }
     }
 
+    /// Creates a transcript for a ceremony with `parameters` that starts from
+    /// powers imported from an earlier ceremony, see [`crate::import`]. Each
+    /// sub-ceremony takes the first of the imported `powers`, and
+    /// `provenance` is recorded as the first participant.
+    ///
+    /// # Errors
+    /// Returns an error if there are not enough imported powers for a
+    /// sub-ceremony, or they are not valid powers of a single $τ$.
+    #[instrument(level = "info", skip_all, fields(n1=powers.g1.len(), n2=powers.g2.len()))]
+    pub fn import<E: Engine>(
+        sizes: &[(usize, usize)],
+        parameters: CeremonyParameters,
+        powers: &Powers,
+        provenance: Identity,
+    ) -> Result<Self, CeremoniesError> {
+        let transcripts = sizes
+            .par_iter()
+            .enumerate()
+            .map(|(i, &(num_g1, num_g2))| {
+                let g1 = powers
+                    .g1
+                    .get(..num_g1)
+                    .ok_or(CeremonyError::UnexpectedNumG1Powers(
+                        num_g1,
+                        powers.g1.len(),
+                    ));
+                let g2 = powers
+                    .g2
+                    .get(..num_g2)
+                    .ok_or(CeremonyError::UnexpectedNumG2Powers(
+                        num_g2,
+                        powers.g2.len(),
+                    ));
+                g1.and_then(|g1| {
+                    g2.map(|g2| Powers {
+                        g1: g1.to_vec(),
+                        g2: g2.to_vec(),
+                    })
+                })
+                .and_then(Transcript::import::<E>)
+                .map_err(|e| CeremoniesError::InvalidCeremony(i, e))
+            })
+            .collect::<Result<_, _>>()?;
+        Ok(Self {
+            transcripts,
+            participant_ids: vec![provenance],
+            participant_ecdsa_signatures: vec![EcdsaSignature::empty()],
+            parameters,
+        })
+    }
+
     /// Returns the number of participants that contributed to this transcript.
     #[must_use]
     pub fn num_participants(&self) -> usize {
         self.participant_ids.len() - 1
     }
 
+    /// Returns whether the last contribution was derived from a random
+    /// beacon, after which the transcript takes no more contributions.
+    #[must_use]
+    pub fn is_finalized(&self) -> bool {
+        matches!(self.participant_ids.last(), Some(Identity::Beacon { .. }))
+    }
+
     /// Creates the start of a new batch contribution.
     #[must_use]
     pub fn contribution(&self) -> BatchContribution {
@@ -48,14 +128,124 @@ impl BatchTranscript {
         }
     }
 
+    /// Checks all BLS and ECDSA signatures in the transcript.
+    #[must_use]
+    pub fn signature_report<E: Engine>(&self) -> SignatureReport {
+        SignatureReport::new::<E>(self)
+    }
+
+    /// Runs [`Powers::self_test`](crate::Powers::self_test) on every
+    /// sub-ceremony, to check that the powers work for KZG commitments.
+    ///
+    /// # Errors
+    /// Returns the error of the first sub-ceremony that fails.
+    #[instrument(level = "info", skip_all, fields(n=self.transcripts.len()))]
+    pub fn self_test<E: Engine>(&self) -> Result<(), CeremoniesError> {
+        self.transcripts
+            .par_iter()
+            .enumerate()
+            .try_for_each(|(i, transcript)| {
+                transcript
+                    .powers
+                    .self_test::<E>()
+                    .map_err(|e| CeremoniesError::InvalidCeremony(i, e))
+            })
+    }
+
+    /// Runs [`Transcript::verify_witness`] on every sub-ceremony, to check the
+    /// pubkey chain from the starting point to the current powers.
+    ///
+    /// # Errors
+    /// Returns the error of the first sub-ceremony that fails.
+    #[instrument(level = "info", skip_all, fields(n=self.transcripts.len()))]
+    pub fn verify_witnesses<E: Engine>(&self) -> Result<(), CeremoniesError> {
+        self.transcripts
+            .par_iter()
+            .enumerate()
+            .try_for_each(|(i, transcript)| {
+                transcript
+                    .verify_witness::<E>()
+                    .map_err(|e| CeremoniesError::InvalidCeremony(i, e))
+            })
+    }
+
+    /// Derives the $τ$ of every random beacon participant again, see
+    /// [`crate::beacon`], and checks that it matches the participant's
+    /// potPubkey in each sub-ceremony.
+    ///
+    /// # Errors
+    /// Returns [`CeremonyError::InvalidBeaconPubkey`]
+    /// for the first sub-ceremony where a beacon does not match.
+    #[instrument(level = "info", skip_all, fields(n=self.transcripts.len()))]
+    pub fn verify_beacons<E: Engine>(&self) -> Result<(), CeremoniesError> {
+        let beacons =
+            self.participant_ids
+                .iter()
+                .enumerate()
+                .filter_map(|(participant, identity)| {
+                    beacon_taus(identity, self.transcripts.len()).map(|taus| (participant, taus))
+                });
+        for (participant, taus) in beacons {
+            self.transcripts
+                .par_iter()
+                .zip(&taus)
+                .enumerate()
+                .try_for_each(|(i, (transcript, tau))| {
+                    let mut powers = [G2::one(), G2::one()];
+                    E::add_tau_g2(tau, &mut powers)
+                        .map_err(|e| CeremoniesError::InvalidCeremony(i, e))?;
+                    if transcript.witness.pubkeys.get(participant) != Some(&powers[1]) {
+                        return Err(CeremoniesError::InvalidCeremony(
+                            i,
+                            CeremonyError::InvalidBeaconPubkey(participant),
+                        ));
+                    }
+                    Ok(())
+                })?;
+        }
+        Ok(())
+    }
+
     /// Adds a batch contribution to the transcript. The contribution must be
-    /// valid.
-    #[instrument(level = "info", skip_all, fields(n=contribution.contributions.len()))]
+    /// valid, and carry the signatures required by `policy`. Signatures are
+    /// checked against the transcript's [`CeremonyParameters`].
     pub fn verify_add<E: Engine>(
+        &mut self,
+        contribution: BatchContribution,
+        identity: Identity,
+        policy: SignaturePolicy,
+    ) -> Result<(), CeremoniesError> {
+        self.verify_add_with::<E>(
+            contribution,
+            identity,
+            policy,
+            &|_: Progress| (),
+            &CancellationToken::default(),
+        )
+    }
+
+    /// Like [`BatchTranscript::verify_add`], but reports the number of
+    /// verification stages completed to `progress`, and stops between stages
+    /// once `cancel` is cancelled. The transcript is only modified after all
+    /// checks passed, so a cancelled verification leaves it unchanged.
+    ///
+    /// # Errors
+    /// Returns [`CeremoniesError::Finalized`] if the transcript was finalized
+    /// by a random beacon, [`CeremoniesError::Cancelled`] if `cancel` is
+    /// cancelled, or an error if the contribution is invalid.
+    #[instrument(level = "info", skip_all, fields(n=contribution.contributions.len()))]
+    pub fn verify_add_with<E: Engine>(
         &mut self,
         mut contribution: BatchContribution,
         identity: Identity,
+        policy: SignaturePolicy,
+        progress: &dyn ProgressSink,
+        cancel: &CancellationToken,
     ) -> Result<(), CeremoniesError> {
+        if self.is_finalized() {
+            return Err(CeremoniesError::Finalized);
+        }
+
         // Verify contribution count
         if self.transcripts.len() != contribution.contributions.len() {
             return Err(CeremoniesError::UnexpectedNumContributions(
@@ -64,28 +254,59 @@ impl BatchTranscript {
             ));
         }
 
+        // Every sub-ceremony needs its own entropy
+        let mut pubkeys = HashMap::with_capacity(contribution.contributions.len());
+        for (i, contribution) in contribution.contributions.iter().enumerate() {
+            if let Some(first) = pubkeys.insert(contribution.pot_pubkey, i) {
+                return Err(CeremoniesError::DuplicatePubkey(first, i));
+            }
+        }
+
+        // Each contribution is verified in stages, followed by its BLS
+        // signature, and finally the ECDSA signature of the batch.
+        let total = contribution.contributions.len() * (Transcript::VERIFY_STAGES + 1) + 1;
+        let tracker = Tracker::new(Stage::Verify, total, progress, cancel);
+
         // Verify contributions in parallel
+        let message = identity.to_string();
+        let parameters = &self.parameters;
         self.transcripts
             .par_iter_mut()
             .zip(&contribution.contributions)
             .enumerate()
             .try_for_each(|(i, (transcript, contribution))| {
                 transcript
-                    .verify::<E>(contribution)
-                    .map_err(|e| CeremoniesError::InvalidCeremony(i, e))
+                    .verify_tracked::<E>(contribution, &tracker)
+                    .and_then(|()| tracker.check())
+                    .and_then(|()| {
+                        if policy.require_bls {
+                            contribution.bls_signature.verify::<E>(
+                                message.as_bytes(),
+                                contribution.pot_pubkey,
+                                parameters,
+                            )
+                        } else {
+                            Ok(())
+                        }
+                    })
+                    .map(|()| tracker.advance(1))
+                    .map_err(|e| CeremoniesError::in_ceremony(i, e))
             })?;
 
-        self.participant_ecdsa_signatures.push(
-            contribution
-                .ecdsa_signature
-                .prune(&identity, &ContributionTypedData::from(&contribution)),
-        );
+        tracker.check().map_err(|_| CeremoniesError::Cancelled)?;
+        let typed_data = ContributionTypedData::new(&contribution, &self.parameters);
+        if let (true, Identity::Ethereum { address }) = (policy.require_ecdsa, &identity) {
+            contribution.ecdsa_signature.verify(*address, &typed_data)?;
+        }
+        tracker.advance(1);
+        self.participant_ecdsa_signatures
+            .push(contribution.ecdsa_signature.prune(&identity, &typed_data));
 
         // Prune BLS Signatures
         contribution.contributions.iter_mut().for_each(|c| {
-            c.bls_signature = c
-                .bls_signature
-                .prune::<E>(identity.to_string().as_bytes(), c.pot_pubkey);
+            c.bls_signature =
+                c.bls_signature
+                    .prune::<E>(message.as_bytes(), c.pot_pubkey, &self.parameters);
         });
 
         // Add contributions
@@ -106,8 +327,22 @@ impl BatchTranscript {
 #[cfg(test)]
 pub mod tests {
     use crate::{
-        BatchTranscript, CeremoniesError::UnexpectedNumContributions, DefaultEngine, Identity,
+        import::ImportFormat,
+        signature::{BlsSignature, EcdsaSignature},
+        BatchTranscript, CancellationToken,
+        CeremoniesError::{
+            Cancelled, DuplicatePubkey, Finalized, InvalidCeremony, InvalidEcdsaSignature,
+            MissingEcdsaSignature, NotABeacon, UnexpectedNumContributions,
+        },
+        CeremonyError::{
+            InvalidBeaconPubkey, InvalidBlsSignature, InvalidOpeningProof, MissingBlsSignature,
+            ReusedPubkey, UnexpectedNumG1Powers,
+        },
+        CeremonyParameters, DefaultEngine, Engine, Identity, Powers, Progress, Secret,
+        SignaturePolicy, Stage,
     };
+    use ethers_core::types::Signature;
+    use std::sync::Mutex;
 
     #[test]
     fn test_verify_add() {
@@ -115,11 +350,368 @@ pub mod tests {
         let mut contrib = transcript.contribution();
         contrib.contributions = contrib.contributions[0..1].to_vec();
         let result = transcript
-            .verify_add::<DefaultEngine>(contrib, Identity::None)
+            .verify_add::<DefaultEngine>(contrib, Identity::None, SignaturePolicy::default())
             .err()
             .unwrap();
         assert_eq!(result, UnexpectedNumContributions(2, 1));
     }
+
+    #[test]
+    fn test_verify_add_reused_entropy() {
+        let mut transcript = BatchTranscript::new([(2, 2), (3, 3)].iter());
+        let tau = DefaultEngine::generate_tau(&Secret::new([7; 32]));
+
+        // The same tau in both sub-ceremonies
+        let mut contrib = transcript.contribution();
+        for contribution in &mut contrib.contributions {
+            contribution
+                .add_tau::<DefaultEngine>(&tau, &Identity::None, &transcript.parameters)
+                .unwrap();
+        }
+        let result = transcript
+            .clone()
+            .verify_add::<DefaultEngine>(contrib, Identity::None, SignaturePolicy::default())
+            .err()
+            .unwrap();
+        assert_eq!(result, DuplicatePubkey(0, 1));
+
+        // A contribution replayed by a later participant
+        let mut contrib = transcript.contribution();
+        contrib
+            .add_entropy::<DefaultEngine>(
+                &Secret::new([1; 32]),
+                &Identity::None,
+                &transcript.parameters,
+            )
+            .unwrap();
+        let replayed = contrib.clone();
+        transcript
+            .verify_add::<DefaultEngine>(contrib, Identity::None, SignaturePolicy::default())
+            .unwrap();
+        let result = transcript
+            .verify_add::<DefaultEngine>(replayed, Identity::None, SignaturePolicy::default())
+            .err()
+            .unwrap();
+        assert_eq!(result, InvalidCeremony(0, ReusedPubkey(1)));
+    }
+
+    #[test]
+    fn test_verify_add_strict_bls() {
+        let identity = Identity::Github {
+            id:       1234,
+            username: "test_user".to_string(),
+        };
+        let transcript = BatchTranscript::new([(2, 2), (3, 3)].iter());
+        let mut contrib = transcript.contribution();
+        contrib
+            .add_entropy::<DefaultEngine>(&Secret::new([1; 32]), &identity, &transcript.parameters)
+            .unwrap();
+
+        let mut unsigned = contrib.clone();
+        unsigned.contributions[1].bls_signature = BlsSignature::empty();
+        let result = transcript
+            .clone()
+            .verify_add::<DefaultEngine>(
+                unsigned.clone(),
+                identity.clone(),
+                SignaturePolicy::strict(),
+            )
+            .err()
+            .unwrap();
+        assert_eq!(result, InvalidCeremony(1, MissingBlsSignature));
+
+        let other = Identity::Github {
+            id:       5678,
+            username: "other_user".to_string(),
+        };
+        let result = transcript
+            .clone()
+            .verify_add::<DefaultEngine>(contrib.clone(), other, SignaturePolicy::strict())
+            .err()
+            .unwrap();
+        assert!(matches!(result, InvalidCeremony(_, InvalidBlsSignature)));
+
+        // Without the policy the signatures are pruned instead.
+        let mut permissive = transcript.clone();
+        permissive
+            .verify_add::<DefaultEngine>(unsigned, identity.clone(), SignaturePolicy::permissive())
+            .unwrap();
+        assert_eq!(
+            permissive.transcripts[1].witness.signatures[1],
+            BlsSignature::empty()
+        );
+
+        transcript
+            .clone()
+            .verify_add::<DefaultEngine>(contrib, identity, SignaturePolicy::strict())
+            .unwrap();
+    }
+
+    #[test]
+    fn test_verify_add_strict_ecdsa() {
+        let identity = Identity::Ethereum { address: [7; 20] };
+        let transcript = BatchTranscript::new([(2, 2), (3, 3)].iter());
+        let mut contrib = transcript.contribution();
+        contrib
+            .add_entropy::<DefaultEngine>(&Secret::new([1; 32]), &identity, &transcript.parameters)
+            .unwrap();
+        let policy = SignaturePolicy {
+            require_bls:   false,
+            require_ecdsa: true,
+        };
+
+        let result = transcript
+            .clone()
+            .verify_add::<DefaultEngine>(contrib.clone(), identity.clone(), policy)
+            .err()
+            .unwrap();
+        assert_eq!(result, MissingEcdsaSignature);
+
+        contrib.ecdsa_signature = EcdsaSignature(Some(Signature {
+            r: 1.into(),
+            s: 1.into(),
+            v: 27,
+        }));
+        let result = transcript
+            .clone()
+            .verify_add::<DefaultEngine>(contrib.clone(), identity, policy)
+            .err()
+            .unwrap();
+        assert_eq!(result, InvalidEcdsaSignature);
+
+        // Only Ethereum participants can sign with ECDSA.
+        transcript
+            .clone()
+            .verify_add::<DefaultEngine>(contrib, Identity::None, policy)
+            .unwrap();
+    }
+
+    #[test]
+    fn test_verify_add_parameters() {
+        let identity = Identity::Github {
+            id:       1234,
+            username: "test_user".to_string(),
+        };
+        let rehearsal = CeremonyParameters {
+            eip712_name: "Ethereum KZG Ceremony Rehearsal".to_string(),
+            chain_id: 5,
+            bls_cipher_suite: "BLS_SIG_BLS12381G1_XMD:SHA-256_SSWU_RO_POP_REHEARSAL_".to_string(),
+            ..CeremonyParameters::mainnet()
+        };
+        let mut transcript = BatchTranscript::with_parameters([(2, 2)].iter(), rehearsal.clone());
+
+        // Signed for mainnet
+        let mut contrib = transcript.contribution();
+        contrib
+            .add_entropy::<DefaultEngine>(
+                &Secret::new([1; 32]),
+                &identity,
+                &CeremonyParameters::mainnet(),
+            )
+            .unwrap();
+        let result = transcript
+            .clone()
+            .verify_add::<DefaultEngine>(contrib, identity.clone(), SignaturePolicy::strict())
+            .err()
+            .unwrap();
+        assert_eq!(result, InvalidCeremony(0, InvalidBlsSignature));
+
+        let mut contrib = transcript.contribution();
+        contrib
+            .add_entropy::<DefaultEngine>(&Secret::new([1; 32]), &identity, &rehearsal)
+            .unwrap();
+        transcript
+            .verify_add::<DefaultEngine>(contrib, identity, SignaturePolicy::strict())
+            .unwrap();
+
+        // The parameters are recorded, except for mainnet.
+        let json = serde_json::to_value(&transcript).unwrap();
+        assert_eq!(json["parameters"]["chainId"], 5);
+        let loaded: BatchTranscript = serde_json::from_value(json).unwrap();
+        assert_eq!(loaded.parameters, rehearsal);
+        let mainnet = serde_json::to_value(BatchTranscript::new([(2, 2)].iter())).unwrap();
+        assert!(mainnet.get("parameters").is_none());
+    }
+
+    #[test]
+    fn test_verify_add_with() {
+        let mut transcript = BatchTranscript::new([(2, 2), (3, 3)].iter());
+        let mut contrib = transcript.contribution();
+        contrib
+            .add_entropy::<DefaultEngine>(
+                &Secret::new([1; 32]),
+                &Identity::None,
+                &transcript.parameters,
+            )
+            .unwrap();
+
+        // A cancelled verification leaves the transcript unchanged.
+        let cancel = CancellationToken::new();
+        cancel.cancel();
+        let result = transcript.verify_add_with::<DefaultEngine>(
+            contrib.clone(),
+            Identity::None,
+            SignaturePolicy::default(),
+            &|_: Progress| (),
+            &cancel,
+        );
+        assert_eq!(result, Err(Cancelled));
+        assert_eq!(transcript, BatchTranscript::new([(2, 2), (3, 3)].iter()));
+
+        let reports = Mutex::new(Vec::new());
+        transcript
+            .verify_add_with::<DefaultEngine>(
+                contrib,
+                Identity::None,
+                SignaturePolicy::default(),
+                &|progress: Progress| reports.lock().unwrap().push(progress),
+                &CancellationToken::new(),
+            )
+            .unwrap();
+        let reports = reports.into_inner().unwrap();
+        assert_eq!(reports.len(), 7);
+        assert!(reports
+            .iter()
+            .all(|p| p.stage == Stage::Verify && p.total == 7));
+        assert_eq!(reports.iter().map(|p| p.done).max(), Some(7));
+        assert_eq!(transcript.num_participants(), 1);
+    }
+
+    #[test]
+    fn test_self_test() {
+        let mut transcript = BatchTranscript::new([(4, 2), (8, 2)].iter());
+        assert_eq!(transcript.self_test::<DefaultEngine>(), Ok(()));
+
+        let mut contrib = transcript.contribution();
+        contrib
+            .add_entropy::<DefaultEngine>(
+                &Secret::new([1; 32]),
+                &Identity::None,
+                &transcript.parameters,
+            )
+            .unwrap();
+        transcript
+            .verify_add::<DefaultEngine>(contrib, Identity::None, SignaturePolicy::default())
+            .unwrap();
+        assert_eq!(transcript.self_test::<DefaultEngine>(), Ok(()));
+
+        // Powers out of order do not open correctly.
+        transcript.transcripts[1].powers.g1.swap(2, 3);
+        assert_eq!(
+            transcript.self_test::<DefaultEngine>(),
+            Err(InvalidCeremony(1, InvalidOpeningProof))
+        );
+    }
+
+    #[test]
+    fn test_beacon() {
+        let mut transcript = BatchTranscript::new([(4, 2), (8, 2)].iter());
+        let mut contrib = transcript.contribution();
+        contrib
+            .add_entropy::<DefaultEngine>(
+                &Secret::new([1; 32]),
+                &Identity::None,
+                &transcript.parameters,
+            )
+            .unwrap();
+        transcript
+            .verify_add::<DefaultEngine>(contrib, Identity::None, SignaturePolicy::default())
+            .unwrap();
+
+        assert_eq!(
+            transcript
+                .contribution()
+                .add_beacon::<DefaultEngine>(&Identity::None, &transcript.parameters),
+            Err(NotABeacon)
+        );
+
+        let beacon = Identity::beacon("drand-quicknet-1234", &[0xab; 32]).unwrap();
+        let mut contrib = transcript.contribution();
+        contrib
+            .add_beacon::<DefaultEngine>(&beacon, &transcript.parameters)
+            .unwrap();
+        transcript
+            .verify_add::<DefaultEngine>(contrib, beacon, SignaturePolicy::default())
+            .unwrap();
+        assert_eq!(transcript.verify_beacons::<DefaultEngine>(), Ok(()));
+        assert!(transcript.is_finalized());
+
+        // The beacon is the last contribution.
+        let mut contrib = transcript.contribution();
+        contrib
+            .add_entropy::<DefaultEngine>(
+                &Secret::new([2; 32]),
+                &Identity::None,
+                &transcript.parameters,
+            )
+            .unwrap();
+        assert_eq!(
+            transcript.verify_add::<DefaultEngine>(
+                contrib,
+                Identity::None,
+                SignaturePolicy::default()
+            ),
+            Err(Finalized)
+        );
+
+        // A pubkey that was not derived from the beacon is detected.
+        let pubkeys = &mut transcript.transcripts[1].witness.pubkeys;
+        pubkeys[2] = pubkeys[1];
+        assert_eq!(
+            transcript.verify_beacons::<DefaultEngine>(),
+            Err(InvalidCeremony(1, InvalidBeaconPubkey(2)))
+        );
+    }
+
+    #[test]
+    fn test_import() {
+        let tau = DefaultEngine::generate_tau(&Secret::new([5; 32]));
+        let mut powers = Powers::new(8, 3);
+        DefaultEngine::add_tau_g1(&tau, &mut powers.g1).unwrap();
+        DefaultEngine::add_tau_g2(&tau, &mut powers.g2).unwrap();
+        let provenance = ImportFormat::Response.provenance(b"response");
+
+        let mut transcript = BatchTranscript::import::<DefaultEngine>(
+            &[(4, 2), (8, 3)],
+            CeremonyParameters::default(),
+            &powers,
+            provenance.clone(),
+        )
+        .unwrap();
+        assert_eq!(transcript.num_participants(), 0);
+        assert_eq!(transcript.participant_ids, vec![provenance]);
+        assert_eq!(transcript.transcripts[0].powers.g1, powers.g1[..4]);
+        assert_eq!(transcript.verify_witnesses::<DefaultEngine>(), Ok(()));
+
+        let mut contrib = transcript.contribution();
+        contrib
+            .add_entropy::<DefaultEngine>(
+                &Secret::new([1; 32]),
+                &Identity::None,
+                &transcript.parameters,
+            )
+            .unwrap();
+        transcript
+            .verify_add::<DefaultEngine>(contrib, Identity::None, SignaturePolicy::default())
+            .unwrap();
+        assert_eq!(transcript.verify_witnesses::<DefaultEngine>(), Ok(()));
+
+        let json = serde_json::to_string(&transcript).unwrap();
+        assert_eq!(
+            serde_json::from_str::<BatchTranscript>(&json).unwrap(),
+            transcript
+        );
+
+        assert_eq!(
+            BatchTranscript::import::<DefaultEngine>(
+                &[(16, 2)],
+                CeremonyParameters::default(),
+                &powers,
+                Identity::None,
+            ),
+            Err(InvalidCeremony(0, UnexpectedNumG1Powers(16, 8)))
+        );
+    }
 }
 
 #[cfg(feature = "bench")]
@@ -148,10 +740,10 @@ pub mod bench {
             let mut transcript = BatchTranscript::new(BATCH_SIZE.iter());
             let mut contribution = transcript.contribution();
             contribution
-                .add_entropy::<E>(&rand_entropy(), &Identity::None)
+                .add_entropy::<E>(&rand_entropy(), &Identity::None, &transcript.parameters)
                 .unwrap();
             transcript
-                .verify_add::<E>(contribution, Identity::None)
+                .verify_add::<E>(contribution, Identity::None, SignaturePolicy::default())
                 .unwrap();
             transcript
         };
@@ -164,14 +756,22 @@ pub mod bench {
                         (transcript.clone(), {
                             let mut contribution = transcript.contribution();
                             contribution
-                                .add_entropy::<E>(&rand_entropy(), &Identity::None)
+                                .add_entropy::<E>(
+                                    &rand_entropy(),
+                                    &Identity::None,
+                                    &transcript.parameters,
+                                )
                                 .unwrap();
                             contribution
                         })
                     },
                     |(mut transcript, contribution)| {
                         transcript
-                            .verify_add::<E>(contribution, Identity::None)
+                            .verify_add::<E>(
+                                contribution,
+                                Identity::None,
+                                SignaturePolicy::default(),
+                            )
                             .unwrap();
                     },
                     BatchSize::LargeInput,
@@ -0,0 +1,137 @@
+//! Random beacon contributions that finalize a ceremony.
+//!
+//! The last contribution to a ceremony is derived from a public random
+//! beacon, such as a future block hash or a drand round, so that the final
+//! transcript does not depend on the last human participant alone. The beacon
+//! is recorded as an [`Identity::Beacon`] participant, and anyone can derive
+//! its $τ$ again and check it against the transcript with
+//! [`BatchTranscript::verify_beacons`](crate::BatchTranscript::verify_beacons).
+//!
+//! The $τ$ of sub-ceremony $i$ is element $i$ of `hash_to_field(msg, n)` from
+//! [RFC 9380, section 5.2][hash-to-field], where `msg` is the identity of the
+//! beacon as a string, `beacon|<source>|0x<value>`, and `n` is the number of
+//! sub-ceremonies. It uses `expand_message_xmd` with SHA-256 and the domain
+//! separation tag [`BEACON_DST`], and reduces $L = 48$ bytes per element
+//! modulo the order of the scalar field.
+//!
+//! [hash-to-field]: https://www.rfc-editor.org/rfc/rfc9380#section-5.2
+
+use crate::{signature::identity::Identity, Tau, F};
+use ark_bls12_381::Fr;
+use ark_ff::PrimeField;
+use secrecy::Secret;
+use sha2::{Digest, Sha256};
+
+/// Domain separation tag of the beacon hash to field.
+pub const BEACON_DST: &[u8] = b"ETHEREUM_KZG_CEREMONY_BEACON_XMD:SHA-256_";
+
+/// Bytes per field element, for 128 bits of security in the 255 bit scalar
+/// field.
+const L: usize = 48;
+
+/// Derives the $τ$ of each of `n` sub-ceremonies from `beacon`. Returns `None`
+/// if `beacon` is not an [`Identity::Beacon`].
+///
+/// # Panics
+/// Panics if `n` is larger than 170, the most field elements a single
+/// `expand_message_xmd` with SHA-256 produces.
+#[must_use]
+pub fn beacon_taus(beacon: &Identity, n: usize) -> Option<Vec<Tau>> {
+    if !matches!(beacon, Identity::Beacon { .. }) {
+        return None;
+    }
+    let taus = hash_to_field(beacon.to_string().as_bytes(), n)
+        .into_iter()
+        .map(Secret::new)
+        .collect();
+    Some(taus)
+}
+
+fn hash_to_field(msg: &[u8], count: usize) -> Vec<F> {
+    expand_message_xmd(msg, BEACON_DST, count * L)
+        .chunks(L)
+        .map(|chunk| F::from(Fr::from_be_bytes_mod_order(chunk)))
+        .collect()
+}
+
+/// `expand_message_xmd` from RFC 9380, section 5.3.1, with SHA-256.
+fn expand_message_xmd(msg: &[u8], dst: &[u8], len: usize) -> Vec<u8> {
+    let ell = u8::try_from((len + 31) / 32).expect("at most 255 blocks");
+    let dst_prime = [dst, &[u8::try_from(dst.len()).expect("short DST")]].concat();
+    let len = u16::try_from(len).expect("at most 255 blocks of 32 bytes");
+
+    let b0 = Sha256::new()
+        .chain_update([0_u8; 64])
+        .chain_update(msg)
+        .chain_update(len.to_be_bytes())
+        .chain_update([0])
+        .chain_update(&dst_prime)
+        .finalize();
+    let mut bi = Sha256::new()
+        .chain_update(b0)
+        .chain_update([1])
+        .chain_update(&dst_prime)
+        .finalize();
+    let mut uniform = bi.to_vec();
+    for i in 2..=ell {
+        let xor = b0.iter().zip(&bi).map(|(a, b)| a ^ b).collect::<Vec<_>>();
+        bi = Sha256::new()
+            .chain_update(xor)
+            .chain_update([i])
+            .chain_update(&dst_prime)
+            .finalize();
+        uniform.extend_from_slice(&bi);
+    }
+    uniform.truncate(len.into());
+    uniform
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use hex_literal::hex;
+    use secrecy::ExposeSecret;
+
+    /// Test vectors from RFC 9380, appendix K.1.
+    #[test]
+    fn test_expand_message_xmd() {
+        const DST: &[u8] = b"QUUX-V01-CS02-with-expander-SHA256-128";
+        assert_eq!(
+            expand_message_xmd(b"", DST, 0x20),
+            hex!("68a985b87eb6b46952128911f2a4412bbc302a9d759667f87f7a21d803f07235")
+        );
+        assert_eq!(
+            expand_message_xmd(b"abc", DST, 0x20),
+            hex!("d8ccab23b5985ccea865c6c97b6e5b8350e794e603b4b97902f53a8a0d605615")
+        );
+        assert_eq!(
+            expand_message_xmd(b"", DST, 0x80),
+            hex!(
+                "af84c27ccfd45d41914fdff5df25293e221afc53d8ad2ac06d5e3e29485dadbe"
+                "e0d121587713a3e0dd4d5e69e93eb7cd4f5df4cd103e188cf60cb02edc3edf18"
+                "eda8576c412b18ffb658e3dd6ec849469b979d444cf7b26911a08e63cf31f9dc"
+                "c541708d3491184472c2c29bb749d4286b004ceb5ee6b9a7fa5b646c993f0ced"
+            )
+        );
+    }
+
+    #[test]
+    fn test_beacon_taus() {
+        let beacon = Identity::beacon("drand-quicknet-1234", &[0xab; 32]).unwrap();
+        let taus = beacon_taus(&beacon, 4).unwrap();
+        assert_eq!(taus.len(), 4);
+        assert_eq!(
+            taus[0].expose_secret(),
+            &F(hex!(
+                "ea9bc2c25dd5f751749aa428ea7c55aa3f700c2836fbb4ef7499560d3a8e6c4c"
+            ))
+        );
+        assert_eq!(
+            taus[3].expose_secret(),
+            &F(hex!(
+                "146a838f5d67b1f6ca3ad04545391ab7aef89d98aaec6b7604732f7255be3407"
+            ))
+        );
+        assert!(beacon_taus(&Identity::None, 4).is_none());
+    }
+}
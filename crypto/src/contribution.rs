@@ -1,6 +1,8 @@
 use crate::{
+    progress::{Stage, Tracker, CHUNK_SIZE},
     signature::{identity::Identity, BlsSignature},
-    CeremonyError, Engine, Powers, Tau, G2,
+    CancellationToken, CeremonyError, CeremonyParameters, Engine, Powers, Progress, ProgressSink,
+    Tau, G2,
 };
 use serde::{Deserialize, Serialize};
 use tracing::instrument;
@@ -23,20 +25,71 @@ impl Contribution {
 
     /// Adds entropy to this contribution. Can be called multiple times.
     /// The entropy is consumed and the blob is zeroized after use.
-    #[instrument(level = "info", skip_all, , fields(n1=self.powers.g1.len(), n2=self.powers.g2.len()))]
     pub fn add_tau<E: Engine>(
         &mut self,
         tau: &Tau,
         identity: &Identity,
+        parameters: &CeremonyParameters,
+    ) -> Result<(), CeremonyError> {
+        self.add_tau_with::<E>(
+            tau,
+            identity,
+            parameters,
+            &|_: Progress| (),
+            &CancellationToken::default(),
+        )
+    }
+
+    /// Like [`Contribution::add_tau`], but reports the number of powers
+    /// processed to `progress`, and stops between chunks of powers once
+    /// `cancel` is cancelled. A cancelled contribution is left partially
+    /// updated and must be discarded.
+    ///
+    /// # Errors
+    /// Returns [`CeremonyError::Cancelled`] if `cancel` is cancelled, or an
+    /// error if any of the powers is not a valid curve point.
+    pub fn add_tau_with<E: Engine>(
+        &mut self,
+        tau: &Tau,
+        identity: &Identity,
+        parameters: &CeremonyParameters,
+        progress: &dyn ProgressSink,
+        cancel: &CancellationToken,
+    ) -> Result<(), CeremonyError> {
+        let tracker = Tracker::new(Stage::AddTau, self.num_points(), progress, cancel);
+        self.add_tau_tracked::<E>(tau, identity, parameters, &tracker)
+    }
+
+    /// Number of points multiplied by $τ$ in [`Contribution::add_tau`].
+    pub(crate) fn num_points(&self) -> usize {
+        self.powers.g1.len() + self.powers.g2.len()
+    }
+
+    #[instrument(level = "info", skip_all, , fields(n1=self.powers.g1.len(), n2=self.powers.g2.len()))]
+    pub(crate) fn add_tau_tracked<E: Engine>(
+        &mut self,
+        tau: &Tau,
+        identity: &Identity,
+        parameters: &CeremonyParameters,
+        tracker: &Tracker,
     ) -> Result<(), CeremonyError> {
         // Validate points after computation to contribute faster
 
         // Add powers of tau
-        E::add_tau_g1(tau, &mut self.powers.g1)?;
-        E::add_tau_g2(tau, &mut self.powers.g2)?;
+        for (i, chunk) in self.powers.g1.chunks_mut(CHUNK_SIZE).enumerate() {
+            tracker.check()?;
+            E::add_tau_g1_from(tau, i * CHUNK_SIZE, chunk)?;
+            tracker.advance(chunk.len());
+        }
+        for (i, chunk) in self.powers.g2.chunks_mut(CHUNK_SIZE).enumerate() {
+            tracker.check()?;
+            E::add_tau_g2_from(tau, i * CHUNK_SIZE, chunk)?;
+            tracker.advance(chunk.len());
+        }
         let mut temp = [G2::one(), self.pot_pubkey];
         E::add_tau_g2(tau, &mut temp)?;
-        self.bls_signature = BlsSignature::sign::<E>(identity.to_string().as_bytes(), tau);
+        self.bls_signature =
+            BlsSignature::sign::<E>(identity.to_string().as_bytes(), tau, parameters);
         self.pot_pubkey = temp[1];
 
         Ok(())
@@ -58,8 +111,9 @@ pub mod test {
     use super::*;
     use crate::{
         group::tests::{invalid_g1, invalid_g2},
-        DefaultEngine, G1,
+        DefaultEngine, Secret, G1,
     };
+    use std::sync::Mutex;
 
     pub fn valid_contribution() -> Contribution {
         Contribution {
@@ -122,6 +176,51 @@ pub mod test {
         assert!(valid_contribution().validate::<DefaultEngine>().is_ok());
     }
 
+    #[test]
+    fn test_add_tau_with() {
+        let tau = DefaultEngine::generate_tau(&Secret::new([1; 32]));
+        let parameters = CeremonyParameters::mainnet();
+        let mut contribution = Contribution {
+            powers:        Powers::new(CHUNK_SIZE + 1, 2),
+            pot_pubkey:    G2::one(),
+            bls_signature: BlsSignature::empty(),
+        };
+        let mut expected = contribution.clone();
+        expected
+            .add_tau::<DefaultEngine>(&tau, &Identity::None, &parameters)
+            .unwrap();
+
+        let reports = Mutex::new(Vec::new());
+        contribution
+            .add_tau_with::<DefaultEngine>(
+                &tau,
+                &Identity::None,
+                &parameters,
+                &|progress: Progress| reports.lock().unwrap().push(progress.done),
+                &CancellationToken::new(),
+            )
+            .unwrap();
+        assert_eq!(contribution, expected);
+        assert_eq!(reports.into_inner().unwrap(), vec![
+            CHUNK_SIZE,
+            CHUNK_SIZE + 1,
+            CHUNK_SIZE + 3
+        ]);
+
+        let cancel = CancellationToken::new();
+        cancel.cancel();
+        assert_eq!(
+            contribution.add_tau_with::<DefaultEngine>(
+                &tau,
+                &Identity::None,
+                &parameters,
+                &|_: Progress| (),
+                &cancel,
+            ),
+            Err(CeremonyError::Cancelled)
+        );
+    }
+
     #[test]
     fn contribution_json() {
         let value = Contribution {
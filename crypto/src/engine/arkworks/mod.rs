@@ -8,13 +8,14 @@ mod hashing;
 mod zcash_format;
 
 use self::endomorphism::{g1_mul_glv, g1_subgroup_check, g2_subgroup_check};
-use super::Engine;
+use super::{BatchSubgroupCheck, Decompressed, Engine};
 use crate::{
     engine::arkworks::hashing::{
         hash_to_curve::{HashToCurve, MapToCurveBasedHasher, WBMap},
         hash_to_field::DefaultFieldHasher,
     },
-    CeremonyError, Entropy, ParseError, Tau, F, G1, G2,
+    parallel::prelude::*,
+    CeremonyError, Entropy, ParseError, Powers, Tau, F, G1, G2,
 };
 use ark_bls12_381::{
     g1::Parameters as G1Parameters, Bls12_381, Fr, G1Affine, G1Projective, G2Affine, G2Projective,
@@ -22,12 +23,11 @@ use ark_bls12_381::{
 use ark_ec::{
     msm::VariableBaseMSM, wnaf::WnafContext, AffineCurve, PairingEngine, ProjectiveCurve,
 };
-use ark_ff::{BigInteger, One, PrimeField, UniformRand, Zero};
+use ark_ff::{BigInteger, Field, One, PrimeField, UniformRand, Zero};
 use digest::Digest;
 use hkdf::Hkdf;
 use rand::{Rng, SeedableRng};
 use rand_chacha::ChaCha20Rng;
-use rayon::prelude::*;
 use secrecy::{ExposeSecret, Secret, SecretVec};
 use sha2::Sha256;
 use std::iter;
@@ -38,32 +38,16 @@ use tracing::instrument;
 pub struct Arkworks;
 
 impl Engine for Arkworks {
+    type ValidPowers = Decompressed<G1Affine, G2Affine>;
+
     #[instrument(level = "info", skip_all, fields(n=points.len()))]
     fn validate_g1(points: &[G1]) -> Result<(), CeremonyError> {
-        points.into_par_iter().enumerate().try_for_each(|(i, p)| {
-            let p = G1Affine::try_from(*p).map_err(|e| CeremonyError::InvalidG1Power(i, e))?;
-            if !g1_subgroup_check(&p) {
-                return Err(CeremonyError::InvalidG1Power(
-                    i,
-                    ParseError::InvalidSubgroup,
-                ));
-            }
-            Ok(())
-        })
+        decompress_g1(points).map(|_| ())
     }
 
     #[instrument(level = "info", skip_all, fields(n=points.len()))]
     fn validate_g2(points: &[G2]) -> Result<(), CeremonyError> {
-        points.into_par_iter().enumerate().try_for_each(|(i, p)| {
-            let p = G2Affine::try_from(*p).map_err(|e| CeremonyError::InvalidG2Power(i, e))?;
-            if !g2_subgroup_check(&p) {
-                return Err(CeremonyError::InvalidG2Power(
-                    i,
-                    ParseError::InvalidSubgroup,
-                ));
-            }
-            Ok(())
-        })
+        decompress_g2(points).map(|_| ())
     }
 
     #[instrument(level = "info", skip_all)]
@@ -71,12 +55,7 @@ impl Engine for Arkworks {
         let tau = G1Affine::try_from(tau)?;
         let previous = G1Affine::try_from(previous)?;
         let pubkey = G2Affine::try_from(pubkey)?;
-        if Bls12_381::pairing(tau, G2Affine::prime_subgroup_generator())
-            != Bls12_381::pairing(previous, pubkey)
-        {
-            return Err(CeremonyError::PubKeyPairingFailed);
-        }
-        Ok(())
+        check_pubkey(tau, previous, pubkey)
     }
 
     #[instrument(level = "info", skip_all, fields(n=powers.len()))]
@@ -87,19 +66,7 @@ impl Engine for Arkworks {
             .map(|p| G1Affine::try_from(*p))
             .collect::<Result<Vec<_>, _>>()?;
         let tau = G2Affine::try_from(tau)?;
-
-        // Compute random linear combination
-        let (factors, sum) = random_factors(powers.len() - 1);
-        let lhs_g1 = VariableBaseMSM::multi_scalar_mul(&powers[1..], &factors[..]);
-        let lhs_g2 = G2Affine::prime_subgroup_generator().mul(sum);
-        let rhs_g1 = VariableBaseMSM::multi_scalar_mul(&powers[..factors.len()], &factors[..]);
-        let rhs_g2 = tau.mul(sum);
-
-        // Check pairing
-        if Bls12_381::pairing(lhs_g1, lhs_g2) != Bls12_381::pairing(rhs_g1, rhs_g2) {
-            return Err(CeremonyError::G1PairingFailed);
-        }
-        Ok(())
+        check_g1(&powers, tau)
     }
 
     #[instrument(level = "info", skip_all, fields(n1=g1.len(), n2=g2.len()))]
@@ -115,19 +82,23 @@ impl Engine for Arkworks {
             .into_par_iter()
             .map(|p| G2Affine::try_from(*p))
             .collect::<Result<Vec<_>, _>>()?;
+        check_g2(&g1, &g2)
+    }
 
-        // Compute random linear combination
-        let (factors, sum) = random_factors(g2.len());
-        let lhs_g1 = VariableBaseMSM::multi_scalar_mul(&g1, &factors[..]);
-        let lhs_g2 = G2Affine::prime_subgroup_generator().mul(sum);
-        let rhs_g1 = G1Affine::prime_subgroup_generator().mul(sum);
-        let rhs_g2 = VariableBaseMSM::multi_scalar_mul(&g2, &factors[..]);
+    #[instrument(level = "info", skip_all, fields(n1=powers.g1.len(), n2=powers.g2.len()))]
+    fn validate_powers(powers: &Powers, pubkey: G2) -> Result<Self::ValidPowers, CeremonyError> {
+        Ok(Decompressed {
+            g1:     decompress_g1(&powers.g1)?,
+            g2:     decompress_g2(&powers.g2)?,
+            pubkey: decompress_g2(&[pubkey])?[0],
+        })
+    }
 
-        // Check pairing
-        if Bls12_381::pairing(lhs_g1, lhs_g2) != Bls12_381::pairing(rhs_g1, rhs_g2) {
-            return Err(CeremonyError::G2PairingFailed);
-        }
-        Ok(())
+    #[instrument(level = "info", skip_all, fields(n1=powers.g1.len(), n2=powers.g2.len()))]
+    fn verify_powers(powers: &Self::ValidPowers, previous: G1) -> Result<(), CeremonyError> {
+        check_pubkey(powers.g1[1], G1Affine::try_from(previous)?, powers.pubkey)?;
+        check_g1(&powers.g1, powers.g2[1])?;
+        check_g2(&powers.g1[..powers.g2.len()], &powers.g2)
     }
 
     #[instrument(level = "info", skip_all)]
@@ -150,8 +121,8 @@ impl Engine for Arkworks {
     }
 
     #[instrument(level = "info", skip_all, fields(n=powers.len()))]
-    fn add_tau_g1(tau: &Tau, powers: &mut [G1]) -> Result<(), CeremonyError> {
-        let taus = powers_of_tau(tau, powers.len());
+    fn add_tau_g1_from(tau: &Tau, start: usize, powers: &mut [G1]) -> Result<(), CeremonyError> {
+        let taus = powers_of_tau(tau, start, powers.len());
         let mut projective = powers
             .par_iter()
             .zip(taus.expose_secret())
@@ -165,8 +136,8 @@ impl Engine for Arkworks {
     }
 
     #[instrument(level = "info", skip_all, fields(n=powers.len()))]
-    fn add_tau_g2(tau: &Tau, powers: &mut [G2]) -> Result<(), CeremonyError> {
-        let taus = powers_of_tau(tau, powers.len());
+    fn add_tau_g2_from(tau: &Tau, start: usize, powers: &mut [G2]) -> Result<(), CeremonyError> {
+        let taus = powers_of_tau(tau, start, powers.len());
         let mut projective = powers
             .par_iter()
             .zip(taus.expose_secret())
@@ -184,19 +155,19 @@ impl Engine for Arkworks {
         Ok(())
     }
 
-    fn sign_message(tau: &Tau, message: &[u8]) -> Option<G1> {
+    fn sign_message(tau: &Tau, message: &[u8], cipher_suite: &str) -> Option<G1> {
         let mapper = MapToCurveBasedHasher::<
             G1Parameters,
             DefaultFieldHasher<Sha256, 128>,
             WBMap<G1Parameters>,
-        >::new(Self::CYPHER_SUITE.as_bytes())
+        >::new(cipher_suite.as_bytes())
         .ok()?;
         let point = mapper.hash(message).ok()?;
         let sig = point.mul(Fr::from(tau.expose_secret())).into_affine();
         Some(G1::from(sig))
     }
 
-    fn verify_signature(sig: G1, message: &[u8], pk: G2) -> bool {
+    fn verify_signature(sig: G1, message: &[u8], pk: G2, cipher_suite: &str) -> bool {
         let sig = match G1Affine::try_from(sig) {
             Ok(sig) => sig,
             _ => return false,
@@ -219,7 +190,7 @@ impl Engine for Arkworks {
             G1Parameters,
             DefaultFieldHasher<Sha256, 128>,
             WBMap<G1Parameters>,
-        >::new(Self::CYPHER_SUITE.as_bytes())
+        >::new(cipher_suite.as_bytes())
         {
             Ok(mapper) => mapper,
             _ => return false,
@@ -235,6 +206,215 @@ impl Engine for Arkworks {
 
         c1 == c2
     }
+
+    #[instrument(level = "info", skip_all, fields(n=signatures.len()))]
+    fn verify_signatures(signatures: &[(G1, &[u8], G2)], cipher_suite: &str) -> bool {
+        if signatures.is_empty() {
+            return true;
+        }
+        let mapper = match MapToCurveBasedHasher::<
+            G1Parameters,
+            DefaultFieldHasher<Sha256, 128>,
+            WBMap<G1Parameters>,
+        >::new(cipher_suite.as_bytes())
+        {
+            Ok(mapper) => mapper,
+            _ => return false,
+        };
+
+        // Parse and hash, with the same checks as `verify_signature`.
+        let parsed = signatures
+            .par_iter()
+            .map(|(sig, message, pk)| {
+                let sig = G1Affine::try_from(*sig).ok()?;
+                let pk = G2Affine::try_from(*pk).ok()?;
+                if !g1_subgroup_check(&sig) || !g2_subgroup_check(&pk) || pk.is_zero() {
+                    return None;
+                }
+                let msg = mapper.hash(message).ok()?;
+                Some((sig, msg, pk))
+            })
+            .collect::<Option<Vec<_>>>();
+        let parsed = match parsed {
+            Some(parsed) => parsed,
+            None => return false,
+        };
+
+        // Check e(Σ rᵢ sigᵢ, g₂) = Π e(rᵢ H(mᵢ), pkᵢ) for random rᵢ
+        let (factors, _) = random_factors(parsed.len());
+        let sigs = parsed.iter().map(|(sig, ..)| *sig).collect::<Vec<_>>();
+        let lhs = VariableBaseMSM::multi_scalar_mul(&sigs, &factors[..]).into_affine();
+        let mut pairs = parsed
+            .par_iter()
+            .zip(&factors)
+            .map(|((_, msg, pk), factor)| (msg.mul(*factor).into_affine().into(), (*pk).into()))
+            .collect::<Vec<(
+                <Bls12_381 as PairingEngine>::G1Prepared,
+                <Bls12_381 as PairingEngine>::G2Prepared,
+            )>>();
+        pairs.push(((-lhs).into(), G2Affine::prime_subgroup_generator().into()));
+        Bls12_381::product_of_pairings(&pairs).is_one()
+    }
+
+    #[instrument(level = "info", skip_all, fields(n=points.len()))]
+    fn msm_g1(points: &[G1], scalars: &[F]) -> Result<G1, CeremonyError> {
+        assert_eq!(points.len(), scalars.len());
+        let points = decompress_g1(points)?;
+        let scalars = bigints(scalars);
+        Ok(VariableBaseMSM::multi_scalar_mul(&points, &scalars)
+            .into_affine()
+            .into())
+    }
+
+    #[instrument(level = "info", skip_all, fields(n=points.len()))]
+    fn msm_g2(points: &[G2], scalars: &[F]) -> Result<G2, CeremonyError> {
+        assert_eq!(points.len(), scalars.len());
+        let points = decompress_g2(points)?;
+        let scalars = bigints(scalars);
+        Ok(VariableBaseMSM::multi_scalar_mul(&points, &scalars)
+            .into_affine()
+            .into())
+    }
+
+    #[instrument(level = "info", skip_all, fields(n=pairs.len()))]
+    fn pairing_check(pairs: &[(G1, G2)]) -> Result<bool, CeremonyError> {
+        let (g1, g2): (Vec<_>, Vec<_>) = pairs.iter().copied().unzip();
+        let g1 = decompress_g1(&g1)?;
+        let g2 = decompress_g2(&g2)?;
+        let prepared = g1
+            .into_iter()
+            .zip(g2)
+            .map(|(p, q)| (p.into(), q.into()))
+            .collect::<Vec<(
+                <Bls12_381 as PairingEngine>::G1Prepared,
+                <Bls12_381 as PairingEngine>::G2Prepared,
+            )>>();
+        Ok(Bls12_381::product_of_pairings(&prepared).is_one())
+    }
+}
+
+impl BatchSubgroupCheck for Arkworks {
+    type G1Affine = G1Affine;
+    type G2Affine = G2Affine;
+
+    fn decompress_g1_unchecked(points: &[G1]) -> Result<Vec<G1Affine>, CeremonyError> {
+        points
+            .into_par_iter()
+            .enumerate()
+            .map(|(i, p)| G1Affine::try_from(*p).map_err(|e| CeremonyError::InvalidG1Power(i, e)))
+            .collect()
+    }
+
+    fn decompress_g2_unchecked(points: &[G2]) -> Result<Vec<G2Affine>, CeremonyError> {
+        points
+            .into_par_iter()
+            .enumerate()
+            .map(|(i, p)| G2Affine::try_from(*p).map_err(|e| CeremonyError::InvalidG2Power(i, e)))
+            .collect()
+    }
+
+    fn g1_sum_in_subgroup(points: &[G1Affine], factors: &[u8]) -> bool {
+        let factors = small_factors(factors);
+        let sum = VariableBaseMSM::multi_scalar_mul(points, &factors).into_affine();
+        g1_subgroup_check(&sum)
+    }
+
+    fn g2_sum_in_subgroup(points: &[G2Affine], factors: &[u8]) -> bool {
+        let factors = small_factors(factors);
+        let sum = VariableBaseMSM::multi_scalar_mul(points, &factors).into_affine();
+        g2_subgroup_check(&sum)
+    }
+}
+
+fn small_factors(factors: &[u8]) -> Vec<<Fr as PrimeField>::BigInt> {
+    factors
+        .iter()
+        .map(|c| Fr::from(u64::from(*c)).into_repr())
+        .collect()
+}
+
+/// Reduces `scalars` and converts them to the representation used in
+/// multi-scalar multiplications.
+fn bigints(scalars: &[F]) -> Vec<<Fr as PrimeField>::BigInt> {
+    scalars
+        .par_iter()
+        .map(|s| Fr::from(s).into_repr())
+        .collect()
+}
+
+/// Parses `points` and checks that they are in the prime order subgroup.
+fn decompress_g1(points: &[G1]) -> Result<Vec<G1Affine>, CeremonyError> {
+    points
+        .into_par_iter()
+        .enumerate()
+        .map(|(i, p)| {
+            let p = G1Affine::try_from(*p).map_err(|e| CeremonyError::InvalidG1Power(i, e))?;
+            if !g1_subgroup_check(&p) {
+                return Err(CeremonyError::InvalidG1Power(
+                    i,
+                    ParseError::InvalidSubgroup,
+                ));
+            }
+            Ok(p)
+        })
+        .collect()
+}
+
+/// Parses `points` and checks that they are in the prime order subgroup.
+fn decompress_g2(points: &[G2]) -> Result<Vec<G2Affine>, CeremonyError> {
+    points
+        .into_par_iter()
+        .enumerate()
+        .map(|(i, p)| {
+            let p = G2Affine::try_from(*p).map_err(|e| CeremonyError::InvalidG2Power(i, e))?;
+            if !g2_subgroup_check(&p) {
+                return Err(CeremonyError::InvalidG2Power(
+                    i,
+                    ParseError::InvalidSubgroup,
+                ));
+            }
+            Ok(p)
+        })
+        .collect()
+}
+
+fn check_pubkey(tau: G1Affine, previous: G1Affine, pubkey: G2Affine) -> Result<(), CeremonyError> {
+    if Bls12_381::pairing(tau, G2Affine::prime_subgroup_generator())
+        != Bls12_381::pairing(previous, pubkey)
+    {
+        return Err(CeremonyError::PubKeyPairingFailed);
+    }
+    Ok(())
+}
+
+fn check_g1(powers: &[G1Affine], tau: G2Affine) -> Result<(), CeremonyError> {
+    // Compute random linear combination
+    let (factors, sum) = random_factors(powers.len() - 1);
+    let lhs_g1 = VariableBaseMSM::multi_scalar_mul(&powers[1..], &factors[..]);
+    let lhs_g2 = G2Affine::prime_subgroup_generator().mul(sum);
+    let rhs_g1 = VariableBaseMSM::multi_scalar_mul(&powers[..factors.len()], &factors[..]);
+    let rhs_g2 = tau.mul(sum);
+
+    // Check pairing
+    if Bls12_381::pairing(lhs_g1, lhs_g2) != Bls12_381::pairing(rhs_g1, rhs_g2) {
+        return Err(CeremonyError::G1PairingFailed);
+    }
+    Ok(())
+}
+
+fn check_g2(g1: &[G1Affine], g2: &[G2Affine]) -> Result<(), CeremonyError> {
+    // Compute random linear combination
+    let (factors, sum) = random_factors(g2.len());
+    let lhs_g1 = VariableBaseMSM::multi_scalar_mul(g1, &factors[..]);
+    let lhs_g2 = G2Affine::prime_subgroup_generator().mul(sum);
+    let rhs_g1 = G1Affine::prime_subgroup_generator().mul(sum);
+    let rhs_g2 = VariableBaseMSM::multi_scalar_mul(g2, &factors[..]);
+
+    // Check pairing
+    if Bls12_381::pairing(lhs_g1, lhs_g2) != Bls12_381::pairing(rhs_g1, rhs_g2) {
+        return Err(CeremonyError::G2PairingFailed);
+    }
+    Ok(())
 }
 
 // Implementation of the KeyGen function as specified in
@@ -265,13 +445,14 @@ fn bls_keygen(ikm: [u8; 64]) -> Fr {
     }
 }
 
-pub fn powers_of_tau(tau: &Tau, n: usize) -> SecretVec<Fr> {
+pub fn powers_of_tau(tau: &Tau, start: usize, n: usize) -> SecretVec<Fr> {
     // Convert tau
     let tau = Secret::new(Fr::from(tau.expose_secret()));
+    let first = tau.expose_secret().pow([start as u64]);
 
     // Compute powers
     Secret::new(
-        iter::successors(Some(Fr::one()), |x| Some(*x * tau.expose_secret()))
+        iter::successors(Some(first), |x| Some(*x * tau.expose_secret()))
             .take(n)
             .collect::<Vec<_>>(),
     )
@@ -290,23 +471,6 @@ fn random_factors(n: usize) -> (Vec<<Fr as PrimeField>::BigInt>, Fr) {
     (factors, sum)
 }
 
-impl From<&F> for Fr {
-    fn from(f: &F) -> Self {
-        Self::from_le_bytes_mod_order(&f.0[..])
-    }
-}
-
-#[cfg(test)]
-impl From<Fr> for F {
-    fn from(fr: Fr) -> Self {
-        let le_bytes = fr.into_repr().to_bytes_le();
-        assert!(le_bytes.len() == 32);
-        let mut f = [0u8; 32];
-        f.copy_from_slice(&le_bytes[..]);
-        Self(f)
-    }
-}
-
 #[cfg(test)]
 pub mod test {
     use super::*;
@@ -0,0 +1,266 @@
+//! Probabilistic batch subgroup checks.
+//!
+//! Validating a point requires checking that it is in the prime order
+//! subgroup, which dominates the cost of validating large contributions.
+//! [`Batched`] instead checks that a random linear combination of the points
+//! is in the subgroup, and bisects the combination to pinpoint an invalid
+//! point.
+//!
+//! Combining the points with random factors $cᵢ ∈ [0, 256)$ misses an invalid
+//! point only if its component outside the subgroup vanishes from the sum.
+//! That happens at most when $cᵢ$ is in one residue class modulo the order of
+//! that component, which is a multiple of the smallest prime factor of the
+//! cofactor: 3 in G1 and 13 in G2. A round thus misses with probability at most
+//! $86/256$ in G1 and $20/256$ in G2, and the rounds are repeated until this is
+//! below $2^{-64}$.
+
+use super::{Decompressed, Engine};
+use crate::{CeremonyError, Entropy, ParseError, Powers, Tau, F, G1, G2};
+use rand::Rng;
+use std::{iter, marker::PhantomData};
+
+/// Number of rounds for an error probability below $2^{-64}$ in G1.
+const G1_ROUNDS: usize = 41;
+
+/// Number of rounds for an error probability below $2^{-64}$ in G2.
+const G2_ROUNDS: usize = 18;
+
+/// Curve operations of an engine needed for [`Batched`] validation.
+pub trait BatchSubgroupCheck: Engine {
+    type G1Affine: Copy + Send + Sync + 'static;
+    type G2Affine: Copy + Send + Sync + 'static;
+
+    /// Parses `points` and checks that they are on the curve, but not that
+    /// they are in the prime order subgroup.
+    ///
+    /// # Errors
+    /// Returns an error if any of `points` is not a compressed ZCash format
+    /// point on the curve.
+    fn decompress_g1_unchecked(points: &[G1]) -> Result<Vec<Self::G1Affine>, CeremonyError>;
+
+    /// Parses `points` and checks that they are on the curve, but not that
+    /// they are in the prime order subgroup.
+    ///
+    /// # Errors
+    /// Returns an error if any of `points` is not a compressed ZCash format
+    /// point on the curve.
+    fn decompress_g2_unchecked(points: &[G2]) -> Result<Vec<Self::G2Affine>, CeremonyError>;
+
+    /// Checks that $Σᵢ cᵢ ⋅ pᵢ$ is in the prime order subgroup.
+    fn g1_sum_in_subgroup(points: &[Self::G1Affine], factors: &[u8]) -> bool;
+
+    /// Checks that $Σᵢ cᵢ ⋅ pᵢ$ is in the prime order subgroup.
+    fn g2_sum_in_subgroup(points: &[Self::G2Affine], factors: &[u8]) -> bool;
+}
+
+/// Implementation of [`Engine`] that validates points with probabilistic
+/// batch subgroup checks, and otherwise runs `E`.
+///
+/// The mode is selected per engine, for example
+/// `Both<Batched<Arkworks>, BLST>` batches only the Arkworks checks.
+pub struct Batched<E: BatchSubgroupCheck> {
+    _engine: PhantomData<E>,
+}
+
+impl<E: BatchSubgroupCheck> Batched<E> {
+    fn decompress_g1(points: &[G1]) -> Result<Vec<E::G1Affine>, CeremonyError> {
+        let points = E::decompress_g1_unchecked(points)?;
+        find_invalid(&points, G1_ROUNDS, E::g1_sum_in_subgroup).map_or(Ok(points), |i| {
+            Err(CeremonyError::InvalidG1Power(
+                i,
+                ParseError::InvalidSubgroup,
+            ))
+        })
+    }
+
+    fn decompress_g2(points: &[G2]) -> Result<Vec<E::G2Affine>, CeremonyError> {
+        let points = E::decompress_g2_unchecked(points)?;
+        find_invalid(&points, G2_ROUNDS, E::g2_sum_in_subgroup).map_or(Ok(points), |i| {
+            Err(CeremonyError::InvalidG2Power(
+                i,
+                ParseError::InvalidSubgroup,
+            ))
+        })
+    }
+}
+
+/// Returns the index of a point outside the subgroup, if any is found.
+fn find_invalid<P>(
+    points: &[P],
+    rounds: usize,
+    in_subgroup: fn(&[P], &[u8]) -> bool,
+) -> Option<usize> {
+    match points.len() {
+        0 => None,
+        // A single point is checked exactly.
+        1 => (!in_subgroup(points, &[1])).then_some(0),
+        _ => {
+            let mut rng = rand::thread_rng();
+            (0..rounds).find_map(|_| {
+                let factors = iter::repeat_with(|| rng.gen())
+                    .take(points.len())
+                    .collect::<Vec<u8>>();
+                (!in_subgroup(points, &factors)).then(|| bisect(points, &factors, in_subgroup))
+            })
+        }
+    }
+}
+
+/// Finds a point outside the subgroup, given that the combination of `points`
+/// with `factors` is outside the subgroup.
+///
+/// A combination is outside the subgroup only if one of its halves is, and
+/// $c ⋅ p$ is outside the subgroup only if $p$ is.
+fn bisect<P>(points: &[P], factors: &[u8], in_subgroup: fn(&[P], &[u8]) -> bool) -> usize {
+    if points.len() == 1 {
+        return 0;
+    }
+    let mid = points.len() / 2;
+    if in_subgroup(&points[..mid], &factors[..mid]) {
+        mid + bisect(&points[mid..], &factors[mid..], in_subgroup)
+    } else {
+        bisect(&points[..mid], &factors[..mid], in_subgroup)
+    }
+}
+
+impl<E> Engine for Batched<E>
+where
+    E: BatchSubgroupCheck
+        + Engine<
+            ValidPowers = Decompressed<
+                <E as BatchSubgroupCheck>::G1Affine,
+                <E as BatchSubgroupCheck>::G2Affine,
+            >,
+        >,
+{
+    type ValidPowers = E::ValidPowers;
+
+    fn validate_g1(points: &[G1]) -> Result<(), CeremonyError> {
+        Self::decompress_g1(points).map(|_| ())
+    }
+
+    fn validate_g2(points: &[G2]) -> Result<(), CeremonyError> {
+        Self::decompress_g2(points).map(|_| ())
+    }
+
+    fn verify_pubkey(tau: G1, previous: G1, pubkey: G2) -> Result<(), CeremonyError> {
+        E::verify_pubkey(tau, previous, pubkey)
+    }
+
+    fn verify_g1(powers: &[G1], tau: G2) -> Result<(), CeremonyError> {
+        E::verify_g1(powers, tau)
+    }
+
+    fn verify_g2(g1: &[G1], g2: &[G2]) -> Result<(), CeremonyError> {
+        E::verify_g2(g1, g2)
+    }
+
+    fn validate_powers(powers: &Powers, pubkey: G2) -> Result<Self::ValidPowers, CeremonyError> {
+        Ok(Decompressed {
+            g1:     Self::decompress_g1(&powers.g1)?,
+            g2:     Self::decompress_g2(&powers.g2)?,
+            pubkey: Self::decompress_g2(&[pubkey])?[0],
+        })
+    }
+
+    fn verify_powers(powers: &Self::ValidPowers, previous: G1) -> Result<(), CeremonyError> {
+        E::verify_powers(powers, previous)
+    }
+
+    fn generate_tau(entropy: &Entropy) -> Tau {
+        E::generate_tau(entropy)
+    }
+
+    fn add_tau_g1_from(tau: &Tau, start: usize, powers: &mut [G1]) -> Result<(), CeremonyError> {
+        E::add_tau_g1_from(tau, start, powers)
+    }
+
+    fn add_tau_g2_from(tau: &Tau, start: usize, powers: &mut [G2]) -> Result<(), CeremonyError> {
+        E::add_tau_g2_from(tau, start, powers)
+    }
+
+    fn sign_message(tau: &Tau, message: &[u8], cipher_suite: &str) -> Option<G1> {
+        E::sign_message(tau, message, cipher_suite)
+    }
+
+    fn verify_signature(sig: G1, message: &[u8], pk: G2, cipher_suite: &str) -> bool {
+        E::verify_signature(sig, message, pk, cipher_suite)
+    }
+
+    fn verify_signatures(signatures: &[(G1, &[u8], G2)], cipher_suite: &str) -> bool {
+        E::verify_signatures(signatures, cipher_suite)
+    }
+
+    fn msm_g1(points: &[G1], scalars: &[F]) -> Result<G1, CeremonyError> {
+        E::msm_g1(points, scalars)
+    }
+
+    fn msm_g2(points: &[G2], scalars: &[F]) -> Result<G2, CeremonyError> {
+        E::msm_g2(points, scalars)
+    }
+
+    fn pairing_check(pairs: &[(G1, G2)]) -> Result<bool, CeremonyError> {
+        E::pairing_check(pairs)
+    }
+}
+
+#[cfg(all(test, feature = "arkworks", feature = "blst"))]
+mod tests {
+    use super::*;
+    use crate::{
+        engine::{
+            arkworks::test::{arb_g1_probably_not_in_subgroup, arb_g2_probably_not_in_subgroup},
+            tests::{arb_g1, arb_g2},
+        },
+        Arkworks, BLST,
+    };
+    use proptest::{arbitrary::any, collection::vec, proptest, sample::Index};
+
+    #[test]
+    fn test_valid_points() {
+        proptest!(|(g1 in vec(arb_g1(), 0..16), g2 in vec(arb_g2(), 0..4))| {
+            assert_eq!(Batched::<Arkworks>::validate_g1(&g1), Ok(()));
+            assert_eq!(Batched::<BLST>::validate_g1(&g1), Ok(()));
+            assert_eq!(Batched::<Arkworks>::validate_g2(&g2), Ok(()));
+            assert_eq!(Batched::<BLST>::validate_g2(&g2), Ok(()));
+        });
+    }
+
+    #[test]
+    fn test_pinpoints_invalid_g1() {
+        proptest!(|(
+            mut points in vec(arb_g1(), 1..16),
+            invalid in arb_g1_probably_not_in_subgroup(),
+            index in any::<Index>(),
+        )| {
+            let i = index.index(points.len());
+            points[i] = invalid.into();
+            let expected = Arkworks::validate_g1(&[points[i]])
+                .map_err(|_| CeremonyError::InvalidG1Power(i, ParseError::InvalidSubgroup));
+            assert_eq!(Batched::<Arkworks>::validate_g1(&points), expected);
+            assert_eq!(Batched::<BLST>::validate_g1(&points), expected);
+        });
+    }
+
+    #[test]
+    fn test_pinpoints_invalid_g2() {
+        proptest!(|(
+            mut points in vec(arb_g2(), 1..8),
+            invalid in arb_g2_probably_not_in_subgroup(),
+            index in any::<Index>(),
+        )| {
+            let i = index.index(points.len());
+            points[i] = invalid.into();
+            let expected = Arkworks::validate_g2(&[points[i]])
+                .map_err(|_| CeremonyError::InvalidG2Power(i, ParseError::InvalidSubgroup));
+            assert_eq!(Batched::<Arkworks>::validate_g2(&points), expected);
+            assert_eq!(Batched::<BLST>::validate_g2(&points), expected);
+        });
+    }
+
+    #[test]
+    fn test_invalid_encoding() {
+        assert!(Batched::<Arkworks>::validate_g1(&[G1::one(), G1([0; 48])]).is_err());
+        assert!(Batched::<BLST>::validate_g2(&[G2::one(), G2([0; 96])]).is_err());
+    }
+}
@@ -5,17 +5,20 @@ mod scalar;
 use self::{
     g1::{p1_affine_in_g1, p1_from_affine, p1_mult, p1s_mult_pippenger, p1s_to_affine},
     g2::{p2_affine_in_g2, p2_from_affine, p2_mult, p2_to_affine, p2s_to_affine},
-    scalar::{fr_from_scalar, fr_mul, fr_one, random_fr, scalar_from_fr},
+    scalar::{fr_from_scalar, fr_mul, fr_pow, random_fr, scalar_from_fr},
 };
 use crate::{
-    engine::blst::{g1::p1_to_affine, g2::p2s_mult_pippenger, scalar::Scalar},
-    CeremonyError, Engine, Entropy, ParseError, Tau, G1, G2,
+    engine::{
+        blst::{g1::p1_to_affine, g2::p2s_mult_pippenger, scalar::Scalar},
+        BatchSubgroupCheck, Decompressed,
+    },
+    CeremonyError, Engine, Entropy, ParseError, Powers, Tau, F, G1, G2,
 };
 use blst::{
-    blst_core_verify_pk_in_g2, blst_final_exp, blst_fp12, blst_fr, blst_fr_add, blst_hash_to_g1,
-    blst_miller_loop, blst_p1, blst_p1_affine, blst_p1_generator, blst_p2_affine,
-    blst_p2_affine_generator, blst_p2_generator, blst_scalar, blst_scalar_from_le_bytes,
-    blst_sign_pk_in_g2, BLST_ERROR,
+    blst_core_verify_pk_in_g2, blst_final_exp, blst_fp12, blst_fp12_is_one, blst_fp12_mul, blst_fr,
+    blst_fr_add, blst_hash_to_g1, blst_miller_loop, blst_p1, blst_p1_affine, blst_p1_generator,
+    blst_p2_affine, blst_p2_affine_generator, blst_p2_affine_is_inf, blst_p2_generator,
+    blst_scalar, blst_scalar_from_le_bytes, blst_sign_pk_in_g2, BLST_ERROR,
 };
 use rand::Rng;
 use rayon::prelude::{
@@ -28,13 +31,15 @@ use std::iter;
 pub struct BLST;
 
 impl Engine for BLST {
+    type ValidPowers = Decompressed<blst_p1_affine, blst_p2_affine>;
+
     fn generate_tau(entropy: &Entropy) -> Tau {
         let fr = random_fr(*entropy.expose_secret());
         Secret::new((&fr).into())
     }
 
-    fn add_tau_g1(tau: &Tau, powers: &mut [G1]) -> Result<(), CeremonyError> {
-        let taus = powers_of_tau(tau, powers.len());
+    fn add_tau_g1_from(tau: &Tau, start: usize, powers: &mut [G1]) -> Result<(), CeremonyError> {
+        let taus = powers_of_tau(tau, start, powers.len());
 
         let powers_projective = powers
             .par_iter()
@@ -57,8 +62,12 @@ impl Engine for BLST {
             })
     }
 
-    fn add_tau_g2(tau: &Tau, powers: &mut [crate::G2]) -> Result<(), crate::CeremonyError> {
-        let taus = powers_of_tau(tau, powers.len());
+    fn add_tau_g2_from(
+        tau: &Tau,
+        start: usize,
+        powers: &mut [crate::G2],
+    ) -> Result<(), crate::CeremonyError> {
+        let taus = powers_of_tau(tau, start, powers.len());
 
         let powers_projective = powers
             .par_iter()
@@ -82,29 +91,11 @@ impl Engine for BLST {
     }
 
     fn validate_g1(points: &[crate::G1]) -> Result<(), crate::CeremonyError> {
-        points.into_par_iter().enumerate().try_for_each(|(i, &p)| {
-            let p = blst_p1_affine::try_from(p)?;
-            if !p1_affine_in_g1(&p) {
-                return Err(CeremonyError::InvalidG1Power(
-                    i,
-                    ParseError::InvalidSubgroup,
-                ));
-            }
-            Ok(())
-        })
+        decompress_g1(points).map(|_| ())
     }
 
     fn validate_g2(points: &[crate::G2]) -> Result<(), crate::CeremonyError> {
-        points.into_par_iter().enumerate().try_for_each(|(i, &p)| {
-            let p = blst_p2_affine::try_from(p)?;
-            if !p2_affine_in_g2(&p) {
-                return Err(CeremonyError::InvalidG2Power(
-                    i,
-                    ParseError::InvalidSubgroup,
-                ));
-            }
-            Ok(())
-        })
+        decompress_g2(points).map(|_| ())
     }
 
     fn verify_pubkey(
@@ -115,14 +106,7 @@ impl Engine for BLST {
         let tau = blst_p1_affine::try_from(tau)?;
         let previous = blst_p1_affine::try_from(previous)?;
         let pubkey = blst_p2_affine::try_from(pubkey)?;
-
-        unsafe {
-            let g2 = *blst_p2_affine_generator();
-            if pairing(&tau, &g2) != pairing(&previous, &pubkey) {
-                return Err(CeremonyError::PubKeyPairingFailed);
-            }
-        }
-        Ok(())
+        check_pubkey(&tau, &previous, &pubkey)
     }
 
     fn verify_g1(powers: &[crate::G1], tau: crate::G2) -> Result<(), crate::CeremonyError> {
@@ -132,24 +116,7 @@ impl Engine for BLST {
             .map(|p| blst_p1_affine::try_from(*p))
             .collect::<Result<Vec<_>, _>>()?;
         let tau = blst_p2_affine::try_from(tau)?;
-        let tau = p2_from_affine(&tau);
-
-        // Compute random linear combination
-        let (factors, sum) = random_factors(powers.len() - 1);
-        let g2 = unsafe { *blst_p2_generator() };
-
-        let lhs_g1 = p1s_mult_pippenger(&powers[1..], &factors[..]);
-        let lhs_g2 = p2_to_affine(&p2_mult(&g2, &sum));
-
-        let rhs_g1 = p1s_mult_pippenger(&powers[..factors.len()], &factors[..]);
-        let rhs_g2 = p2_to_affine(&p2_mult(&tau, &sum));
-
-        // Check pairing
-        if pairing(&lhs_g1, &lhs_g2) != pairing(&rhs_g1, &rhs_g2) {
-            return Err(CeremonyError::G1PairingFailed);
-        }
-
-        Ok(())
+        check_g1(&powers, &tau)
     }
 
     fn verify_g2(g1: &[crate::G1], g2: &[crate::G2]) -> Result<(), crate::CeremonyError> {
@@ -166,26 +133,25 @@ impl Engine for BLST {
             .map(|p| blst_p2_affine::try_from(*p))
             .collect::<Result<Vec<_>, _>>()?;
 
-        // Compute random linear combination
-        let (factors, sum) = random_factors(g2.len());
-        let g1_generator = unsafe { *blst_p1_generator() };
-        let g2_generator = unsafe { *blst_p2_generator() };
-
-        let lhs_g1 = p1s_mult_pippenger(&g1, &factors[..]);
-        let lhs_g2 = p2_to_affine(&p2_mult(&g2_generator, &sum));
-
-        let rhs_g1 = p1_to_affine(&p1_mult(&g1_generator, &sum));
-        let rhs_g2 = p2s_mult_pippenger(&g2, &factors[..]);
+        check_g2(&g1, &g2)
+    }
 
-        // Check pairing
-        if pairing(&lhs_g1, &lhs_g2) != pairing(&rhs_g1, &rhs_g2) {
-            return Err(CeremonyError::G1PairingFailed);
-        }
+    fn validate_powers(powers: &Powers, pubkey: G2) -> Result<Self::ValidPowers, CeremonyError> {
+        Ok(Decompressed {
+            g1:     decompress_g1(&powers.g1)?,
+            g2:     decompress_g2(&powers.g2)?,
+            pubkey: decompress_g2(&[pubkey])?[0],
+        })
+    }
 
-        Ok(())
+    fn verify_powers(powers: &Self::ValidPowers, previous: G1) -> Result<(), CeremonyError> {
+        let previous = blst_p1_affine::try_from(previous)?;
+        check_pubkey(&powers.g1[1], &previous, &powers.pubkey)?;
+        check_g1(&powers.g1, &powers.g2[1])?;
+        check_g2(&powers.g1[..powers.g2.len()], &powers.g2)
     }
 
-    fn sign_message(tau: &Tau, message: &[u8]) -> Option<G1> {
+    fn sign_message(tau: &Tau, message: &[u8], cipher_suite: &str) -> Option<G1> {
         let mut hash = blst_p1::default();
         let mut sig = blst_p1::default();
         let sk = blst_scalar::from(tau.expose_secret());
@@ -194,8 +160,8 @@ impl Engine for BLST {
                 &mut hash,
                 message.as_ptr(),
                 message.len(),
-                Self::CYPHER_SUITE.as_ptr(),
-                Self::CYPHER_SUITE.len(),
+                cipher_suite.as_ptr(),
+                cipher_suite.len(),
                 [0; 0].as_ptr(),
                 0,
             );
@@ -204,7 +170,7 @@ impl Engine for BLST {
         G1::try_from(sig).ok()
     }
 
-    fn verify_signature(sig: G1, message: &[u8], pk: G2) -> bool {
+    fn verify_signature(sig: G1, message: &[u8], pk: G2, cipher_suite: &str) -> bool {
         let blst_pk = match blst_p2_affine::try_from(pk).ok() {
             Some(pk) => pk,
             _ => return false,
@@ -220,14 +186,262 @@ impl Engine for BLST {
                 true,
                 message.as_ptr(),
                 message.len(),
-                Self::CYPHER_SUITE.as_ptr(),
-                Self::CYPHER_SUITE.len(),
+                cipher_suite.as_ptr(),
+                cipher_suite.len(),
                 [0; 0].as_ptr(),
                 0,
             )
         };
         result == BLST_ERROR::BLST_SUCCESS
     }
+
+    fn verify_signatures(signatures: &[(G1, &[u8], G2)], cipher_suite: &str) -> bool {
+        if signatures.is_empty() {
+            return true;
+        }
+
+        // Parse and hash, with the same checks as `verify_signature`.
+        let parsed = signatures
+            .par_iter()
+            .map(|(sig, message, pk)| {
+                let sig = blst_p1_affine::try_from(*sig).ok()?;
+                let pk = blst_p2_affine::try_from(*pk).ok()?;
+                if !p1_affine_in_g1(&sig)
+                    || !p2_affine_in_g2(&pk)
+                    || unsafe { blst_p2_affine_is_inf(&pk) }
+                {
+                    return None;
+                }
+                let mut hash = blst_p1::default();
+                unsafe {
+                    blst_hash_to_g1(
+                        &mut hash,
+                        message.as_ptr(),
+                        message.len(),
+                        cipher_suite.as_ptr(),
+                        cipher_suite.len(),
+                        [0; 0].as_ptr(),
+                        0,
+                    );
+                }
+                Some((sig, hash, pk))
+            })
+            .collect::<Option<Vec<_>>>();
+        let parsed = match parsed {
+            Some(parsed) => parsed,
+            None => return false,
+        };
+
+        // Check e(Σ rᵢ sigᵢ, g₂) = Π e(rᵢ H(mᵢ), pkᵢ) for random rᵢ
+        let (factors, _) = random_factors(parsed.len());
+        let sigs = parsed.iter().map(|(sig, ..)| *sig).collect::<Vec<_>>();
+        let lhs = pairing(&p1s_mult_pippenger(&sigs, &factors), unsafe {
+            &*blst_p2_affine_generator()
+        });
+        let miller_loops = parsed
+            .par_iter()
+            .zip(&factors)
+            .map(|((_, hash, pk), factor)| {
+                let msg = p1_to_affine(&p1_mult(hash, factor));
+                let mut out = blst_fp12::default();
+                unsafe { blst_miller_loop(&mut out, pk, &msg) };
+                out
+            })
+            .collect::<Vec<_>>();
+        let product = miller_loops[1..]
+            .iter()
+            .fold(miller_loops[0], |mut acc, ml| {
+                unsafe { blst_fp12_mul(&mut acc, &acc, ml) };
+                acc
+            });
+        let mut rhs = blst_fp12::default();
+        unsafe { blst_final_exp(&mut rhs, &product) };
+        lhs == rhs
+    }
+
+    fn msm_g1(points: &[G1], scalars: &[F]) -> Result<G1, CeremonyError> {
+        assert_eq!(points.len(), scalars.len());
+        let points = decompress_g1(points)?;
+        let scalars = reduced_scalars(scalars);
+        Ok(G1::try_from(p1s_mult_pippenger(&points, &scalars))?)
+    }
+
+    fn msm_g2(points: &[G2], scalars: &[F]) -> Result<G2, CeremonyError> {
+        assert_eq!(points.len(), scalars.len());
+        let points = decompress_g2(points)?;
+        let scalars = reduced_scalars(scalars);
+        Ok(G2::try_from(p2s_mult_pippenger(&points, &scalars))?)
+    }
+
+    fn pairing_check(pairs: &[(G1, G2)]) -> Result<bool, CeremonyError> {
+        if pairs.is_empty() {
+            return Ok(true);
+        }
+        let (g1, g2): (Vec<_>, Vec<_>) = pairs.iter().copied().unzip();
+        let g1 = decompress_g1(&g1)?;
+        let g2 = decompress_g2(&g2)?;
+        let miller_loops = g1
+            .par_iter()
+            .zip(&g2)
+            .map(|(p, q)| {
+                let mut out = blst_fp12::default();
+                unsafe { blst_miller_loop(&mut out, q, p) };
+                out
+            })
+            .collect::<Vec<_>>();
+        let product = miller_loops[1..]
+            .iter()
+            .fold(miller_loops[0], |mut acc, ml| {
+                unsafe { blst_fp12_mul(&mut acc, &acc, ml) };
+                acc
+            });
+        let mut out = blst_fp12::default();
+        unsafe { blst_final_exp(&mut out, &product) };
+        Ok(unsafe { blst_fp12_is_one(&out) })
+    }
+}
+
+impl BatchSubgroupCheck for BLST {
+    type G1Affine = blst_p1_affine;
+    type G2Affine = blst_p2_affine;
+
+    fn decompress_g1_unchecked(points: &[G1]) -> Result<Vec<blst_p1_affine>, CeremonyError> {
+        points
+            .into_par_iter()
+            .enumerate()
+            .map(|(i, &p)| {
+                blst_p1_affine::try_from(p).map_err(|e| CeremonyError::InvalidG1Power(i, e))
+            })
+            .collect()
+    }
+
+    fn decompress_g2_unchecked(points: &[G2]) -> Result<Vec<blst_p2_affine>, CeremonyError> {
+        points
+            .into_par_iter()
+            .enumerate()
+            .map(|(i, &p)| {
+                blst_p2_affine::try_from(p).map_err(|e| CeremonyError::InvalidG2Power(i, e))
+            })
+            .collect()
+    }
+
+    fn g1_sum_in_subgroup(points: &[blst_p1_affine], factors: &[u8]) -> bool {
+        p1_affine_in_g1(&p1s_mult_pippenger(points, &small_factors(factors)))
+    }
+
+    fn g2_sum_in_subgroup(points: &[blst_p2_affine], factors: &[u8]) -> bool {
+        p2_affine_in_g2(&p2s_mult_pippenger(points, &small_factors(factors)))
+    }
+}
+
+fn small_factors(factors: &[u8]) -> Vec<blst_scalar> {
+    factors
+        .iter()
+        .map(|c| {
+            let mut scalar = blst_scalar::default();
+            scalar.b[0] = *c;
+            scalar
+        })
+        .collect()
+}
+
+/// Reduces `scalars` modulo the group order.
+fn reduced_scalars(scalars: &[F]) -> Vec<blst_scalar> {
+    scalars
+        .par_iter()
+        .map(|s| scalar_from_fr(&blst_fr::from(s)))
+        .collect()
+}
+
+/// Parses `points` and checks that they are in the prime order subgroup.
+fn decompress_g1(points: &[G1]) -> Result<Vec<blst_p1_affine>, CeremonyError> {
+    points
+        .into_par_iter()
+        .enumerate()
+        .map(|(i, &p)| {
+            let p = blst_p1_affine::try_from(p)?;
+            if !p1_affine_in_g1(&p) {
+                return Err(CeremonyError::InvalidG1Power(
+                    i,
+                    ParseError::InvalidSubgroup,
+                ));
+            }
+            Ok(p)
+        })
+        .collect()
+}
+
+/// Parses `points` and checks that they are in the prime order subgroup.
+fn decompress_g2(points: &[G2]) -> Result<Vec<blst_p2_affine>, CeremonyError> {
+    points
+        .into_par_iter()
+        .enumerate()
+        .map(|(i, &p)| {
+            let p = blst_p2_affine::try_from(p)?;
+            if !p2_affine_in_g2(&p) {
+                return Err(CeremonyError::InvalidG2Power(
+                    i,
+                    ParseError::InvalidSubgroup,
+                ));
+            }
+            Ok(p)
+        })
+        .collect()
+}
+
+fn check_pubkey(
+    tau: &blst_p1_affine,
+    previous: &blst_p1_affine,
+    pubkey: &blst_p2_affine,
+) -> Result<(), CeremonyError> {
+    unsafe {
+        let g2 = *blst_p2_affine_generator();
+        if pairing(tau, &g2) != pairing(previous, pubkey) {
+            return Err(CeremonyError::PubKeyPairingFailed);
+        }
+    }
+    Ok(())
+}
+
+fn check_g1(powers: &[blst_p1_affine], tau: &blst_p2_affine) -> Result<(), CeremonyError> {
+    let tau = p2_from_affine(tau);
+
+    // Compute random linear combination
+    let (factors, sum) = random_factors(powers.len() - 1);
+    let g2 = unsafe { *blst_p2_generator() };
+
+    let lhs_g1 = p1s_mult_pippenger(&powers[1..], &factors[..]);
+    let lhs_g2 = p2_to_affine(&p2_mult(&g2, &sum));
+
+    let rhs_g1 = p1s_mult_pippenger(&powers[..factors.len()], &factors[..]);
+    let rhs_g2 = p2_to_affine(&p2_mult(&tau, &sum));
+
+    // Check pairing
+    if pairing(&lhs_g1, &lhs_g2) != pairing(&rhs_g1, &rhs_g2) {
+        return Err(CeremonyError::G1PairingFailed);
+    }
+
+    Ok(())
+}
+
+fn check_g2(g1: &[blst_p1_affine], g2: &[blst_p2_affine]) -> Result<(), CeremonyError> {
+    // Compute random linear combination
+    let (factors, sum) = random_factors(g2.len());
+    let g1_generator = unsafe { *blst_p1_generator() };
+    let g2_generator = unsafe { *blst_p2_generator() };
+
+    let lhs_g1 = p1s_mult_pippenger(g1, &factors[..]);
+    let lhs_g2 = p2_to_affine(&p2_mult(&g2_generator, &sum));
+
+    let rhs_g1 = p1_to_affine(&p1_mult(&g1_generator, &sum));
+    let rhs_g2 = p2s_mult_pippenger(g2, &factors[..]);
+
+    // Check pairing
+    if pairing(&lhs_g1, &lhs_g2) != pairing(&rhs_g1, &rhs_g2) {
+        return Err(CeremonyError::G1PairingFailed);
+    }
+
+    Ok(())
 }
 
 fn pairing(p: &blst_p1_affine, q: &blst_p2_affine) -> blst_fp12 {
@@ -240,9 +454,9 @@ fn pairing(p: &blst_p1_affine, q: &blst_p2_affine) -> blst_fp12 {
     out
 }
 
-fn powers_of_tau(tau: &Tau, n: usize) -> SecretVec<Scalar> {
+fn powers_of_tau(tau: &Tau, start: usize, n: usize) -> SecretVec<Scalar> {
     let tau = tau.expose_secret().into();
-    let vec = iter::successors(Some(fr_one()), |x| Some(fr_mul(x, &tau)))
+    let vec = iter::successors(Some(fr_pow(&tau, start as u64)), |x| Some(fr_mul(x, &tau)))
         .map(|n| Scalar::from(scalar_from_fr(&n)))
         .take(n)
         .collect();
@@ -59,6 +59,21 @@ pub fn fr_one() -> blst_fr {
     fr_from_scalar(&scalar_from_u64(1u64))
 }
 
+/// Computes $a^e$ by square-and-multiply. The exponent is public, so it can be
+/// variable time.
+pub fn fr_pow(a: &blst_fr, mut exponent: u64) -> blst_fr {
+    let mut base = *a;
+    let mut out = fr_one();
+    while exponent > 0 {
+        if exponent & 1 == 1 {
+            out = fr_mul(&out, &base);
+        }
+        base = fr_mul(&base, &base);
+        exponent >>= 1;
+    }
+    out
+}
+
 pub fn scalar_from_fr(a: &blst_fr) -> blst_scalar {
     let mut ret = blst_scalar::default();
     unsafe {
@@ -1,6 +1,5 @@
 use super::Engine;
-use crate::{CeremonyError, Entropy, Tau, G1, G2};
-use rayon::join;
+use crate::{parallel::join, CeremonyError, Entropy, Powers, Tau, F, G1, G2};
 use secrecy::ExposeSecret;
 use std::marker::PhantomData;
 
@@ -12,6 +11,8 @@ pub struct Both<A: Engine, B: Engine> {
 }
 
 impl<A: Engine, B: Engine> Engine for Both<A, B> {
+    type ValidPowers = (A::ValidPowers, B::ValidPowers);
+
     fn validate_g1(points: &[G1]) -> Result<(), CeremonyError> {
         let (a, b) = join(|| A::validate_g1(points), || B::validate_g1(points));
         a?;
@@ -50,45 +51,99 @@ impl<A: Engine, B: Engine> Engine for Both<A, B> {
         Ok(())
     }
 
+    fn validate_powers(powers: &Powers, pubkey: G2) -> Result<Self::ValidPowers, CeremonyError> {
+        let (a, b) = join(
+            || A::validate_powers(powers, pubkey),
+            || B::validate_powers(powers, pubkey),
+        );
+        Ok((a?, b?))
+    }
+
+    fn verify_powers(powers: &Self::ValidPowers, previous: G1) -> Result<(), CeremonyError> {
+        let (a, b) = join(
+            || A::verify_powers(&powers.0, previous),
+            || B::verify_powers(&powers.1, previous),
+        );
+        a?;
+        b?;
+        Ok(())
+    }
+
     fn generate_tau(entropy: &Entropy) -> Tau {
         let (a, b) = join(|| A::generate_tau(entropy), || B::generate_tau(entropy));
         assert_eq!(a.expose_secret(), b.expose_secret());
         a
     }
 
-    fn add_tau_g1(tau: &Tau, powers: &mut [G1]) -> Result<(), CeremonyError> {
+    fn add_tau_g1_from(tau: &Tau, start: usize, powers: &mut [G1]) -> Result<(), CeremonyError> {
         let mut b = powers.to_vec();
-        let (ra, rb) = join(|| A::add_tau_g1(tau, powers), || B::add_tau_g1(tau, &mut b));
+        let (ra, rb) = join(
+            || A::add_tau_g1_from(tau, start, powers),
+            || B::add_tau_g1_from(tau, start, &mut b),
+        );
         ra?;
         rb?;
         assert_eq!(powers, &b[..]);
         Ok(())
     }
 
-    fn add_tau_g2(tau: &Tau, powers: &mut [G2]) -> Result<(), CeremonyError> {
+    fn add_tau_g2_from(tau: &Tau, start: usize, powers: &mut [G2]) -> Result<(), CeremonyError> {
         let mut b = powers.to_vec();
-        let (ra, rb) = join(|| A::add_tau_g2(tau, powers), || B::add_tau_g2(tau, &mut b));
+        let (ra, rb) = join(
+            || A::add_tau_g2_from(tau, start, powers),
+            || B::add_tau_g2_from(tau, start, &mut b),
+        );
         ra?;
         rb?;
         assert_eq!(powers, &b[..]);
         Ok(())
     }
 
-    fn sign_message(tau: &Tau, message: &[u8]) -> Option<G1> {
+    fn sign_message(tau: &Tau, message: &[u8], cipher_suite: &str) -> Option<G1> {
         let (a, b) = join(
-            || A::sign_message(tau, message),
-            || B::sign_message(tau, message),
+            || A::sign_message(tau, message, cipher_suite),
+            || B::sign_message(tau, message, cipher_suite),
         );
         assert_eq!(a, b);
         a
     }
 
-    fn verify_signature(sig: G1, message: &[u8], pk: G2) -> bool {
+    fn verify_signature(sig: G1, message: &[u8], pk: G2, cipher_suite: &str) -> bool {
         let (a, b) = join(
-            || A::verify_signature(sig, message, pk),
-            || B::verify_signature(sig, message, pk),
+            || A::verify_signature(sig, message, pk, cipher_suite),
+            || B::verify_signature(sig, message, pk, cipher_suite),
         );
         assert_eq!(a, b);
         a
     }
+
+    fn verify_signatures(signatures: &[(G1, &[u8], G2)], cipher_suite: &str) -> bool {
+        let (a, b) = join(
+            || A::verify_signatures(signatures, cipher_suite),
+            || B::verify_signatures(signatures, cipher_suite),
+        );
+        assert_eq!(a, b);
+        a
+    }
+
+    fn msm_g1(points: &[G1], scalars: &[F]) -> Result<G1, CeremonyError> {
+        let (a, b) = join(|| A::msm_g1(points, scalars), || B::msm_g1(points, scalars));
+        let (a, b) = (a?, b?);
+        assert_eq!(a, b);
+        Ok(a)
+    }
+
+    fn msm_g2(points: &[G2], scalars: &[F]) -> Result<G2, CeremonyError> {
+        let (a, b) = join(|| A::msm_g2(points, scalars), || B::msm_g2(points, scalars));
+        let (a, b) = (a?, b?);
+        assert_eq!(a, b);
+        Ok(a)
+    }
+
+    fn pairing_check(pairs: &[(G1, G2)]) -> Result<bool, CeremonyError> {
+        let (a, b) = join(|| A::pairing_check(pairs), || B::pairing_check(pairs));
+        let (a, b) = (a?, b?);
+        assert_eq!(a, b);
+        Ok(a)
+    }
 }
@@ -7,24 +7,44 @@
 
 #[cfg(feature = "arkworks")]
 mod arkworks;
+mod batched;
 #[cfg(feature = "blst")]
 mod blst;
 mod both;
+mod quorum;
+#[cfg(feature = "zkcrypto")]
+mod zkcrypto;
 
-use crate::{CeremonyError, F, G1, G2};
+use crate::{CeremonyError, Powers, F, G1, G2};
 pub use secrecy::Secret;
 
 #[cfg(feature = "arkworks")]
 pub use self::arkworks::Arkworks;
 #[cfg(feature = "blst")]
 pub use self::blst::BLST;
-pub use self::both::Both;
+#[cfg(feature = "zkcrypto")]
+pub use self::zkcrypto::Zkcrypto;
+pub use self::{
+    batched::{BatchSubgroupCheck, Batched},
+    both::Both,
+    quorum::{Member, Members, Quorum},
+};
 
 pub type Entropy = Secret<[u8; 32]>;
 pub type Tau = Secret<F>;
 
+/// The points of a contribution in the affine representation of an engine,
+/// see [`Engine::validate_powers`].
+pub struct Decompressed<P1, P2> {
+    pub g1:     Vec<P1>,
+    pub g2:     Vec<P2>,
+    pub pubkey: P2,
+}
+
 pub trait Engine {
-    const CYPHER_SUITE: &'static str = "BLS_SIG_BLS12381G1_XMD:SHA-256_SSWU_RO_POP_";
+    /// Validated and decompressed points of a contribution, so that the
+    /// pairing checks do not parse them again.
+    type ValidPowers: Send + Sync + 'static;
 
     /// Verifies that the given G1 points are valid.
     ///
@@ -71,6 +91,23 @@ pub trait Engine {
     /// exponents.
     fn verify_g2(g1: &[G1], g2: &[G2]) -> Result<(), CeremonyError>;
 
+    /// Validates the points of a contribution like [`Engine::validate_g1`] and
+    /// [`Engine::validate_g2`] do, and returns them decompressed.
+    ///
+    /// # Errors
+    /// Returns an error if any of `powers` or `pubkey` is not a valid point. An
+    /// invalid `pubkey` is reported as G2 power `0`.
+    fn validate_powers(powers: &Powers, pubkey: G2) -> Result<Self::ValidPowers, CeremonyError>;
+
+    /// Runs the checks of [`Engine::verify_pubkey`], [`Engine::verify_g1`] and
+    /// [`Engine::verify_g2`] on validated contribution points, where
+    /// `previous` is the first power of $τ$ in G1 before the contribution.
+    ///
+    /// # Errors
+    /// Returns an error if `previous` is invalid, or if any of the pairing
+    /// checks fails.
+    fn verify_powers(powers: &Self::ValidPowers, previous: G1) -> Result<(), CeremonyError>;
+
     /// Derive a secret scalar $τ$ from the given entropy.
     fn generate_tau(entropy: &Entropy) -> Tau;
 
@@ -78,28 +115,90 @@ pub trait Engine {
     ///
     /// # Errors
     /// Returns an error if any of `powers` is not a valid curve point.
-    fn add_tau_g1(tau: &Tau, powers: &mut [G1]) -> Result<(), CeremonyError>;
+    fn add_tau_g1(tau: &Tau, powers: &mut [G1]) -> Result<(), CeremonyError> {
+        Self::add_tau_g1_from(tau, 0, powers)
+    }
 
     /// Multiply elements of `powers` by powers of $τ$.
     ///
     /// # Errors
     /// Returns an error if any of `powers` is not a valid curve point.
-    fn add_tau_g2(tau: &Tau, powers: &mut [G2]) -> Result<(), CeremonyError>;
+    fn add_tau_g2(tau: &Tau, powers: &mut [G2]) -> Result<(), CeremonyError> {
+        Self::add_tau_g2_from(tau, 0, powers)
+    }
+
+    /// Multiply `powers[i]` by $τ^{start + i}$, so that a long list of powers
+    /// can be processed in chunks.
+    ///
+    /// # Errors
+    /// Returns an error if any of `powers` is not a valid curve point.
+    fn add_tau_g1_from(tau: &Tau, start: usize, powers: &mut [G1]) -> Result<(), CeremonyError>;
+
+    /// Multiply `powers[i]` by $τ^{start + i}$, so that a long list of powers
+    /// can be processed in chunks.
+    ///
+    /// # Errors
+    /// Returns an error if any of `powers` is not a valid curve point.
+    fn add_tau_g2_from(tau: &Tau, start: usize, powers: &mut [G2]) -> Result<(), CeremonyError>;
+
+    /// Sign a message with `cipher_suite`, using $τ$ as the secret key.
+    fn sign_message(tau: &Tau, message: &[u8], cipher_suite: &str) -> Option<G1>;
 
-    /// Sign a message with `CYPHER_SUITE`, using $τ$ as the secret key.
-    fn sign_message(tau: &Tau, message: &[u8]) -> Option<G1>;
+    /// Verify a `cipher_suite` signature.
+    fn verify_signature(sig: G1, message: &[u8], pk: G2, cipher_suite: &str) -> bool;
 
-    /// Verify a `CYPHER_SUITE` signature.
-    fn verify_signature(sig: G1, message: &[u8], pk: G2) -> bool;
+    /// Verify a batch of `cipher_suite` signatures `(sig, message, pk)` at
+    /// once.
+    ///
+    /// Returns `true` if all signatures are valid. Implementations may check a
+    /// random linear combination of the pairing equations, in which case an
+    /// invalid batch is accepted with negligible probability.
+    #[must_use]
+    fn verify_signatures(signatures: &[(G1, &[u8], G2)], cipher_suite: &str) -> bool {
+        signatures
+            .iter()
+            .all(|(sig, message, pk)| Self::verify_signature(*sig, message, *pk, cipher_suite))
+    }
+
+    /// Computes the multi-scalar multiplication $Σᵢ sᵢ ⋅ pᵢ$ of `points` and
+    /// `scalars`. Scalars are reduced modulo the group order.
+    ///
+    /// # Errors
+    /// Returns an error if any of `points` is not a valid point in the prime
+    /// order subgroup.
+    ///
+    /// # Panics
+    /// Panics if `points` and `scalars` differ in length.
+    fn msm_g1(points: &[G1], scalars: &[F]) -> Result<G1, CeremonyError>;
+
+    /// Computes the multi-scalar multiplication $Σᵢ sᵢ ⋅ pᵢ$ of `points` and
+    /// `scalars`. Scalars are reduced modulo the group order.
+    ///
+    /// # Errors
+    /// Returns an error if any of `points` is not a valid point in the prime
+    /// order subgroup.
+    ///
+    /// # Panics
+    /// Panics if `points` and `scalars` differ in length.
+    fn msm_g2(points: &[G2], scalars: &[F]) -> Result<G2, CeremonyError>;
+
+    /// Checks that the product of pairings $Πᵢ e(pᵢ, qᵢ)$ of `pairs`
+    /// $(pᵢ, qᵢ)$ is one.
+    ///
+    /// # Errors
+    /// Returns an error if any of the points is not a valid point in the prime
+    /// order subgroup. An invalid $pᵢ$ is reported as G1 power $i$, an invalid
+    /// $qᵢ$ as G2 power $i$.
+    fn pairing_check(pairs: &[(G1, G2)]) -> Result<bool, CeremonyError>;
 }
 
 #[cfg(all(test, feature = "arkworks", feature = "blst"))]
 pub mod tests {
     use super::*;
-    use crate::DefaultEngine;
-    use ark_bls12_381::G1Affine;
+    use crate::{parameters::MAINNET_CIPHER_SUITE, DefaultEngine};
+    use ark_bls12_381::{Fr, G1Affine};
     use hex_literal::hex;
-    use proptest::{arbitrary::any, proptest, strategy::Strategy};
+    use proptest::{arbitrary::any, collection::vec, proptest, strategy::Strategy};
     use secrecy::ExposeSecret;
 
     pub fn arb_f() -> impl Strategy<Value = F> {
@@ -118,10 +217,46 @@ pub mod tests {
         proptest::array::uniform32(any::<u8>())
     }
 
+    fn signed_batch(n: u8) -> Vec<(G1, Vec<u8>, G2)> {
+        (1..=n)
+            .map(|i| {
+                let tau = DefaultEngine::generate_tau(&Secret::new([i; 32]));
+                let message = format!("git|{i}|user{i}").into_bytes();
+                let sig =
+                    DefaultEngine::sign_message(&tau, &message, MAINNET_CIPHER_SUITE).unwrap();
+                let mut pk = [G2::one(), G2::one()];
+                DefaultEngine::add_tau_g2(&tau, &mut pk).unwrap();
+                (sig, message, pk[1])
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_verify_signatures() {
+        let mut batch = signed_batch(5);
+        let check = |batch: &[(G1, Vec<u8>, G2)]| {
+            let batch = batch
+                .iter()
+                .map(|(sig, message, pk)| (*sig, message.as_slice(), *pk))
+                .collect::<Vec<_>>();
+            let ark = Arkworks::verify_signatures(&batch, MAINNET_CIPHER_SUITE);
+            let blst = BLST::verify_signatures(&batch, MAINNET_CIPHER_SUITE);
+            assert_eq!(ark, blst);
+            ark
+        };
+        assert!(check(&[]));
+        assert!(check(&batch));
+        batch[3].1 = b"git|1|user1".to_vec();
+        assert!(!check(&batch));
+        batch[3].1 = b"git|4|user4".to_vec();
+        batch[2].2 = G2::zero();
+        assert!(!check(&batch));
+    }
+
     #[test]
     fn test_zeros_in_verify_signature() {
-        let r1 = Arkworks::verify_signature(G1::zero(), b"hello", G2::zero());
-        let r2 = BLST::verify_signature(G1::zero(), b"hello", G2::zero());
+        let r1 = Arkworks::verify_signature(G1::zero(), b"hello", G2::zero(), MAINNET_CIPHER_SUITE);
+        let r2 = BLST::verify_signature(G1::zero(), b"hello", G2::zero(), MAINNET_CIPHER_SUITE);
         assert_eq!(r1, r2);
     }
 
@@ -176,6 +311,38 @@ pub mod tests {
         });
     }
 
+    #[test]
+    fn test_add_tau_in_chunks() {
+        fn chunked<E: Engine>(tau: &Tau, split: usize, powers: &mut Powers) {
+            let (head, tail) = powers.g1.split_at_mut(split);
+            E::add_tau_g1_from(tau, 0, head).unwrap();
+            E::add_tau_g1_from(tau, split, tail).unwrap();
+            let (head, tail) = powers.g2.split_at_mut(split / 4);
+            E::add_tau_g2_from(tau, 0, head).unwrap();
+            E::add_tau_g2_from(tau, split / 4, tail).unwrap();
+        }
+
+        proptest!(|(tau in arb_f(), split in 0..=16_usize)| {
+            let tau = Secret::new(tau);
+            let mut expected = Powers::new(16, 4);
+            Arkworks::add_tau_g1(&tau, &mut expected.g1).unwrap();
+            Arkworks::add_tau_g2(&tau, &mut expected.g2).unwrap();
+
+            let mut powers = Powers::new(16, 4);
+            chunked::<Arkworks>(&tau, split, &mut powers);
+            assert_eq!(powers, expected);
+            let mut powers = Powers::new(16, 4);
+            chunked::<BLST>(&tau, split, &mut powers);
+            assert_eq!(powers, expected);
+            #[cfg(feature = "zkcrypto")]
+            {
+                let mut powers = Powers::new(16, 4);
+                chunked::<Zkcrypto>(&tau, split, &mut powers);
+                assert_eq!(powers, expected);
+            }
+        });
+    }
+
     #[test]
     fn test_tau_larger_than_modulus() {
         let f = F(hex!(
@@ -197,6 +364,101 @@ pub mod tests {
         assert_eq!(g2_1, g2_2);
     }
 
+    #[test]
+    fn test_verify_powers() {
+        fn check<E: Engine>(
+            powers: &Powers,
+            pubkey: G2,
+            previous: G1,
+        ) -> Result<(), CeremonyError> {
+            let valid = E::validate_powers(powers, pubkey)?;
+            E::verify_powers(&valid, previous)
+        }
+
+        proptest!(|(tau in arb_f())| {
+            let tau = Secret::new(tau);
+            let mut powers = Powers::new(8, 4);
+            DefaultEngine::add_tau_g1(&tau, &mut powers.g1).unwrap();
+            DefaultEngine::add_tau_g2(&tau, &mut powers.g2).unwrap();
+            let pubkey = powers.g2[1];
+            assert_eq!(check::<Arkworks>(&powers, pubkey, G1::one()), Ok(()));
+            assert_eq!(check::<BLST>(&powers, pubkey, G1::one()), Ok(()));
+            assert_eq!(check::<DefaultEngine>(&powers, pubkey, G1::one()), Ok(()));
+
+            // Previous power does not match the pubkey
+            assert_eq!(
+                check::<DefaultEngine>(&powers, pubkey, powers.g1[1]),
+                Err(CeremonyError::PubKeyPairingFailed)
+            );
+
+            // Invalid pubkey is reported as the first G2 power
+            assert!(matches!(
+                check::<DefaultEngine>(&powers, G2([0; 96]), G1::one()),
+                Err(CeremonyError::InvalidG2Power(0, _))
+            ));
+
+            // Powers out of order
+            powers.g1.swap(2, 3);
+            assert_eq!(
+                check::<Arkworks>(&powers, pubkey, G1::one()),
+                Err(CeremonyError::G1PairingFailed)
+            );
+            assert_eq!(
+                check::<BLST>(&powers, pubkey, G1::one()),
+                Err(CeremonyError::G1PairingFailed)
+            );
+        });
+    }
+
+    #[test]
+    fn test_msm() {
+        proptest!(|(points in vec(arb_g1(), 0..8), scalars in vec(arb_f(), 8), p in arb_g2(), s in arb_f())| {
+            let scalars = &scalars[..points.len()];
+            let expected = Arkworks::msm_g1(&points, scalars).unwrap();
+            assert_eq!(BLST::msm_g1(&points, scalars), Ok(expected));
+            #[cfg(feature = "zkcrypto")]
+            assert_eq!(Zkcrypto::msm_g1(&points, scalars), Ok(expected));
+
+            let expected = Arkworks::msm_g2(&[p, G2::one()], &[s, F::one()]).unwrap();
+            assert_eq!(BLST::msm_g2(&[p, G2::one()], &[s, F::one()]), Ok(expected));
+            #[cfg(feature = "zkcrypto")]
+            assert_eq!(Zkcrypto::msm_g2(&[p, G2::one()], &[s, F::one()]), Ok(expected));
+        });
+        assert_eq!(DefaultEngine::msm_g1(&[], &[]), Ok(G1::zero()));
+        assert_eq!(DefaultEngine::msm_g2(&[], &[]), Ok(G2::zero()));
+        assert!(matches!(
+            DefaultEngine::msm_g1(&[G1::one(), G1([0; 48])], &[F::one(), F::one()]),
+            Err(CeremonyError::InvalidG1Power(1, _))
+        ));
+    }
+
+    #[test]
+    fn test_pairing_check() {
+        fn check(pairs: &[(G1, G2)]) -> Result<bool, CeremonyError> {
+            let result = DefaultEngine::pairing_check(pairs);
+            #[cfg(feature = "zkcrypto")]
+            assert_eq!(Zkcrypto::pairing_check(pairs).ok(), result.ok());
+            result
+        }
+
+        proptest!(|(s in arb_f())| {
+            // e([s]₁, [1]₂) ⋅ e([-1]₁, [s]₂) = 1
+            let minus_one = F::from(-Fr::from(&F::one()));
+            let g1 = DefaultEngine::msm_g1(&[G1::one()], &[s]).unwrap();
+            let g2 = DefaultEngine::msm_g2(&[G2::one()], &[s]).unwrap();
+            let minus_g1 = DefaultEngine::msm_g1(&[G1::one()], &[minus_one]).unwrap();
+            assert_eq!(check(&[(g1, G2::one()), (minus_g1, g2)]), Ok(true));
+            assert_eq!(check(&[(g1, G2::one()), (G1::one(), g2)]), Ok(s == F::zero()));
+        });
+        assert_eq!(check(&[]), Ok(true));
+        assert_eq!(check(&[(G1::zero(), G2::one())]), Ok(true));
+        assert_eq!(check(&[(G1::one(), G2::one())]), Ok(false));
+        assert!(matches!(
+            check(&[(G1::one(), G2::one()), (G1::one(), G2([0; 96]))]),
+            Err(CeremonyError::InvalidG2Power(1, _))
+        ));
+    }
+
     #[test]
     fn test_validate_g1() {
         let g1 = G1([0u8; 48]);
@@ -228,6 +490,14 @@ pub mod bench {
         blst::bench::group(criterion);
         #[cfg(all(feature = "arkworks", feature = "blst"))]
         bench_engine::<Both<Arkworks, BLST>>(criterion, "both");
+        #[cfg(feature = "arkworks")]
+        bench_engine::<Batched<Arkworks>>(criterion, "arkworks-batched");
+        #[cfg(feature = "blst")]
+        bench_engine::<Batched<BLST>>(criterion, "blst-batched");
+        #[cfg(feature = "zkcrypto")]
+        bench_engine::<Zkcrypto>(criterion, "zkcrypto");
+        #[cfg(all(feature = "arkworks", feature = "blst", feature = "zkcrypto"))]
+        bench_engine::<Quorum<(Arkworks, BLST, Zkcrypto)>>(criterion, "quorum");
     }
 
     pub(super) fn bench_engine<E: Engine>(criterion: &mut Criterion, name: &str) {
@@ -236,6 +506,7 @@ pub mod bench {
         bench_verify_pubkey::<E>(criterion, name);
         bench_verify_g1::<E>(criterion, name);
         bench_verify_g2::<E>(criterion, name);
+        bench_verify_contribution::<E>(criterion, name);
         bench_generate_tau::<E>(criterion, name);
         bench_add_tau_g1::<E>(criterion, name);
         bench_add_tau_g2::<E>(criterion, name);
@@ -350,6 +621,38 @@ pub mod bench {
         }
     }
 
+    /// Compares verifying a contribution with the compressed point methods,
+    /// which parse every point for each check, to decompressing it once.
+    fn bench_verify_contribution<E: Engine>(criterion: &mut Criterion, name: &str) {
+        const SIZE: (usize, usize) = (32768, 65);
+        let tau = rand_tau();
+        let mut powers = Powers::new(SIZE.0, SIZE.1);
+        Arkworks::add_tau_g1(&tau, &mut powers.g1).unwrap();
+        Arkworks::add_tau_g2(&tau, &mut powers.g2).unwrap();
+        let pubkey = powers.g2[1];
+        let previous = G1::one();
+
+        let id = format!("engine/{name}/verify_contribution/compressed");
+        criterion.bench_with_input(BenchmarkId::new(id, SIZE.0), &powers, |bencher, powers| {
+            bencher.iter(|| {
+                E::validate_g1(&powers.g1).unwrap();
+                E::validate_g2(&powers.g2).unwrap();
+                E::validate_g2(&[pubkey]).unwrap();
+                E::verify_pubkey(powers.g1[1], previous, pubkey).unwrap();
+                E::verify_g1(&powers.g1, powers.g2[1]).unwrap();
+                E::verify_g2(&powers.g1[..powers.g2.len()], &powers.g2).unwrap();
+            });
+        });
+
+        let id = format!("engine/{name}/verify_contribution/decompressed");
+        criterion.bench_with_input(BenchmarkId::new(id, SIZE.0), &powers, |bencher, powers| {
+            bencher.iter(|| {
+                let valid = E::validate_powers(powers, pubkey).unwrap();
+                E::verify_powers(&valid, previous).unwrap();
+            });
+        });
+    }
+
     fn bench_generate_tau<E: Engine>(criterion: &mut Criterion, name: &str) {
         let id = format!("engine/{name}/generate_tau");
         criterion.bench_function(&id, move |bencher| {
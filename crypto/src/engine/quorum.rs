@@ -0,0 +1,473 @@
+use super::Engine;
+use crate::{parallel::prelude::*, CeremonyError, Entropy, Powers, Tau, F, G1, G2};
+use secrecy::ExposeSecret;
+use std::{
+    any::{type_name, Any},
+    marker::PhantomData,
+};
+use tracing::error;
+
+/// Implementation of [`Engine`] that runs three or more engines and returns
+/// the outcome of the majority.
+///
+/// Where [`Both`](super::Both) can only tell that two engines disagree,
+/// `Quorum` also tells which engine diverged. Every divergence is logged as an
+/// error with the names of the engines in the minority. Without a strict
+/// majority it panics, like `Both` does on any disagreement.
+///
+/// The engines are given as a tuple, for example
+/// `Quorum<(Arkworks, BLST, Zkcrypto)>`.
+pub struct Quorum<M: Members> {
+    _members: PhantomData<M>,
+}
+
+/// [`Engine::ValidPowers`] of any member.
+type AnyPowers = Box<dyn Any + Send + Sync>;
+
+/// Signatures `(sig, message, pk)` verified as one batch.
+type Signatures<'a> = [(G1, &'a [u8], G2)];
+
+/// Pairs `(p, q)` of a pairing check.
+type Pairs = [(G1, G2)];
+
+/// A tuple of engines that can form a [`Quorum`].
+pub trait Members {
+    fn members() -> Vec<Member>;
+}
+
+/// The methods of an [`Engine`] as function pointers, so that different
+/// engines can be called in a loop.
+pub struct Member {
+    name:              &'static str,
+    validate_g1:       fn(&[G1]) -> Result<(), CeremonyError>,
+    validate_g2:       fn(&[G2]) -> Result<(), CeremonyError>,
+    verify_pubkey:     fn(G1, G1, G2) -> Result<(), CeremonyError>,
+    verify_g1:         fn(&[G1], G2) -> Result<(), CeremonyError>,
+    verify_g2:         fn(&[G1], &[G2]) -> Result<(), CeremonyError>,
+    validate_powers:   fn(&Powers, G2) -> Result<AnyPowers, CeremonyError>,
+    verify_powers:     fn(&AnyPowers, G1) -> Result<(), CeremonyError>,
+    generate_tau:      fn(&Entropy) -> Tau,
+    add_tau_g1_from:   fn(&Tau, usize, &mut [G1]) -> Result<(), CeremonyError>,
+    add_tau_g2_from:   fn(&Tau, usize, &mut [G2]) -> Result<(), CeremonyError>,
+    sign_message:      fn(&Tau, &[u8], &str) -> Option<G1>,
+    verify_signature:  fn(G1, &[u8], G2, &str) -> bool,
+    verify_signatures: fn(&Signatures, &str) -> bool,
+    msm_g1:            fn(&[G1], &[F]) -> Result<G1, CeremonyError>,
+    msm_g2:            fn(&[G2], &[F]) -> Result<G2, CeremonyError>,
+    pairing_check:     fn(&Pairs) -> Result<bool, CeremonyError>,
+}
+
+impl Member {
+    /// # Panics
+    /// The resulting `verify_powers` panics when given powers validated by a
+    /// different engine.
+    #[must_use]
+    pub fn of<E: Engine>() -> Self {
+        Self {
+            name:              type_name::<E>(),
+            validate_g1:       E::validate_g1,
+            validate_g2:       E::validate_g2,
+            verify_pubkey:     E::verify_pubkey,
+            verify_g1:         E::verify_g1,
+            verify_g2:         E::verify_g2,
+            validate_powers:   |powers, pubkey| {
+                E::validate_powers(powers, pubkey).map(|p| Box::new(p) as AnyPowers)
+            },
+            verify_powers:     |powers, previous| {
+                let powers = powers
+                    .downcast_ref()
+                    .expect("points are validated by the same engine");
+                E::verify_powers(powers, previous)
+            },
+            generate_tau:      E::generate_tau,
+            add_tau_g1_from:   E::add_tau_g1_from,
+            add_tau_g2_from:   E::add_tau_g2_from,
+            sign_message:      E::sign_message,
+            verify_signature:  E::verify_signature,
+            verify_signatures: E::verify_signatures,
+            msm_g1:            E::msm_g1,
+            msm_g2:            E::msm_g2,
+            pairing_check:     E::pairing_check,
+        }
+    }
+}
+
+macro_rules! impl_members {
+    ($($engine:ident),+) => {
+        impl<$($engine: Engine),+> Members for ($($engine,)+) {
+            fn members() -> Vec<Member> {
+                vec![$(Member::of::<$engine>()),+]
+            }
+        }
+    };
+}
+
+impl_members!(A, B, C);
+impl_members!(A, B, C, D);
+impl_members!(A, B, C, D, E);
+
+impl<M: Members> Quorum<M> {
+    /// Runs `call` on all members in parallel and returns the majority
+    /// outcome, where outcomes are compared with `same`.
+    fn run<T, C, S>(method: &str, call: C, same: S) -> T
+    where
+        T: Send,
+        C: Fn(&Member) -> T + Send + Sync,
+        S: Fn(&T, &T) -> bool,
+    {
+        let members = M::members();
+        let outcomes = members.par_iter().map(call).collect::<Vec<_>>();
+        decide(method, &members, outcomes, same)
+    }
+
+    fn run_results<C>(method: &str, call: C) -> Result<(), CeremonyError>
+    where
+        C: Fn(&Member) -> Result<(), CeremonyError> + Send + Sync,
+    {
+        // Engines may report different errors for the same invalid input.
+        Self::run(method, call, |a, b| a.is_ok() == b.is_ok())
+    }
+
+    fn run_add_tau<P>(
+        method: &str,
+        powers: &mut [P],
+        call: impl Fn(&Member, &mut [P]) -> Result<(), CeremonyError> + Send + Sync,
+    ) -> Result<(), CeremonyError>
+    where
+        P: Copy + PartialEq + Send + Sync,
+    {
+        let input = powers.to_vec();
+        let (result, output) = Self::run(
+            method,
+            |member| {
+                let mut output = input.clone();
+                let result = call(member, &mut output);
+                (result, output)
+            },
+            |(ra, a), (rb, b)| match (ra, rb) {
+                (Ok(()), Ok(())) => a == b,
+                (Err(_), Err(_)) => true,
+                _ => false,
+            },
+        );
+        result?;
+        powers.copy_from_slice(&output);
+        Ok(())
+    }
+}
+
+/// Compares outcomes that carry a value on success. Engines may report
+/// different errors for the same invalid input.
+fn same_output<T: PartialEq>(a: &Result<T, CeremonyError>, b: &Result<T, CeremonyError>) -> bool {
+    match (a, b) {
+        (Ok(a), Ok(b)) => a == b,
+        (Err(_), Err(_)) => true,
+        _ => false,
+    }
+}
+
+/// Returns the majority outcome and logs the members that diverged from it.
+fn decide<T>(
+    method: &str,
+    members: &[Member],
+    outcomes: Vec<T>,
+    same: impl Fn(&T, &T) -> bool,
+) -> T {
+    let names = members.iter().map(|m| m.name).collect::<Vec<_>>();
+    let (outcome, diverged) = vote(&names, outcomes, same);
+    if !diverged.is_empty() {
+        error!(method, ?diverged, "engines diverged from the quorum");
+    }
+    outcome
+}
+
+/// Returns the outcome that a strict majority of `outcomes` agrees on, and
+/// the names of the members that disagree with it.
+///
+/// # Panics
+/// Panics if there is no strict majority.
+fn vote<T>(
+    names: &[&'static str],
+    mut outcomes: Vec<T>,
+    same: impl Fn(&T, &T) -> bool,
+) -> (T, Vec<&'static str>) {
+    assert_eq!(names.len(), outcomes.len());
+    let (winner, votes) = outcomes
+        .iter()
+        .enumerate()
+        .map(|(i, a)| (i, outcomes.iter().filter(|b| same(a, b)).count()))
+        .max_by_key(|(_, votes)| *votes)
+        .expect("quorum has no members");
+    let diverged = names
+        .iter()
+        .zip(&outcomes)
+        .filter(|(_, outcome)| !same(&outcomes[winner], outcome))
+        .map(|(name, _)| *name)
+        .collect::<Vec<_>>();
+    assert!(
+        2 * votes > outcomes.len(),
+        "no majority among engines, diverged: {diverged:?}"
+    );
+    (outcomes.swap_remove(winner), diverged)
+}
+
+impl<M: Members> Engine for Quorum<M> {
+    /// The points as validated by each member, or the error it found.
+    type ValidPowers = Vec<Result<AnyPowers, CeremonyError>>;
+
+    fn validate_g1(points: &[G1]) -> Result<(), CeremonyError> {
+        Self::run_results("validate_g1", |m| (m.validate_g1)(points))
+    }
+
+    fn validate_g2(points: &[G2]) -> Result<(), CeremonyError> {
+        Self::run_results("validate_g2", |m| (m.validate_g2)(points))
+    }
+
+    fn verify_pubkey(tau: G1, previous: G1, pubkey: G2) -> Result<(), CeremonyError> {
+        Self::run_results("verify_pubkey", |m| {
+            (m.verify_pubkey)(tau, previous, pubkey)
+        })
+    }
+
+    fn verify_g1(powers: &[G1], tau: G2) -> Result<(), CeremonyError> {
+        Self::run_results("verify_g1", |m| (m.verify_g1)(powers, tau))
+    }
+
+    fn verify_g2(g1: &[G1], g2: &[G2]) -> Result<(), CeremonyError> {
+        Self::run_results("verify_g2", |m| (m.verify_g2)(g1, g2))
+    }
+
+    fn validate_powers(powers: &Powers, pubkey: G2) -> Result<Self::ValidPowers, CeremonyError> {
+        let members = M::members();
+        let validated = members
+            .par_iter()
+            .map(|m| (m.validate_powers)(powers, pubkey))
+            .collect::<Vec<_>>();
+        let outcomes = validated
+            .iter()
+            .map(|v| v.as_ref().map(|_| ()).map_err(|e| *e))
+            .collect();
+        decide("validate_powers", &members, outcomes, |a, b| {
+            a.is_ok() == b.is_ok()
+        })?;
+        Ok(validated)
+    }
+
+    fn verify_powers(powers: &Self::ValidPowers, previous: G1) -> Result<(), CeremonyError> {
+        let members = M::members();
+        let outcomes = members
+            .par_iter()
+            .zip(powers)
+            .map(|(m, validated)| match validated {
+                Ok(validated) => (m.verify_powers)(validated, previous),
+                // Diverged in validation already, keep its error.
+                Err(e) => Err(*e),
+            })
+            .collect::<Vec<_>>();
+        decide("verify_powers", &members, outcomes, |a, b| {
+            a.is_ok() == b.is_ok()
+        })
+    }
+
+    fn generate_tau(entropy: &Entropy) -> Tau {
+        Self::run(
+            "generate_tau",
+            |m| (m.generate_tau)(entropy),
+            |a, b| a.expose_secret() == b.expose_secret(),
+        )
+    }
+
+    fn add_tau_g1_from(tau: &Tau, start: usize, powers: &mut [G1]) -> Result<(), CeremonyError> {
+        Self::run_add_tau("add_tau_g1", powers, |m, p| {
+            (m.add_tau_g1_from)(tau, start, p)
+        })
+    }
+
+    fn add_tau_g2_from(tau: &Tau, start: usize, powers: &mut [G2]) -> Result<(), CeremonyError> {
+        Self::run_add_tau("add_tau_g2", powers, |m, p| {
+            (m.add_tau_g2_from)(tau, start, p)
+        })
+    }
+
+    fn sign_message(tau: &Tau, message: &[u8], cipher_suite: &str) -> Option<G1> {
+        Self::run(
+            "sign_message",
+            |m| (m.sign_message)(tau, message, cipher_suite),
+            PartialEq::eq,
+        )
+    }
+
+    fn verify_signature(sig: G1, message: &[u8], pk: G2, cipher_suite: &str) -> bool {
+        Self::run(
+            "verify_signature",
+            |m| (m.verify_signature)(sig, message, pk, cipher_suite),
+            PartialEq::eq,
+        )
+    }
+
+    fn verify_signatures(signatures: &[(G1, &[u8], G2)], cipher_suite: &str) -> bool {
+        Self::run(
+            "verify_signatures",
+            |m| (m.verify_signatures)(signatures, cipher_suite),
+            PartialEq::eq,
+        )
+    }
+
+    fn msm_g1(points: &[G1], scalars: &[F]) -> Result<G1, CeremonyError> {
+        Self::run("msm_g1", |m| (m.msm_g1)(points, scalars), same_output)
+    }
+
+    fn msm_g2(points: &[G2], scalars: &[F]) -> Result<G2, CeremonyError> {
+        Self::run("msm_g2", |m| (m.msm_g2)(points, scalars), same_output)
+    }
+
+    fn pairing_check(pairs: &[(G1, G2)]) -> Result<bool, CeremonyError> {
+        Self::run("pairing_check", |m| (m.pairing_check)(pairs), same_output)
+    }
+}
+
+#[cfg(all(test, feature = "arkworks", feature = "blst"))]
+mod tests {
+    use super::*;
+    use crate::{
+        engine::tests::{arb_f, arb_g1},
+        parameters::MAINNET_CIPHER_SUITE,
+        Arkworks, BLST,
+    };
+    use proptest::proptest;
+    use secrecy::Secret;
+
+    /// Arkworks, except that it gets the last power of `add_tau_g1` wrong and
+    /// accepts any signature.
+    struct Faulty;
+
+    impl Engine for Faulty {
+        type ValidPowers = <Arkworks as Engine>::ValidPowers;
+
+        fn validate_g1(points: &[G1]) -> Result<(), CeremonyError> {
+            Arkworks::validate_g1(points)
+        }
+
+        fn validate_g2(points: &[G2]) -> Result<(), CeremonyError> {
+            Arkworks::validate_g2(points)
+        }
+
+        fn verify_pubkey(tau: G1, previous: G1, pubkey: G2) -> Result<(), CeremonyError> {
+            Arkworks::verify_pubkey(tau, previous, pubkey)
+        }
+
+        fn verify_g1(powers: &[G1], tau: G2) -> Result<(), CeremonyError> {
+            Arkworks::verify_g1(powers, tau)
+        }
+
+        fn verify_g2(g1: &[G1], g2: &[G2]) -> Result<(), CeremonyError> {
+            Arkworks::verify_g2(g1, g2)
+        }
+
+        fn validate_powers(
+            powers: &Powers,
+            pubkey: G2,
+        ) -> Result<Self::ValidPowers, CeremonyError> {
+            Arkworks::validate_powers(powers, pubkey)
+        }
+
+        fn verify_powers(powers: &Self::ValidPowers, previous: G1) -> Result<(), CeremonyError> {
+            Arkworks::verify_powers(powers, previous)
+        }
+
+        fn generate_tau(entropy: &Entropy) -> Tau {
+            Arkworks::generate_tau(entropy)
+        }
+
+        fn add_tau_g1_from(
+            tau: &Tau,
+            start: usize,
+            powers: &mut [G1],
+        ) -> Result<(), CeremonyError> {
+            Arkworks::add_tau_g1_from(tau, start, powers)?;
+            if let Some(last) = powers.last_mut() {
+                *last = G1::one();
+            }
+            Ok(())
+        }
+
+        fn add_tau_g2_from(
+            tau: &Tau,
+            start: usize,
+            powers: &mut [G2],
+        ) -> Result<(), CeremonyError> {
+            Arkworks::add_tau_g2_from(tau, start, powers)
+        }
+
+        fn sign_message(tau: &Tau, message: &[u8], cipher_suite: &str) -> Option<G1> {
+            Arkworks::sign_message(tau, message, cipher_suite)
+        }
+
+        fn verify_signature(_sig: G1, _message: &[u8], _pk: G2, _cipher_suite: &str) -> bool {
+            true
+        }
+
+        fn msm_g1(points: &[G1], scalars: &[F]) -> Result<G1, CeremonyError> {
+            Arkworks::msm_g1(points, scalars)
+        }
+
+        fn msm_g2(points: &[G2], scalars: &[F]) -> Result<G2, CeremonyError> {
+            Arkworks::msm_g2(points, scalars)
+        }
+
+        fn pairing_check(pairs: &[(G1, G2)]) -> Result<bool, CeremonyError> {
+            Arkworks::pairing_check(pairs)
+        }
+    }
+
+    type TestQuorum = Quorum<(Arkworks, BLST, Faulty)>;
+
+    #[test]
+    fn test_vote() {
+        let names = ["a", "b", "c"];
+        assert_eq!(vote(&names, vec![1, 1, 1], PartialEq::eq), (1, vec![]));
+        assert_eq!(vote(&names, vec![1, 2, 1], PartialEq::eq), (1, vec!["b"]));
+        assert_eq!(vote(&names, vec![2, 1, 1], PartialEq::eq), (1, vec!["a"]));
+    }
+
+    #[test]
+    #[should_panic(expected = "no majority")]
+    fn test_vote_no_majority() {
+        vote(&["a", "b", "c"], vec![1, 2, 3], PartialEq::eq);
+    }
+
+    #[test]
+    fn test_member_names() {
+        let names = <(Arkworks, BLST, Faulty)>::members()
+            .iter()
+            .map(|m| m.name)
+            .collect::<Vec<_>>();
+        assert!(names[0].ends_with("Arkworks"));
+        assert!(names[1].ends_with("BLST"));
+        assert!(names[2].ends_with("Faulty"));
+    }
+
+    #[test]
+    fn test_outvotes_faulty_engine() {
+        proptest!(|(tau in arb_f(), g1 in arb_g1())| {
+            let tau = Secret::new(tau);
+            let mut expected = [g1; 4];
+            let mut powers = [g1; 4];
+            Arkworks::add_tau_g1(&tau, &mut expected).unwrap();
+            TestQuorum::add_tau_g1(&tau, &mut powers).unwrap();
+            assert_eq!(powers, expected);
+
+            let mut pk = [G2::one(), G2::one()];
+            BLST::add_tau_g2(&tau, &mut pk).unwrap();
+            let sig = TestQuorum::sign_message(&tau, b"hello", MAINNET_CIPHER_SUITE).unwrap();
+            assert!(TestQuorum::verify_signature(sig, b"hello", pk[1], MAINNET_CIPHER_SUITE));
+            assert!(!TestQuorum::verify_signature(sig, b"other", pk[1], MAINNET_CIPHER_SUITE));
+        });
+    }
+
+    #[test]
+    fn test_invalid_points() {
+        assert!(TestQuorum::validate_g1(&[G1([0; 48])]).is_err());
+        assert!(TestQuorum::add_tau_g1(&Secret::new(F::zero()), &mut [G1([0; 48])]).is_err());
+    }
+}
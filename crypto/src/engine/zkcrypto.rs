@@ -0,0 +1,527 @@
+//! Implementation of [`Engine`] on the pure Rust `bls12_381` crate by zkcrypto.
+//!
+//! This engine is not optimized. It shares no code with [`Arkworks`] or
+//! [`BLST`], so it can serve as a third opinion when the two disagree, see
+//! [`Quorum`].
+//!
+//! [`Arkworks`]: super::Arkworks
+//! [`BLST`]: super::BLST
+//! [`Quorum`]: super::Quorum
+
+#![cfg(feature = "zkcrypto")]
+
+use super::{Decompressed, Engine};
+use crate::{CeremonyError, Entropy, ParseError, Powers, Tau, F, G1, G2};
+use bls12_381::{
+    hash_to_curve::{ExpandMsgXmd, HashToCurve},
+    multi_miller_loop, pairing, G1Affine, G1Projective, G2Affine, G2Prepared, G2Projective, Gt,
+    Scalar,
+};
+use digest::Digest;
+use ff::Field;
+use hkdf::Hkdf;
+use rand::{Rng, SeedableRng};
+use rand_chacha::ChaCha20Rng;
+use rayon::prelude::*;
+use secrecy::{ExposeSecret, Secret, SecretVec};
+use sha2::Sha256;
+use std::iter;
+use zeroize::{Zeroize, Zeroizing};
+
+pub struct Zkcrypto;
+
+impl Engine for Zkcrypto {
+    type ValidPowers = Decompressed<G1Affine, G2Affine>;
+
+    fn validate_g1(points: &[G1]) -> Result<(), CeremonyError> {
+        decompress_g1(points).map(|_| ())
+    }
+
+    fn validate_g2(points: &[G2]) -> Result<(), CeremonyError> {
+        decompress_g2(points).map(|_| ())
+    }
+
+    fn verify_pubkey(tau: G1, previous: G1, pubkey: G2) -> Result<(), CeremonyError> {
+        let tau = G1Affine::try_from(tau)?;
+        let previous = G1Affine::try_from(previous)?;
+        let pubkey = G2Affine::try_from(pubkey)?;
+        check_pubkey(&tau, &previous, &pubkey)
+    }
+
+    fn verify_g1(powers: &[G1], tau: G2) -> Result<(), CeremonyError> {
+        // Parse ZCash format
+        let powers = powers
+            .into_par_iter()
+            .map(|p| G1Affine::try_from(*p))
+            .collect::<Result<Vec<_>, _>>()?;
+        let tau = G2Affine::try_from(tau)?;
+        check_g1(&powers, &tau)
+    }
+
+    fn verify_g2(g1: &[G1], g2: &[G2]) -> Result<(), CeremonyError> {
+        assert!(g1.len() == g2.len());
+
+        // Parse ZCash format
+        let g1 = g1
+            .into_par_iter()
+            .map(|p| G1Affine::try_from(*p))
+            .collect::<Result<Vec<_>, _>>()?;
+        let g2 = g2
+            .into_par_iter()
+            .map(|p| G2Affine::try_from(*p))
+            .collect::<Result<Vec<_>, _>>()?;
+        check_g2(&g1, &g2)
+    }
+
+    fn validate_powers(powers: &Powers, pubkey: G2) -> Result<Self::ValidPowers, CeremonyError> {
+        Ok(Decompressed {
+            g1:     decompress_g1(&powers.g1)?,
+            g2:     decompress_g2(&powers.g2)?,
+            pubkey: decompress_g2(&[pubkey])?[0],
+        })
+    }
+
+    fn verify_powers(powers: &Self::ValidPowers, previous: G1) -> Result<(), CeremonyError> {
+        let previous = G1Affine::try_from(previous)?;
+        check_pubkey(&powers.g1[1], &previous, &powers.pubkey)?;
+        check_g1(&powers.g1, &powers.g2[1])?;
+        check_g2(&powers.g1[..powers.g2.len()], &powers.g2)
+    }
+
+    fn generate_tau(entropy: &Entropy) -> Tau {
+        // Use ChaCha20 CPRNG
+        let mut rng = ChaCha20Rng::from_seed(*entropy.expose_secret());
+
+        // Generate tau by reducing 512 bits of entropy modulo prime.
+        let mut large = [0_u8; 64];
+        rng.fill(&mut large);
+        let scalar = bls_keygen(large);
+        large.zeroize();
+
+        Secret::new(F(scalar.to_bytes()))
+    }
+
+    fn add_tau_g1_from(tau: &Tau, start: usize, powers: &mut [G1]) -> Result<(), CeremonyError> {
+        let taus = powers_of_tau(tau, start, powers.len());
+        let projective = powers
+            .par_iter()
+            .zip(taus.expose_secret())
+            .map(|(p, tau)| G1Affine::try_from(*p).map(|p| p * tau))
+            .collect::<Result<Vec<_>, _>>()?;
+        let mut affine = vec![G1Affine::identity(); projective.len()];
+        G1Projective::batch_normalize(&projective, &mut affine);
+        for (p, a) in powers.iter_mut().zip(affine) {
+            *p = a.into();
+        }
+        Ok(())
+    }
+
+    fn add_tau_g2_from(tau: &Tau, start: usize, powers: &mut [G2]) -> Result<(), CeremonyError> {
+        let taus = powers_of_tau(tau, start, powers.len());
+        let projective = powers
+            .par_iter()
+            .zip(taus.expose_secret())
+            .map(|(p, tau)| G2Affine::try_from(*p).map(|p| p * tau))
+            .collect::<Result<Vec<_>, _>>()?;
+        let mut affine = vec![G2Affine::identity(); projective.len()];
+        G2Projective::batch_normalize(&projective, &mut affine);
+        for (p, a) in powers.iter_mut().zip(affine) {
+            *p = a.into();
+        }
+        Ok(())
+    }
+
+    fn sign_message(tau: &Tau, message: &[u8], cipher_suite: &str) -> Option<G1> {
+        let point = hash_to_g1(message, cipher_suite);
+        let tau = Zeroizing::new(scalar_from_f(tau.expose_secret()));
+        let sig = point * *tau;
+        Some(G1Affine::from(sig).into())
+    }
+
+    fn verify_signature(sig: G1, message: &[u8], pk: G2, cipher_suite: &str) -> bool {
+        let sig = match G1Affine::try_from(sig) {
+            Ok(sig) => sig,
+            _ => return false,
+        };
+        let pk = match G2Affine::try_from(pk) {
+            Ok(pk) => pk,
+            _ => return false,
+        };
+        if bool::from(pk.is_identity()) {
+            // Rejecting infinite public keys to match BLST behavior
+            return false;
+        }
+        let msg = G1Affine::from(hash_to_g1(message, cipher_suite));
+        pairing(&msg, &pk) == pairing(&sig, &G2Affine::generator())
+    }
+
+    fn verify_signatures(signatures: &[(G1, &[u8], G2)], cipher_suite: &str) -> bool {
+        if signatures.is_empty() {
+            return true;
+        }
+
+        // Parse and hash, with the same checks as `verify_signature`.
+        let parsed = signatures
+            .par_iter()
+            .map(|(sig, message, pk)| {
+                let sig = G1Affine::try_from(*sig).ok()?;
+                let pk = G2Affine::try_from(*pk).ok()?;
+                if bool::from(pk.is_identity()) {
+                    return None;
+                }
+                Some((sig, hash_to_g1(message, cipher_suite), pk))
+            })
+            .collect::<Option<Vec<_>>>();
+        let parsed = match parsed {
+            Some(parsed) => parsed,
+            None => return false,
+        };
+
+        // Check e(Σ rᵢ sigᵢ, g₂) = Π e(rᵢ H(mᵢ), pkᵢ) for random rᵢ
+        let (factors, _) = random_factors(parsed.len());
+        let sigs = parsed.iter().map(|(sig, ..)| *sig).collect::<Vec<_>>();
+        let lhs = G1Affine::from(-g1_linear_combination(&sigs, &factors));
+        let mut pairs = parsed
+            .par_iter()
+            .zip(&factors)
+            .map(|((_, msg, pk), factor)| (G1Affine::from(msg * factor), G2Prepared::from(*pk)))
+            .collect::<Vec<_>>();
+        pairs.push((lhs, G2Prepared::from(G2Affine::generator())));
+        let terms = pairs.iter().map(|(p, q)| (p, q)).collect::<Vec<_>>();
+        multi_miller_loop(&terms).final_exponentiation() == Gt::identity()
+    }
+
+    fn msm_g1(points: &[G1], scalars: &[F]) -> Result<G1, CeremonyError> {
+        assert_eq!(points.len(), scalars.len());
+        let points = decompress_g1(points)?;
+        let scalars = scalars.par_iter().map(scalar_from_f).collect::<Vec<_>>();
+        Ok(G1Affine::from(g1_linear_combination(&points, &scalars)).into())
+    }
+
+    fn msm_g2(points: &[G2], scalars: &[F]) -> Result<G2, CeremonyError> {
+        assert_eq!(points.len(), scalars.len());
+        let points = decompress_g2(points)?;
+        let scalars = scalars.par_iter().map(scalar_from_f).collect::<Vec<_>>();
+        Ok(G2Affine::from(g2_linear_combination(&points, &scalars)).into())
+    }
+
+    fn pairing_check(pairs: &[(G1, G2)]) -> Result<bool, CeremonyError> {
+        let (g1, g2): (Vec<_>, Vec<_>) = pairs.iter().copied().unzip();
+        let g1 = decompress_g1(&g1)?;
+        let g2 = decompress_g2(&g2)?
+            .into_iter()
+            .map(G2Prepared::from)
+            .collect::<Vec<_>>();
+        let terms = g1.iter().zip(&g2).collect::<Vec<_>>();
+        Ok(multi_miller_loop(&terms).final_exponentiation() == Gt::identity())
+    }
+}
+
+impl TryFrom<G1> for G1Affine {
+    type Error = ParseError;
+
+    fn try_from(g1: G1) -> Result<Self, Self::Error> {
+        let p = Option::<Self>::from(Self::from_compressed_unchecked(&g1.0))
+            .ok_or(ParseError::InvalidCompression)?;
+        if !bool::from(p.is_torsion_free()) {
+            return Err(ParseError::InvalidSubgroup);
+        }
+        Ok(p)
+    }
+}
+
+impl From<G1Affine> for G1 {
+    fn from(g1: G1Affine) -> Self {
+        Self(g1.to_compressed())
+    }
+}
+
+impl TryFrom<G2> for G2Affine {
+    type Error = ParseError;
+
+    fn try_from(g2: G2) -> Result<Self, Self::Error> {
+        let p = Option::<Self>::from(Self::from_compressed_unchecked(&g2.0))
+            .ok_or(ParseError::InvalidCompression)?;
+        if !bool::from(p.is_torsion_free()) {
+            return Err(ParseError::InvalidSubgroup);
+        }
+        Ok(p)
+    }
+}
+
+impl From<G2Affine> for G2 {
+    fn from(g2: G2Affine) -> Self {
+        Self(g2.to_compressed())
+    }
+}
+
+/// Parses `points`, which includes the subgroup check.
+fn decompress_g1(points: &[G1]) -> Result<Vec<G1Affine>, CeremonyError> {
+    points
+        .into_par_iter()
+        .enumerate()
+        .map(|(i, p)| G1Affine::try_from(*p).map_err(|e| CeremonyError::InvalidG1Power(i, e)))
+        .collect()
+}
+
+/// Parses `points`, which includes the subgroup check.
+fn decompress_g2(points: &[G2]) -> Result<Vec<G2Affine>, CeremonyError> {
+    points
+        .into_par_iter()
+        .enumerate()
+        .map(|(i, p)| G2Affine::try_from(*p).map_err(|e| CeremonyError::InvalidG2Power(i, e)))
+        .collect()
+}
+
+fn check_pubkey(
+    tau: &G1Affine,
+    previous: &G1Affine,
+    pubkey: &G2Affine,
+) -> Result<(), CeremonyError> {
+    if pairing(tau, &G2Affine::generator()) != pairing(previous, pubkey) {
+        return Err(CeremonyError::PubKeyPairingFailed);
+    }
+    Ok(())
+}
+
+fn check_g1(powers: &[G1Affine], tau: &G2Affine) -> Result<(), CeremonyError> {
+    // Compute random linear combination
+    let (factors, sum) = random_factors(powers.len() - 1);
+    let lhs_g1 = g1_linear_combination(&powers[1..], &factors);
+    let lhs_g2 = G2Affine::generator() * sum;
+    let rhs_g1 = g1_linear_combination(&powers[..factors.len()], &factors);
+    let rhs_g2 = tau * sum;
+
+    // Check pairing
+    if pairing(&G1Affine::from(lhs_g1), &G2Affine::from(lhs_g2))
+        != pairing(&G1Affine::from(rhs_g1), &G2Affine::from(rhs_g2))
+    {
+        return Err(CeremonyError::G1PairingFailed);
+    }
+    Ok(())
+}
+
+fn check_g2(g1: &[G1Affine], g2: &[G2Affine]) -> Result<(), CeremonyError> {
+    // Compute random linear combination
+    let (factors, sum) = random_factors(g2.len());
+    let lhs_g1 = g1_linear_combination(g1, &factors);
+    let lhs_g2 = G2Affine::generator() * sum;
+    let rhs_g1 = G1Affine::generator() * sum;
+    let rhs_g2 = g2_linear_combination(g2, &factors);
+
+    // Check pairing
+    if pairing(&G1Affine::from(lhs_g1), &G2Affine::from(lhs_g2))
+        != pairing(&G1Affine::from(rhs_g1), &G2Affine::from(rhs_g2))
+    {
+        return Err(CeremonyError::G2PairingFailed);
+    }
+    Ok(())
+}
+
+/// Reduces the little-endian bytes of `f` modulo the group order.
+fn scalar_from_f(f: &F) -> Scalar {
+    let mut wide = [0_u8; 64];
+    wide[..32].copy_from_slice(&f.0);
+    let scalar = Scalar::from_bytes_wide(&wide);
+    wide.zeroize();
+    scalar
+}
+
+fn hash_to_g1(message: &[u8], cipher_suite: &str) -> G1Projective {
+    <G1Projective as HashToCurve<ExpandMsgXmd<sha2_09::Sha256>>>::hash_to_curve(
+        message,
+        cipher_suite.as_bytes(),
+    )
+}
+
+// Implementation of the KeyGen function as specified in
+// https://datatracker.ietf.org/doc/draft-irtf-cfrg-bls-signature/
+fn bls_keygen(ikm: [u8; 64]) -> Scalar {
+    // the `L` value, precomputed from the formula given in the spec
+    const L: u8 = 48;
+    let mut full_ikm = [0u8; 65];
+    full_ikm[..64].copy_from_slice(&ikm);
+    full_ikm[64] = 0;
+    let key_info = [0, L];
+
+    let mut hasher = Sha256::new();
+    hasher.update(b"BLS-SIG-KEYGEN-SALT-");
+    let mut salt = hasher.finalize();
+
+    loop {
+        let hk = Hkdf::<Sha256>::new(Some(&salt), &full_ikm);
+        let mut out = [0; L as usize];
+        hk.expand(&key_info, &mut out).unwrap();
+        // OS2IP of the big-endian output, reduced modulo the group order.
+        let mut wide = [0_u8; 64];
+        for (w, o) in wide.iter_mut().zip(out.iter().rev()) {
+            *w = *o;
+        }
+        let scalar = Scalar::from_bytes_wide(&wide);
+        wide.zeroize();
+        out.zeroize();
+        if scalar != Scalar::ZERO {
+            full_ikm.zeroize();
+            return scalar;
+        }
+        hasher = Sha256::new();
+        hasher.update(salt);
+        salt = hasher.finalize();
+    }
+}
+
+fn powers_of_tau(tau: &Tau, start: usize, n: usize) -> SecretVec<Scalar> {
+    // Convert tau
+    let tau = Zeroizing::new(scalar_from_f(tau.expose_secret()));
+    // The exponent is public, so it can be variable time.
+    let first = tau.pow_vartime(&[start as u64, 0, 0, 0]);
+
+    // Compute powers
+    SecretVec::new(
+        iter::successors(Some(first), |x| Some(x * *tau))
+            .take(n)
+            .collect(),
+    )
+}
+
+fn random_factors(n: usize) -> (Vec<Scalar>, Scalar) {
+    let mut rng = rand::thread_rng();
+    let mut sum = Scalar::ZERO;
+    let factors = iter::from_fn(|| {
+        let r = Scalar::random(&mut rng);
+        sum += r;
+        Some(r)
+    })
+    .take(n)
+    .collect::<Vec<_>>();
+    (factors, sum)
+}
+
+fn g1_linear_combination(points: &[G1Affine], factors: &[Scalar]) -> G1Projective {
+    points
+        .par_iter()
+        .zip(factors)
+        .map(|(p, f)| p * f)
+        .reduce(G1Projective::identity, |a, b| a + b)
+}
+
+fn g2_linear_combination(points: &[G2Affine], factors: &[Scalar]) -> G2Projective {
+    points
+        .par_iter()
+        .zip(factors)
+        .map(|(p, f)| p * f)
+        .reduce(G2Projective::identity, |a, b| a + b)
+}
+
+#[cfg(all(test, feature = "arkworks", feature = "blst"))]
+mod tests {
+    use super::*;
+    use crate::{
+        engine::tests::{arb_entropy, arb_f, arb_g1, arb_g2},
+        parameters::MAINNET_CIPHER_SUITE,
+        Arkworks, BLST,
+    };
+    use proptest::{collection::vec, proptest};
+
+    #[test]
+    fn test_generate_tau() {
+        proptest!(|(entropy in arb_entropy())| {
+            let entropy = Secret::new(entropy);
+            let tau = Zkcrypto::generate_tau(&entropy);
+            assert_eq!(tau.expose_secret(), Arkworks::generate_tau(&entropy).expose_secret());
+            assert_eq!(tau.expose_secret(), BLST::generate_tau(&entropy).expose_secret());
+        });
+    }
+
+    #[test]
+    fn test_add_tau() {
+        proptest!(|(tau in arb_f(), g1 in arb_g1(), g2 in arb_g2())| {
+            let tau = Secret::new(tau);
+            let mut expected_g1 = [g1; 8];
+            let mut expected_g2 = [g2; 4];
+            Arkworks::add_tau_g1(&tau, &mut expected_g1).unwrap();
+            BLST::add_tau_g2(&tau, &mut expected_g2).unwrap();
+            let mut g1 = [g1; 8];
+            let mut g2 = [g2; 4];
+            Zkcrypto::add_tau_g1(&tau, &mut g1).unwrap();
+            Zkcrypto::add_tau_g2(&tau, &mut g2).unwrap();
+            assert_eq!(g1, expected_g1);
+            assert_eq!(g2, expected_g2);
+        });
+    }
+
+    #[test]
+    fn test_verify() {
+        proptest!(|(tau in arb_f(), g1 in arb_g1())| {
+            let tau = Secret::new(tau);
+            let mut powers = vec![g1; 8];
+            Zkcrypto::add_tau_g1(&tau, &mut powers).unwrap();
+            let mut pubkey = [G2::one(), G2::one()];
+            Zkcrypto::add_tau_g2(&tau, &mut pubkey).unwrap();
+            let mut g2 = vec![G2::one(); 4];
+            Zkcrypto::add_tau_g2(&tau, &mut g2).unwrap();
+            let mut g1 = vec![G1::one(); 4];
+            Zkcrypto::add_tau_g1(&tau, &mut g1).unwrap();
+
+            assert!(Zkcrypto::verify_g1(&powers, pubkey[1]).is_ok());
+            assert!(Zkcrypto::verify_g2(&g1, &g2).is_ok());
+            assert!(Zkcrypto::verify_pubkey(g1[1], G1::one(), pubkey[1]).is_ok());
+
+            // Wrong tau
+            assert_eq!(
+                Zkcrypto::verify_g1(&powers, G2::one()).is_ok(),
+                Arkworks::verify_g1(&powers, G2::one()).is_ok()
+            );
+            g1.swap(1, 2);
+            assert_eq!(
+                Zkcrypto::verify_g2(&g1, &g2).is_ok(),
+                BLST::verify_g2(&g1, &g2).is_ok()
+            );
+        });
+    }
+
+    #[test]
+    fn test_validate() {
+        proptest!(|(bytes in vec(0_u8.., 48))| {
+            let mut g1 = G1([0; 48]);
+            g1.0.copy_from_slice(&bytes);
+            let result = Zkcrypto::validate_g1(&[g1]).is_ok();
+            assert_eq!(result, Arkworks::validate_g1(&[g1]).is_ok());
+            assert_eq!(result, BLST::validate_g1(&[g1]).is_ok());
+        });
+        proptest!(|(g1 in arb_g1(), g2 in arb_g2())| {
+            assert!(Zkcrypto::validate_g1(&[g1, G1::zero()]).is_ok());
+            assert!(Zkcrypto::validate_g2(&[g2, G2::zero()]).is_ok());
+        });
+        assert!(Zkcrypto::validate_g1(&[G1([0; 48])]).is_err());
+        assert!(Zkcrypto::validate_g2(&[G2([0; 96])]).is_err());
+    }
+
+    #[test]
+    fn test_sign() {
+        proptest!(|(tau in arb_f(), message in ".*")| {
+            let tau = Secret::new(tau);
+            let message = message.as_bytes();
+            let sig = Zkcrypto::sign_message(&tau, message, MAINNET_CIPHER_SUITE);
+            assert_eq!(sig, Arkworks::sign_message(&tau, message, MAINNET_CIPHER_SUITE));
+            assert_eq!(sig, BLST::sign_message(&tau, message, MAINNET_CIPHER_SUITE));
+
+            let mut pk = [G2::one(), G2::one()];
+            Zkcrypto::add_tau_g2(&tau, &mut pk).unwrap();
+            let sig = sig.unwrap();
+            let valid = Zkcrypto::verify_signature(sig, message, pk[1], MAINNET_CIPHER_SUITE);
+            assert_eq!(valid, BLST::verify_signature(sig, message, pk[1], MAINNET_CIPHER_SUITE));
+            assert!(!Zkcrypto::verify_signature(sig, b"other", pk[1], MAINNET_CIPHER_SUITE));
+            assert_eq!(
+                Zkcrypto::verify_signatures(&[(sig, message, pk[1])], MAINNET_CIPHER_SUITE),
+                valid
+            );
+        });
+        assert!(!Zkcrypto::verify_signature(
+            G1::zero(),
+            b"hello",
+            G2::zero(),
+            MAINNET_CIPHER_SUITE
+        ));
+    }
+}
@@ -11,6 +11,29 @@ pub enum CeremoniesError {
     UnexpectedNumContributions(usize, usize),
     #[error("Error in contribution {0}: {1}")]
     InvalidCeremony(usize, #[source] CeremonyError),
+    #[error("Contributions {0} and {1} reuse the same potPubkey")]
+    DuplicatePubkey(usize, usize),
+    #[error("Ethereum participants must sign the contribution")]
+    MissingEcdsaSignature,
+    #[error("ECDSA signature is not by the participant's address")]
+    InvalidEcdsaSignature,
+    #[error("Operation was cancelled")]
+    Cancelled,
+    #[error("Identity is not a random beacon")]
+    NotABeacon,
+    #[error("Ceremony was finalized by a random beacon")]
+    Finalized,
+}
+
+impl CeremoniesError {
+    /// Attributes `error` to the contribution at `index`, except for
+    /// cancellation which applies to the whole batch.
+    pub(crate) const fn in_ceremony(index: usize, error: CeremonyError) -> Self {
+        match error {
+            CeremonyError::Cancelled => Self::Cancelled,
+            error => Self::InvalidCeremony(index, error),
+        }
+    }
 }
 
 impl ErrorCode for CeremoniesError {
@@ -59,6 +82,12 @@ pub enum CeremonyError {
     G2PairingFailed,
     #[error("pubkey is zero")]
     ZeroPubkey,
+    #[error("potPubkey was already contributed by participant {0}")]
+    ReusedPubkey(usize),
+    #[error("BLS signature is missing")]
+    MissingBlsSignature,
+    #[error("BLS signature does not verify against potPubkey")]
+    InvalidBlsSignature,
     #[error("g1[{0}] is zero")]
     ZeroG1(usize),
     #[error("g2[{0}] is zero")]
@@ -81,6 +110,18 @@ pub enum CeremonyError {
     ContributionNoEntropy,
     #[error("Mismatch in witness length: {0} products and {1} pubkeys")]
     WitnessLengthMismatch(usize, usize),
+    #[error("Operation was cancelled")]
+    Cancelled,
+    #[error("Polynomial has {0} coefficients, but there are only {1} G1 powers")]
+    TooManyCoefficients(usize, usize),
+    #[error("KZG opening proof does not verify")]
+    InvalidOpeningProof,
+    #[error("potPubkey of participant {0} is not derived from its beacon")]
+    InvalidBeaconPubkey(usize),
+    #[error("Running product {0} does not match potPubkey {0}")]
+    WitnessPairingFailed(usize),
+    #[error("Last running product does not match g1[1]")]
+    WitnessPowersMismatch,
 }
 
 impl ErrorCode for CeremonyError {
@@ -130,4 +171,14 @@ fn test_error_codes() {
         )
         .to_error_code()
     );
+
+    assert_eq!(
+        "CeremoniesError::DuplicatePubkey",
+        CeremoniesError::DuplicatePubkey(0, 2).to_error_code()
+    );
+
+    assert_eq!(
+        "CeremonyError::ReusedPubkey",
+        CeremoniesError::InvalidCeremony(1, CeremonyError::ReusedPubkey(3)).to_error_code()
+    );
 }
@@ -1,6 +1,8 @@
 //! BLS12-381 group elements in ZCash encoding.
 
 use crate::hex_format::{bytes_to_hex, hex_to_bytes};
+use ark_bls12_381::Fr;
+use ark_ff::{BigInteger, PrimeField};
 use hex_literal::hex;
 use serde::{Deserialize, Deserializer, Serialize, Serializer};
 use zeroize::Zeroize;
@@ -102,6 +104,22 @@ impl<'de> Deserialize<'de> for G2 {
     }
 }
 
+impl From<&F> for Fr {
+    fn from(f: &F) -> Self {
+        Self::from_le_bytes_mod_order(&f.0[..])
+    }
+}
+
+impl From<Fr> for F {
+    fn from(fr: Fr) -> Self {
+        let le_bytes = fr.into_repr().to_bytes_le();
+        assert!(le_bytes.len() == 32);
+        let mut f = [0u8; 32];
+        f.copy_from_slice(&le_bytes[..]);
+        Self(f)
+    }
+}
+
 #[cfg(test)]
 pub mod tests {
     use crate::{G1, G2};
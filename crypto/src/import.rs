@@ -0,0 +1,415 @@
+//! Powers of $τ$ from earlier ceremonies, as the starting point of a new one.
+//!
+//! Only setups on BLS12-381 can be imported. Two file formats are supported:
+//!
+//! * [`ImportFormat::Ptau`], the `.ptau` files of [snarkjs](https://github.com/iden3/snarkjs),
+//!   with the curve `bls12381`. The $τ$ powers are read from sections 2 and 3,
+//!   where points are stored uncompressed with coordinates in little-endian
+//!   Montgomery form.
+//! * [`ImportFormat::Response`], the `response` files of the powers of tau
+//!   ceremonies run with the [`powersoftau`](https://github.com/ebfull/powersoftau)
+//!   code and its perpetual successors. They start with a 64 byte hash of the
+//!   challenge, followed by the compressed $τ$ powers in G1 and G2, and the
+//!   number of powers follows from the file size.
+//!
+//! The imported powers are recorded in the transcript with an
+//! [`Identity::Import`] as the first participant, which holds the SHA-256 hash
+//! of the imported file.
+
+use crate::{signature::identity::Identity, ErrorCode, Powers, G1, G2};
+use ark_bls12_381::{Fq, Fq2, FqParameters, G1Affine, G2Affine};
+use ark_ff::{BigInteger, BigInteger384, FpParameters, PrimeField, Zero};
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+use strum::{Display, EnumString, IntoStaticStr};
+use thiserror::Error;
+
+/// Bytes in an element of the BLS12-381 base field.
+const FQ_SIZE: usize = 48;
+
+/// Bytes in the hash of the challenge that a response file starts with.
+const RESPONSE_HASH_SIZE: usize = 64;
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Display, EnumString, IntoStaticStr)]
+#[strum(serialize_all = "lowercase")]
+pub enum ImportFormat {
+    /// A snarkjs `.ptau` file.
+    Ptau,
+    /// A powers of tau `response` file.
+    Response,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Error, IntoStaticStr)]
+pub enum ImportError {
+    #[error("Not a ptau file")]
+    InvalidPtauHeader,
+    #[error("ptau file has no section {0}")]
+    MissingPtauSection(u32),
+    #[error("ptau file is not for the BLS12-381 curve")]
+    UnsupportedCurve,
+    #[error("Response file of {0} bytes does not match any number of powers")]
+    InvalidResponseSize(usize),
+    #[error("Not enough G1 powers: needed {0}, got {1}")]
+    NotEnoughG1Powers(usize, usize),
+    #[error("Not enough G2 powers: needed {0}, got {1}")]
+    NotEnoughG2Powers(usize, usize),
+    #[error("G1 power {0} is not a valid curve point")]
+    InvalidG1Power(usize),
+    #[error("G2 power {0} is not a valid curve point")]
+    InvalidG2Power(usize),
+    #[error("File is truncated")]
+    Truncated,
+}
+
+impl ErrorCode for ImportError {
+    fn to_error_code(&self) -> String {
+        format!("ImportError::{}", <&str>::from(self))
+    }
+}
+
+impl ImportFormat {
+    /// Reads the first `num_g1` G1 and `num_g2` G2 powers of $τ$ from the
+    /// contents of a file in this format. The points are not subgroup
+    /// checked, this is left to
+    /// [`BatchTranscript::import`](crate::BatchTranscript::import).
+    ///
+    /// # Errors
+    /// Returns an error if the file is malformed, is not for BLS12-381, or has
+    /// fewer powers.
+    pub fn read_powers(
+        self,
+        bytes: &[u8],
+        num_g1: usize,
+        num_g2: usize,
+    ) -> Result<Powers, ImportError> {
+        match self {
+            Self::Ptau => read_ptau(bytes, num_g1, num_g2),
+            Self::Response => read_response(bytes, num_g1, num_g2),
+        }
+    }
+
+    /// Returns the identity that records an import of `bytes` in the
+    /// transcript.
+    #[must_use]
+    pub fn provenance(self, bytes: &[u8]) -> Identity {
+        Identity::Import {
+            format: self,
+            hash:   Sha256::digest(bytes).into(),
+        }
+    }
+}
+
+fn read_ptau(bytes: &[u8], num_g1: usize, num_g2: usize) -> Result<Powers, ImportError> {
+    let mut reader = Reader(bytes);
+    if reader.take(4)? != b"ptau" {
+        return Err(ImportError::InvalidPtauHeader);
+    }
+    let _version = reader.u32()?;
+    let num_sections = reader.u32()?;
+    let mut sections = HashMap::new();
+    for _ in 0..num_sections {
+        let section = reader.u32()?;
+        let size = usize::try_from(reader.u64()?).map_err(|_| ImportError::Truncated)?;
+        sections.insert(section, reader.take(size)?);
+    }
+    let section = |id| {
+        sections
+            .get(&id)
+            .map(|bytes| Reader(bytes))
+            .ok_or(ImportError::MissingPtauSection(id))
+    };
+
+    // Section 1 is the header: the size and modulus of the base field, and
+    // the log₂ of the number of powers.
+    let mut header = section(1)?;
+    let modulus = FqParameters::MODULUS.to_bytes_le();
+    if header.u32()? as usize != FQ_SIZE || header.take(FQ_SIZE)? != modulus {
+        return Err(ImportError::UnsupportedCurve);
+    }
+    let power = header.u32()?;
+    check_available(
+        1_usize.checked_shl(power).unwrap_or(usize::MAX),
+        num_g1,
+        num_g2,
+    )?;
+
+    let mut g1_section = section(2)?;
+    let g1 = (0..num_g1)
+        .map(|i| {
+            let x = g1_section.fq()?;
+            let y = g1_section.fq()?;
+            x.zip(y)
+                .and_then(|(x, y)| ptau_g1(x, y))
+                .ok_or(ImportError::InvalidG1Power(i))
+        })
+        .collect::<Result<_, _>>()?;
+    let mut g2_section = section(3)?;
+    let g2 = (0..num_g2)
+        .map(|i| {
+            let x = g2_section.fq()?.zip(g2_section.fq()?);
+            let y = g2_section.fq()?.zip(g2_section.fq()?);
+            x.zip(y)
+                .and_then(|((x0, x1), (y0, y1))| ptau_g2(Fq2::new(x0, x1), Fq2::new(y0, y1)))
+                .ok_or(ImportError::InvalidG2Power(i))
+        })
+        .collect::<Result<_, _>>()?;
+    Ok(Powers { g1, g2 })
+}
+
+fn read_response(bytes: &[u8], num_g1: usize, num_g2: usize) -> Result<Powers, ImportError> {
+    // For n powers, a response holds the hash, 2n - 1 τ powers in G1, n τ
+    // powers in G2, 2n α and β powers in G1, β in G2 and the public key of
+    // six uncompressed G1 and three uncompressed G2 points.
+    const PER_POWER: usize = 2 * 48 + 96 + 2 * 48;
+    const FIXED: usize = RESPONSE_HASH_SIZE - 48 + 96 + 6 * 96 + 3 * 192;
+    let available = bytes
+        .len()
+        .checked_sub(FIXED)
+        .filter(|size| size % PER_POWER == 0)
+        .map(|size| size / PER_POWER)
+        .filter(|&available| available > 0)
+        .ok_or(ImportError::InvalidResponseSize(bytes.len()))?;
+    check_available(available, num_g1, num_g2)?;
+
+    // Points are in the compressed ZCash format, like in the transcript.
+    let (g1_bytes, rest) = bytes[RESPONSE_HASH_SIZE..].split_at((2 * available - 1) * 48);
+    let g1 = g1_bytes
+        .chunks_exact(48)
+        .take(num_g1)
+        .map(|chunk| G1(chunk.try_into().unwrap()))
+        .collect();
+    let g2 = rest
+        .chunks_exact(96)
+        .take(num_g2)
+        .map(|chunk| G2(chunk.try_into().unwrap()))
+        .collect();
+    Ok(Powers { g1, g2 })
+}
+
+/// Checks that a setup with `available` powers in G2, and twice as many in G1,
+/// has enough of them.
+const fn check_available(
+    available: usize,
+    num_g1: usize,
+    num_g2: usize,
+) -> Result<(), ImportError> {
+    let available_g1 = available.saturating_mul(2) - 1;
+    if num_g1 > available_g1 {
+        return Err(ImportError::NotEnoughG1Powers(num_g1, available_g1));
+    }
+    if num_g2 > available {
+        return Err(ImportError::NotEnoughG2Powers(num_g2, available));
+    }
+    Ok(())
+}
+
+/// Reads little-endian integers and field elements from the front of a slice.
+struct Reader<'a>(&'a [u8]);
+
+impl<'a> Reader<'a> {
+    fn take(&mut self, n: usize) -> Result<&'a [u8], ImportError> {
+        if self.0.len() < n {
+            return Err(ImportError::Truncated);
+        }
+        let (head, tail) = self.0.split_at(n);
+        self.0 = tail;
+        Ok(head)
+    }
+
+    fn u32(&mut self) -> Result<u32, ImportError> {
+        Ok(u32::from_le_bytes(self.take(4)?.try_into().unwrap()))
+    }
+
+    fn u64(&mut self) -> Result<u64, ImportError> {
+        Ok(u64::from_le_bytes(self.take(8)?.try_into().unwrap()))
+    }
+
+    /// Reads a field element in Montgomery form. Returns `None` for values
+    /// that are not reduced.
+    fn fq(&mut self) -> Result<Option<Fq>, ImportError> {
+        let mut limbs = [0_u64; 6];
+        for (limb, chunk) in limbs.iter_mut().zip(self.take(FQ_SIZE)?.chunks_exact(8)) {
+            *limb = u64::from_le_bytes(chunk.try_into().unwrap());
+        }
+        let montgomery = BigInteger384::new(limbs);
+        Ok((montgomery < FqParameters::MODULUS).then(|| Fq::new(montgomery)))
+    }
+}
+
+/// Compresses a ptau point, where the point at infinity has zero coordinates.
+fn ptau_g1(x: Fq, y: Fq) -> Option<G1> {
+    if x.is_zero() && y.is_zero() {
+        return Some(G1(compress([], true, false)));
+    }
+    let point = G1Affine::new(x, y, false);
+    point
+        .is_on_curve()
+        .then(|| G1(compress([x], false, y > -y)))
+}
+
+fn ptau_g2(x: Fq2, y: Fq2) -> Option<G2> {
+    if x.is_zero() && y.is_zero() {
+        return Some(G2(compress([], true, false)));
+    }
+    let point = G2Affine::new(x, y, false);
+    point
+        .is_on_curve()
+        .then(|| G2(compress([x.c1, x.c0], false, y > -y)))
+}
+
+/// Compresses a point to its x coordinate with the flags for infinity and the
+/// larger y coordinate, as in the transcript.
+fn compress<const N: usize, const M: usize>(x: [Fq; M], infinity: bool, greatest: bool) -> [u8; N] {
+    let mut bytes = [0_u8; N];
+    for (chunk, element) in bytes.chunks_exact_mut(FQ_SIZE).zip(x) {
+        chunk.copy_from_slice(&element.into_repr().to_bytes_be());
+    }
+    bytes[0] |= 0x80;
+    if infinity {
+        bytes[0] |= 0x40;
+    }
+    if greatest {
+        bytes[0] |= 0x20;
+    }
+    bytes
+}
+
+#[cfg(all(test, feature = "arkworks"))]
+pub mod tests {
+    use super::*;
+    use crate::{Arkworks, Engine, Secret, F};
+    use ark_bls12_381::Fr;
+
+    /// The first powers of $τ = 2$.
+    fn powers(num_g1: usize, num_g2: usize) -> Powers {
+        let tau = Secret::new(F::from(Fr::from(2)));
+        let mut powers = Powers::new(num_g1, num_g2);
+        Arkworks::add_tau_g1(&tau, &mut powers.g1).unwrap();
+        Arkworks::add_tau_g2(&tau, &mut powers.g2).unwrap();
+        powers
+    }
+
+    fn fq_bytes(element: Fq) -> Vec<u8> {
+        element.0.to_bytes_le()
+    }
+
+    /// Writes `powers` as a ptau file with `2^power` powers.
+    fn ptau(powers: &Powers, power: u32) -> Vec<u8> {
+        let mut header = 48_u32.to_le_bytes().to_vec();
+        header.extend(FqParameters::MODULUS.to_bytes_le());
+        header.extend(power.to_le_bytes());
+        header.extend(power.to_le_bytes());
+        let g1 = powers
+            .g1
+            .iter()
+            .flat_map(|p| {
+                let p = G1Affine::try_from(*p).unwrap();
+                [fq_bytes(p.x), fq_bytes(p.y)].concat()
+            })
+            .collect::<Vec<_>>();
+        let g2 = powers
+            .g2
+            .iter()
+            .flat_map(|p| {
+                let p = G2Affine::try_from(*p).unwrap();
+                [
+                    fq_bytes(p.x.c0),
+                    fq_bytes(p.x.c1),
+                    fq_bytes(p.y.c0),
+                    fq_bytes(p.y.c1),
+                ]
+                .concat()
+            })
+            .collect::<Vec<_>>();
+
+        let mut file = b"ptau".to_vec();
+        file.extend(1_u32.to_le_bytes());
+        file.extend(3_u32.to_le_bytes());
+        for (id, section) in [(1_u32, header), (2, g1), (3, g2)] {
+            file.extend(id.to_le_bytes());
+            file.extend((section.len() as u64).to_le_bytes());
+            file.extend(section);
+        }
+        file
+    }
+
+    /// Writes `powers` as a response file, with zero bytes for everything but
+    /// the τ powers.
+    fn response(powers: &Powers) -> Vec<u8> {
+        let n = powers.g2.len();
+        assert_eq!(powers.g1.len(), 2 * n - 1);
+        let mut file = vec![0_u8; RESPONSE_HASH_SIZE];
+        file.extend(powers.g1.iter().flat_map(|p| p.0));
+        file.extend(powers.g2.iter().flat_map(|p| p.0));
+        file.extend(vec![0_u8; 2 * n * 48 + 96 + 6 * 96 + 3 * 192]);
+        file
+    }
+
+    #[test]
+    fn test_read_ptau() {
+        let expected = powers(7, 4);
+        let file = ptau(&expected, 2);
+        assert_eq!(ImportFormat::Ptau.read_powers(&file, 7, 4), Ok(expected));
+        assert_eq!(
+            ImportFormat::Ptau.read_powers(&file, 4, 2),
+            Ok(powers(4, 2))
+        );
+        assert_eq!(
+            ImportFormat::Ptau.read_powers(&file, 8, 4),
+            Err(ImportError::NotEnoughG1Powers(8, 7))
+        );
+        assert_eq!(
+            ImportFormat::Ptau.read_powers(&file, 4, 5),
+            Err(ImportError::NotEnoughG2Powers(5, 4))
+        );
+        assert_eq!(
+            ImportFormat::Ptau.read_powers(&file[..file.len() - 1], 7, 4),
+            Err(ImportError::Truncated)
+        );
+        assert_eq!(
+            ImportFormat::Ptau.read_powers(b"ptaf", 7, 4),
+            Err(ImportError::InvalidPtauHeader)
+        );
+
+        // A point that is not on the curve.
+        let mut invalid = file.clone();
+        let offset = file.len() - 4 * 192 + 12;
+        invalid[offset] ^= 1;
+        assert_eq!(
+            ImportFormat::Ptau.read_powers(&invalid, 7, 4),
+            Err(ImportError::InvalidG2Power(0))
+        );
+    }
+
+    #[test]
+    fn test_read_response() {
+        let expected = powers(7, 4);
+        let file = response(&expected);
+        assert_eq!(
+            ImportFormat::Response.read_powers(&file, 7, 4),
+            Ok(expected)
+        );
+        assert_eq!(
+            ImportFormat::Response.read_powers(&file, 4, 2),
+            Ok(powers(4, 2))
+        );
+        assert_eq!(
+            ImportFormat::Response.read_powers(&file, 4, 5),
+            Err(ImportError::NotEnoughG2Powers(5, 4))
+        );
+        assert_eq!(
+            ImportFormat::Response.read_powers(&file[1..], 4, 2),
+            Err(ImportError::InvalidResponseSize(file.len() - 1))
+        );
+    }
+
+    #[test]
+    fn test_provenance() {
+        let identity = ImportFormat::Response.provenance(b"");
+        assert_eq!(
+            identity.to_string(),
+            "import|response|0xe3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855"
+        );
+        assert_eq!(identity, identity.to_string().parse().unwrap());
+    }
+}
@@ -0,0 +1,180 @@
+//! KZG polynomial commitments using [`Powers`], to check end to end that a
+//! completed setup works.
+//!
+//! A polynomial $p$ is given by its coefficients, lowest degree first. The
+//! commitment to it is $C = [p(τ)]₁$. The proof that $p(z) = y$ is
+//! $π = [q(τ)]₁$ with the quotient $q(X) = (p(X) - y) / (X - z)$, and it is
+//! checked with the pairing equation $e(C - [y]₁, [1]₂) = e(π, [τ - z]₂)$.
+
+use crate::{CeremonyError, Engine, Powers, F, G1, G2};
+use ark_bls12_381::Fr;
+use ark_ff::{UniformRand, Zero};
+use std::iter;
+
+impl Powers {
+    /// Commits to the polynomial with `coefficients`.
+    ///
+    /// # Errors
+    /// Returns an error if there are more coefficients than G1 powers, or if
+    /// any of the powers is invalid.
+    pub fn commit<E: Engine>(&self, coefficients: &[F]) -> Result<G1, CeremonyError> {
+        if coefficients.len() > self.g1.len() {
+            return Err(CeremonyError::TooManyCoefficients(
+                coefficients.len(),
+                self.g1.len(),
+            ));
+        }
+        E::msm_g1(&self.g1[..coefficients.len()], coefficients)
+    }
+
+    /// Evaluates the polynomial with `coefficients` at `point`, and returns
+    /// the value with a proof of the evaluation.
+    ///
+    /// # Errors
+    /// Returns an error if there are more coefficients than G1 powers, or if
+    /// any of the powers is invalid.
+    pub fn open<E: Engine>(&self, coefficients: &[F], point: F) -> Result<(F, G1), CeremonyError> {
+        let (quotient, value) = divide_by_linear(coefficients, point);
+        let proof = self.commit::<E>(&quotient)?;
+        Ok((value, proof))
+    }
+
+    /// Verifies that the polynomial committed to in `commitment` evaluates to
+    /// `value` at `point`, given the `proof` from [`Powers::open`].
+    ///
+    /// # Errors
+    /// Returns [`CeremonyError::InvalidOpeningProof`] if the proof does not
+    /// verify, or an error if there are fewer than two G2 powers or any of
+    /// the points is invalid.
+    pub fn verify_opening<E: Engine>(
+        &self,
+        commitment: G1,
+        point: F,
+        value: F,
+        proof: G1,
+    ) -> Result<(), CeremonyError> {
+        if self.g2.len() < 2 {
+            return Err(CeremonyError::UnsupportedNumG2Powers(self.g2.len()));
+        }
+        let lhs = E::msm_g1(&[commitment, G1::one()], &[F::one(), neg(value)])?;
+        let proof = E::msm_g1(&[proof], &[neg(F::one())])?;
+        let tau_minus_point = E::msm_g2(&[self.g2[1], G2::one()], &[F::one(), neg(point)])?;
+
+        // e(C - [y]₁, [1]₂) ⋅ e(-π, [τ - z]₂) = 1
+        if !E::pairing_check(&[(lhs, G2::one()), (proof, tau_minus_point)])? {
+            return Err(CeremonyError::InvalidOpeningProof);
+        }
+        Ok(())
+    }
+
+    /// Commits to a random polynomial of the largest degree the G1 powers
+    /// support, opens it at a random point and verifies the opening.
+    ///
+    /// # Errors
+    /// Returns an error if the opening does not verify, or if any of the
+    /// powers is invalid.
+    pub fn self_test<E: Engine>(&self) -> Result<(), CeremonyError> {
+        let mut rng = rand::thread_rng();
+        let coefficients = iter::repeat_with(|| F::from(Fr::rand(&mut rng)))
+            .take(self.g1.len())
+            .collect::<Vec<_>>();
+        let point = F::from(Fr::rand(&mut rng));
+
+        let commitment = self.commit::<E>(&coefficients)?;
+        let (value, proof) = self.open::<E>(&coefficients, point)?;
+        self.verify_opening::<E>(commitment, point, value, proof)
+    }
+}
+
+fn neg(f: F) -> F {
+    F::from(-Fr::from(&f))
+}
+
+/// Divides $p(X)$ by $X - z$ using Horner's method, and returns the quotient
+/// and the remainder $p(z)$.
+fn divide_by_linear(coefficients: &[F], point: F) -> (Vec<F>, F) {
+    let point = Fr::from(&point);
+    let mut quotient = coefficients
+        .iter()
+        .rev()
+        .scan(Fr::zero(), |acc, c| {
+            *acc = *acc * point + Fr::from(c);
+            Some(*acc)
+        })
+        .collect::<Vec<_>>();
+    let value = quotient.pop().unwrap_or_else(Fr::zero);
+    quotient.reverse();
+    (quotient.into_iter().map(F::from).collect(), F::from(value))
+}
+
+#[cfg(all(test, feature = "arkworks", feature = "blst"))]
+mod tests {
+    use super::*;
+    use crate::{engine::tests::arb_f, Arkworks, DefaultEngine, Secret, BLST};
+    use proptest::{collection::vec, proptest};
+
+    fn powers(tau: F) -> Powers {
+        let tau = Secret::new(tau);
+        let mut powers = Powers::new(16, 2);
+        DefaultEngine::add_tau_g1(&tau, &mut powers.g1).unwrap();
+        DefaultEngine::add_tau_g2(&tau, &mut powers.g2).unwrap();
+        powers
+    }
+
+    #[test]
+    fn test_divide_by_linear() {
+        // (X² + 2X + 3) / (X - 2) = X + 4 remainder 11
+        let coefficients = [F::from(Fr::from(3)), F::from(Fr::from(2)), F::one()];
+        let (quotient, value) = divide_by_linear(&coefficients, F::from(Fr::from(2)));
+        assert_eq!(quotient, vec![F::from(Fr::from(4)), F::one()]);
+        assert_eq!(value, F::from(Fr::from(11)));
+
+        assert_eq!(divide_by_linear(&[], F::one()), (vec![], F::zero()));
+    }
+
+    #[test]
+    fn test_commit_known_tau() {
+        // p(X) = 1 + X + X², so p(2) = 7
+        let powers = powers(F::from(Fr::from(2)));
+        let commitment = powers
+            .commit::<DefaultEngine>(&[F::one(), F::one(), F::one()])
+            .unwrap();
+        let expected = DefaultEngine::msm_g1(&[G1::one()], &[F::from(Fr::from(7))]).unwrap();
+        assert_eq!(commitment, expected);
+        assert_eq!(powers.commit::<DefaultEngine>(&[]), Ok(G1::zero()));
+        assert_eq!(
+            powers.commit::<DefaultEngine>(&[F::one(); 17]),
+            Err(CeremonyError::TooManyCoefficients(17, 16))
+        );
+    }
+
+    #[test]
+    fn test_open_verify() {
+        proptest!(|(tau in arb_f(), coefficients in vec(arb_f(), 0..=16), point in arb_f())| {
+            let powers = powers(tau);
+            let commitment = powers.commit::<DefaultEngine>(&coefficients).unwrap();
+            let (value, proof) = powers.open::<DefaultEngine>(&coefficients, point).unwrap();
+            assert_eq!(
+                powers.verify_opening::<Arkworks>(commitment, point, value, proof),
+                Ok(())
+            );
+            assert_eq!(
+                powers.verify_opening::<BLST>(commitment, point, value, proof),
+                Ok(())
+            );
+
+            let wrong = F::from(Fr::from(&value) + Fr::from(1));
+            assert_eq!(
+                powers.verify_opening::<DefaultEngine>(commitment, point, wrong, proof),
+                Err(CeremonyError::InvalidOpeningProof)
+            );
+        });
+    }
+
+    #[test]
+    fn test_self_test() {
+        proptest!(|(tau in arb_f())| {
+            assert_eq!(powers(tau).self_test::<DefaultEngine>(), Ok(()));
+        });
+    }
+}
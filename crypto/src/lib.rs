@@ -5,28 +5,38 @@
 
 mod batch_contribution;
 mod batch_transcript;
+pub mod beacon;
 mod contribution;
 mod engine;
 mod error;
 mod group;
 mod hex_format;
+pub mod import;
+mod kzg;
+mod parallel;
+pub mod parameters;
 mod powers;
+mod progress;
 pub mod signature;
 mod transcript;
+#[cfg(feature = "wasm")]
+mod wasm;
 
 pub use crate::{
     batch_contribution::{get_pot_pubkeys, BatchContribution},
     batch_transcript::BatchTranscript,
     contribution::Contribution,
-    engine::{Engine, Entropy, Secret, Tau},
+    engine::{Decompressed, Engine, Entropy, Secret, Tau},
     error::{CeremoniesError, CeremonyError, ErrorCode, ParseError},
     group::{F, G1, G2},
+    parameters::CeremonyParameters,
     powers::Powers,
-    signature::identity::Identity,
+    progress::{CancellationToken, Progress, ProgressSink, Stage},
+    signature::{identity::Identity, SignaturePolicy},
     transcript::Transcript,
 };
 
-pub use crate::engine::Both;
+pub use crate::engine::{BatchSubgroupCheck, Batched, Both, Member, Members, Quorum};
 
 #[cfg(feature = "arkworks")]
 pub use crate::engine::Arkworks;
@@ -34,6 +44,9 @@ pub use crate::engine::Arkworks;
 #[cfg(feature = "blst")]
 pub use crate::engine::BLST;
 
+#[cfg(feature = "zkcrypto")]
+pub use crate::engine::Zkcrypto;
+
 #[cfg(all(feature = "arkworks", feature = "blst"))]
 pub type DefaultEngine = Both<Arkworks, BLST>;
 
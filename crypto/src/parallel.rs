@@ -0,0 +1,85 @@
+//! Rayon, or a sequential drop-in where threads are not available.
+//!
+//! With the `parallel` feature this re-exports `rayon`. Without it, as on
+//! `wasm32-unknown-unknown`, `par_iter` and friends return the standard
+//! sequential iterators and [`join`] runs both closures in turn, so the same
+//! code compiles for both.
+
+#[cfg(feature = "parallel")]
+pub use rayon::join;
+
+#[cfg(not(feature = "parallel"))]
+pub use self::sequential::join;
+
+/// The parallel iterator traits, to be imported with a wildcard like
+/// `rayon::prelude`.
+pub mod prelude {
+    #[cfg(feature = "parallel")]
+    pub use rayon::prelude::*;
+
+    #[cfg(not(feature = "parallel"))]
+    pub use super::sequential::{
+        IntoParallelIterator, IntoParallelRefIterator, IntoParallelRefMutIterator,
+    };
+}
+
+#[cfg(not(feature = "parallel"))]
+mod sequential {
+    pub trait IntoParallelIterator {
+        type Iter: Iterator<Item = Self::Item>;
+        type Item;
+
+        fn into_par_iter(self) -> Self::Iter;
+    }
+
+    impl<I: IntoIterator> IntoParallelIterator for I {
+        type Item = I::Item;
+        type Iter = I::IntoIter;
+
+        fn into_par_iter(self) -> Self::Iter {
+            self.into_iter()
+        }
+    }
+
+    pub trait IntoParallelRefIterator<'data> {
+        type Iter: Iterator;
+
+        fn par_iter(&'data self) -> Self::Iter;
+    }
+
+    impl<'data, I: 'data + ?Sized> IntoParallelRefIterator<'data> for I
+    where
+        &'data I: IntoIterator,
+    {
+        type Iter = <&'data I as IntoIterator>::IntoIter;
+
+        fn par_iter(&'data self) -> Self::Iter {
+            self.into_iter()
+        }
+    }
+
+    pub trait IntoParallelRefMutIterator<'data> {
+        type Iter: Iterator;
+
+        fn par_iter_mut(&'data mut self) -> Self::Iter;
+    }
+
+    impl<'data, I: 'data + ?Sized> IntoParallelRefMutIterator<'data> for I
+    where
+        &'data mut I: IntoIterator,
+    {
+        type Iter = <&'data mut I as IntoIterator>::IntoIter;
+
+        fn par_iter_mut(&'data mut self) -> Self::Iter {
+            self.into_iter()
+        }
+    }
+
+    pub fn join<A, B, RA, RB>(a: A, b: B) -> (RA, RB)
+    where
+        A: FnOnce() -> RA,
+        B: FnOnce() -> RB,
+    {
+        (a(), b())
+    }
+}
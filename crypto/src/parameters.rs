@@ -0,0 +1,50 @@
+//! Parameters that separate the signatures of one ceremony from another.
+//!
+//! Participants sign their identity with BLS and their pubkeys with EIP-712.
+//! Testnet and rehearsal ceremonies use their own EIP-712 domain and BLS
+//! ciphersuite, so that these signatures can not be replayed in the mainnet
+//! ceremony, or the other way around.
+
+use serde::{Deserialize, Serialize};
+
+pub const MAINNET_EIP712_NAME: &str = "Ethereum KZG Ceremony";
+pub const MAINNET_EIP712_VERSION: &str = "1.0";
+pub const MAINNET_CHAIN_ID: u64 = 1;
+pub const MAINNET_CIPHER_SUITE: &str = "BLS_SIG_BLS12381G1_XMD:SHA-256_SSWU_RO_POP_";
+
+#[derive(Clone, PartialEq, Eq, Debug, Serialize, Deserialize)]
+#[serde(deny_unknown_fields, rename_all = "camelCase")]
+pub struct CeremonyParameters {
+    /// `name` of the EIP-712 domain of the contribution signature.
+    pub eip712_name:      String,
+    /// `version` of the EIP-712 domain of the contribution signature.
+    pub eip712_version:   String,
+    /// `chainId` of the EIP-712 domain of the contribution signature.
+    pub chain_id:         u64,
+    /// Domain separation tag of the BLS signatures of identities.
+    pub bls_cipher_suite: String,
+}
+
+impl CeremonyParameters {
+    /// The parameters of the Ethereum mainnet ceremony.
+    #[must_use]
+    pub fn mainnet() -> Self {
+        Self {
+            eip712_name:      MAINNET_EIP712_NAME.to_string(),
+            eip712_version:   MAINNET_EIP712_VERSION.to_string(),
+            chain_id:         MAINNET_CHAIN_ID,
+            bls_cipher_suite: MAINNET_CIPHER_SUITE.to_string(),
+        }
+    }
+
+    #[must_use]
+    pub fn is_mainnet(&self) -> bool {
+        *self == Self::mainnet()
+    }
+}
+
+impl Default for CeremonyParameters {
+    fn default() -> Self {
+        Self::mainnet()
+    }
+}
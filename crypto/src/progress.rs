@@ -0,0 +1,149 @@
+//! Progress reporting and cancellation of long running operations.
+//!
+//! Contributing and verifying take minutes for the mainnet sizes. The
+//! `*_with` variants of [`BatchContribution::add_entropy`],
+//! [`Contribution::add_tau`] and [`BatchTranscript::verify_add`] report their
+//! progress to a [`ProgressSink`], and stop with a `Cancelled` error once the
+//! [`CancellationToken`] is cancelled. The token is checked between chunks of
+//! powers and between verification stages.
+//!
+//! [`BatchContribution::add_entropy`]: crate::BatchContribution::add_entropy
+//! [`Contribution::add_tau`]: crate::Contribution::add_tau
+//! [`BatchTranscript::verify_add`]: crate::BatchTranscript::verify_add
+
+use crate::CeremonyError;
+use std::sync::{
+    atomic::{AtomicBool, AtomicUsize, Ordering},
+    Arc,
+};
+
+/// Number of powers multiplied by $τ$ between cancellation checks.
+pub const CHUNK_SIZE: usize = 1024;
+
+/// The operation that is in progress.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Stage {
+    /// Multiplying powers by $τ$, counted in points.
+    AddTau,
+    /// Verifying a contribution, counted in checks.
+    Verify,
+}
+
+/// Progress of an operation: `done` out of `total` units of `stage`.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct Progress {
+    pub stage: Stage,
+    pub done:  usize,
+    pub total: usize,
+}
+
+/// Receives [`Progress`] updates.
+///
+/// Sub-ceremonies are processed in parallel, so updates can arrive from
+/// multiple threads and slightly out of order.
+pub trait ProgressSink: Sync {
+    fn report(&self, progress: Progress);
+}
+
+impl<F: Fn(Progress) + Sync> ProgressSink for F {
+    fn report(&self, progress: Progress) {
+        self(progress);
+    }
+}
+
+/// Shared flag to stop an operation in progress.
+#[derive(Clone, Debug, Default)]
+pub struct CancellationToken(Arc<AtomicBool>);
+
+impl CancellationToken {
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Stops all operations using this token, or a clone of it.
+    pub fn cancel(&self) {
+        self.0.store(true, Ordering::Relaxed);
+    }
+
+    #[must_use]
+    pub fn is_cancelled(&self) -> bool {
+        self.0.load(Ordering::Relaxed)
+    }
+}
+
+/// Counts the work done across parallel tasks of one operation.
+pub struct Tracker<'a> {
+    stage:  Stage,
+    total:  usize,
+    done:   AtomicUsize,
+    sink:   &'a dyn ProgressSink,
+    cancel: &'a CancellationToken,
+}
+
+impl<'a> Tracker<'a> {
+    pub(crate) const fn new(
+        stage: Stage,
+        total: usize,
+        sink: &'a dyn ProgressSink,
+        cancel: &'a CancellationToken,
+    ) -> Self {
+        Self {
+            stage,
+            total,
+            done: AtomicUsize::new(0),
+            sink,
+            cancel,
+        }
+    }
+
+    /// Returns an error if the operation was cancelled.
+    pub(crate) fn check(&self) -> Result<(), CeremonyError> {
+        if self.cancel.is_cancelled() {
+            Err(CeremonyError::Cancelled)
+        } else {
+            Ok(())
+        }
+    }
+
+    /// Reports `units` more of work done.
+    pub(crate) fn advance(&self, units: usize) {
+        let done = self.done.fetch_add(units, Ordering::Relaxed) + units;
+        self.sink.report(Progress {
+            stage: self.stage,
+            done,
+            total: self.total,
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Mutex;
+
+    #[test]
+    fn test_tracker() {
+        let reports = Mutex::new(Vec::new());
+        let sink = |progress: Progress| reports.lock().unwrap().push(progress);
+        let cancel = CancellationToken::new();
+        let tracker = Tracker::new(Stage::AddTau, 3, &sink, &cancel);
+        assert_eq!(tracker.check(), Ok(()));
+        tracker.advance(1);
+        tracker.advance(2);
+        cancel.clone().cancel();
+        assert_eq!(tracker.check(), Err(CeremonyError::Cancelled));
+        assert_eq!(reports.into_inner().unwrap(), vec![
+            Progress {
+                stage: Stage::AddTau,
+                done:  1,
+                total: 3,
+            },
+            Progress {
+                stage: Stage::AddTau,
+                done:  3,
+                total: 3,
+            },
+        ]);
+    }
+}
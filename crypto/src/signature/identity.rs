@@ -1,12 +1,32 @@
+use crate::{import::ImportFormat, ErrorCode};
 use serde::{Deserialize, Serialize};
 use std::{fmt, fmt::Display, str::FromStr};
+use strum::IntoStaticStr;
 use thiserror::Error;
 
 #[derive(Clone, PartialEq, Eq, Debug)]
 pub enum Identity {
     None,
-    Ethereum { address: [u8; 20] },
-    Github { id: u64, username: String },
+    Ethereum {
+        address: [u8; 20],
+    },
+    Github {
+        id:       u64,
+        username: String,
+    },
+    /// A public random beacon that finalizes the ceremony, see
+    /// [`crate::beacon`]. The `source` names the beacon and round, such as
+    /// `drand-quicknet-1234567`, and `value` is its output.
+    Beacon {
+        source: String,
+        value:  Vec<u8>,
+    },
+    /// Powers imported from an earlier ceremony as the starting point, see
+    /// [`crate::import`]. The `hash` is the SHA-256 hash of the imported file.
+    Import {
+        format: ImportFormat,
+        hash:   [u8; 32],
+    },
 }
 
 impl Identity {
@@ -27,9 +47,34 @@ impl Identity {
         Ok(Self::Ethereum { address })
     }
 
+    /// Creates a random beacon identity.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`IdentityError::InvalidBeaconSource`] if `source` is empty or
+    /// contains a `|`.
+    pub fn beacon(source: &str, value: &[u8]) -> Result<Self, IdentityError> {
+        if source.is_empty() || source.contains('|') {
+            return Err(IdentityError::InvalidBeaconSource);
+        }
+        Ok(Self::Beacon {
+            source: source.to_string(),
+            value:  value.to_vec(),
+        })
+    }
+
+    /// Returns a stable identifier for the participant, suitable for
+    /// deduplication.
+    ///
+    /// Unlike the [`Display`] format, this does not include mutable account
+    /// attributes such as the Github username, so renaming an account does
+    /// not change it.
     #[must_use]
     pub fn unique_id(&self) -> String {
-        self.to_string()
+        match self {
+            Self::Github { id, .. } => format!("git|{id}"),
+            _ => self.to_string(),
+        }
     }
 
     #[must_use]
@@ -37,6 +82,8 @@ impl Identity {
         match self {
             Self::Ethereum { address } => format!("0x{}", hex::encode(address)),
             Self::Github { username, .. } => username.to_string(),
+            Self::Beacon { source, .. } => source.to_string(),
+            Self::Import { format, .. } => format.to_string(),
             Self::None => "<<unauthorized>>".to_string(),
         }
     }
@@ -46,13 +93,15 @@ impl Identity {
         match self {
             Self::Ethereum { .. } => "Ethereum",
             Self::Github { .. } => "Github",
+            Self::Beacon { .. } => "Beacon",
+            Self::Import { .. } => "Import",
             Self::None => "None",
         }
         .to_string()
     }
 }
 
-#[derive(Clone, Copy, PartialEq, Eq, Debug, Error)]
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Error, IntoStaticStr)]
 pub enum IdentityError {
     #[error("invalid identity")]
     UnsupportedType,
@@ -64,6 +113,20 @@ pub enum IdentityError {
     InvalidEthereumAddress,
     #[error("Invalid Github ID")]
     InvalidGithubId,
+    #[error("Invalid beacon source")]
+    InvalidBeaconSource,
+    #[error("Invalid beacon value")]
+    InvalidBeaconValue,
+    #[error("Invalid import format")]
+    InvalidImportFormat,
+    #[error("Invalid import hash")]
+    InvalidImportHash,
+}
+
+impl ErrorCode for IdentityError {
+    fn to_error_code(&self) -> String {
+        format!("IdentityError::{}", <&str>::from(self))
+    }
 }
 
 impl Display for Identity {
@@ -72,6 +135,10 @@ impl Display for Identity {
             Self::None => write!(f, ""),
             Self::Ethereum { address } => write!(f, "eth|0x{}", hex::encode(address)),
             Self::Github { id, username } => write!(f, "git|{id}|{username}"),
+            Self::Beacon { source, value } => {
+                write!(f, "beacon|{source}|0x{}", hex::encode(value))
+            }
+            Self::Import { format, hash } => write!(f, "import|{format}|0x{}", hex::encode(hash)),
         }
     }
 }
@@ -110,6 +177,38 @@ impl FromStr for Identity {
 
                 Ok(Self::Github { id, username })
             }
+            Some("beacon") => {
+                let source = parts.next().ok_or(IdentityError::MissingField)?;
+                let value = parts.next().ok_or(IdentityError::MissingField)?;
+                if parts.next().is_some() {
+                    return Err(IdentityError::TooManyFields);
+                }
+
+                let value = value
+                    .strip_prefix("0x")
+                    .and_then(|value| hex::decode(value).ok())
+                    .ok_or(IdentityError::InvalidBeaconValue)?;
+
+                Self::beacon(source, &value)
+            }
+            Some("import") => {
+                let format = parts.next().ok_or(IdentityError::MissingField)?;
+                let hash = parts.next().ok_or(IdentityError::MissingField)?;
+                if parts.next().is_some() {
+                    return Err(IdentityError::TooManyFields);
+                }
+
+                let format = format
+                    .parse()
+                    .map_err(|_| IdentityError::InvalidImportFormat)?;
+                let hash = hash
+                    .strip_prefix("0x")
+                    .and_then(|hash| hex::decode(hash).ok())
+                    .and_then(|hash| hash.try_into().ok())
+                    .ok_or(IdentityError::InvalidImportHash)?;
+
+                Ok(Self::Import { format, hash })
+            }
             Some("") => {
                 if parts.next().is_some() {
                     return Err(IdentityError::TooManyFields);
@@ -190,6 +289,7 @@ mod tests {
             username: "username".to_string(),
         };
         assert_eq!(identity.to_string(), "git|123|username");
+        assert_eq!(identity.unique_id(), "git|123");
         assert_eq!(identity, "git|123|username".parse().unwrap());
         assert_eq!(
             "git|123|username|".parse::<Identity>().err().unwrap(),
@@ -197,6 +297,71 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_git_unique_id_ignores_username() {
+        let identity = Identity::Github {
+            id:       123,
+            username: "username".to_string(),
+        };
+        let renamed = Identity::Github {
+            id:       123,
+            username: "new_username".to_string(),
+        };
+        assert_ne!(identity.to_string(), renamed.to_string());
+        assert_eq!(identity.unique_id(), renamed.unique_id());
+    }
+
+    #[test]
+    fn test_beacon() {
+        let identity = Identity::beacon("drand-quicknet-1234", &[0xab, 0xcd]).unwrap();
+        assert_eq!(identity.to_string(), "beacon|drand-quicknet-1234|0xabcd");
+        assert_eq!(
+            identity,
+            "beacon|drand-quicknet-1234|0xabcd".parse().unwrap()
+        );
+        assert_eq!(identity.provider_name(), "Beacon");
+        assert_eq!(identity.nickname(), "drand-quicknet-1234");
+        assert_eq!(
+            Identity::beacon("", &[]),
+            Err(IdentityError::InvalidBeaconSource)
+        );
+        assert_eq!(
+            Identity::beacon("a|b", &[]),
+            Err(IdentityError::InvalidBeaconSource)
+        );
+        assert_eq!(
+            "beacon|drand|abcd".parse::<Identity>(),
+            Err(IdentityError::InvalidBeaconValue)
+        );
+        assert_eq!(
+            "beacon|drand".parse::<Identity>(),
+            Err(IdentityError::MissingField)
+        );
+    }
+
+    #[test]
+    fn test_import() {
+        let identity = Identity::Import {
+            format: ImportFormat::Ptau,
+            hash:   [0xab; 32],
+        };
+        assert_eq!(
+            identity.to_string(),
+            "import|ptau|0xabababababababababababababababababababababababababababababababab"
+        );
+        assert_eq!(identity, identity.to_string().parse().unwrap());
+        assert_eq!(identity.provider_name(), "Import");
+        assert_eq!(identity.nickname(), "ptau");
+        assert_eq!(
+            "import|zip|0xabab".parse::<Identity>(),
+            Err(IdentityError::InvalidImportFormat)
+        );
+        assert_eq!(
+            "import|ptau|0xabab".parse::<Identity>(),
+            Err(IdentityError::InvalidImportHash)
+        );
+    }
+
     #[test]
     fn test_invalid() {
         assert_eq!(
@@ -3,11 +3,14 @@
 //! <https://github.com/gakonst/ethers-rs/blob/e89c7a378bba6587e3f525982785c59a33c14d9b/ethers-core/ethers-derive-eip712/tests/derive_eip712.rs>
 
 pub mod identity;
+pub mod receipt;
+pub mod report;
 
 use crate::{
     hex_format::{bytes_to_hex, optional_hex_to_bytes},
     signature::identity::Identity,
-    BatchContribution, Engine, Tau, G1, G2,
+    BatchContribution, BatchTranscript, CeremoniesError, CeremonyError, CeremonyParameters, Engine,
+    Tau, G1, G2,
 };
 use ethers_core::types::{
     transaction::eip712::{EIP712Domain, Eip712, Eip712Error, TypedData},
@@ -16,6 +19,41 @@ use ethers_core::types::{
 use serde::{Deserialize, Deserializer, Serialize, Serializer};
 use serde_json::json;
 
+/// Which signatures [`BatchTranscript::verify_add`] requires.
+///
+/// Signatures that are not required are checked as well, but invalid ones are
+/// dropped from the transcript instead of rejecting the contribution.
+///
+/// [`BatchTranscript::verify_add`]: crate::BatchTranscript::verify_add
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default)]
+pub struct SignaturePolicy {
+    /// Require a valid BLS signature of the identity in every sub-ceremony.
+    pub require_bls:   bool,
+    /// Require a valid EIP-712 signature of the pubkeys from participants
+    /// with an [`Identity::Ethereum`].
+    pub require_ecdsa: bool,
+}
+
+impl SignaturePolicy {
+    /// Accepts contributions without valid signatures, pruning invalid ones.
+    #[must_use]
+    pub const fn permissive() -> Self {
+        Self {
+            require_bls:   false,
+            require_ecdsa: false,
+        }
+    }
+
+    /// Requires all signatures.
+    #[must_use]
+    pub const fn strict() -> Self {
+        Self {
+            require_bls:   true,
+            require_ecdsa: true,
+        }
+    }
+}
+
 #[derive(Clone, PartialEq, Eq, Debug)]
 pub struct BlsSignature(pub Option<G1>);
 
@@ -26,9 +64,14 @@ impl BlsSignature {
     }
 
     #[must_use]
-    pub fn prune<E: Engine>(&self, message: &[u8], pk: G2) -> Self {
+    pub fn prune<E: Engine>(
+        &self,
+        message: &[u8],
+        pk: G2,
+        parameters: &CeremonyParameters,
+    ) -> Self {
         Self(self.0.and_then(|sig| {
-            if E::verify_signature(sig, message, pk) {
+            if E::verify_signature(sig, message, pk, &parameters.bls_cipher_suite) {
                 Some(sig)
             } else {
                 None
@@ -36,9 +79,29 @@ impl BlsSignature {
         }))
     }
 
+    /// Checks that the signature is present and signs `message` with `pk`.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`CeremonyError::MissingBlsSignature`] or
+    /// [`CeremonyError::InvalidBlsSignature`].
+    pub fn verify<E: Engine>(
+        &self,
+        message: &[u8],
+        pk: G2,
+        parameters: &CeremonyParameters,
+    ) -> Result<(), CeremonyError> {
+        let sig = self.0.ok_or(CeremonyError::MissingBlsSignature)?;
+        if E::verify_signature(sig, message, pk, &parameters.bls_cipher_suite) {
+            Ok(())
+        } else {
+            Err(CeremonyError::InvalidBlsSignature)
+        }
+    }
+
     #[must_use]
-    pub fn sign<E: Engine>(message: &[u8], sk: &Tau) -> Self {
-        Self(E::sign_message(sk, message))
+    pub fn sign<E: Engine>(message: &[u8], sk: &Tau, parameters: &CeremonyParameters) -> Self {
+        Self(E::sign_message(sk, message, &parameters.bls_cipher_suite))
     }
 }
 
@@ -83,6 +146,21 @@ impl EcdsaSignature {
             }
         }))
     }
+
+    /// Checks that the signature is present and signs `data` with `address`.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`CeremoniesError::MissingEcdsaSignature`] or
+    /// [`CeremoniesError::InvalidEcdsaSignature`].
+    pub fn verify<T: Eip712>(&self, address: [u8; 20], data: &T) -> Result<(), CeremoniesError> {
+        let sig = self.0.ok_or(CeremoniesError::MissingEcdsaSignature)?;
+        let hash = data
+            .encode_eip712()
+            .map_err(|_| CeremoniesError::InvalidEcdsaSignature)?;
+        sig.verify(hash, address)
+            .map_err(|_| CeremoniesError::InvalidEcdsaSignature)
+    }
 }
 
 impl Serialize for EcdsaSignature {
@@ -125,10 +203,16 @@ pub struct PubkeyTypedData {
 #[serde(rename_all = "camelCase")]
 pub struct ContributionTypedData {
     pot_pubkeys: Vec<PubkeyTypedData>,
+    /// Determines the EIP-712 domain.
+    #[serde(skip)]
+    parameters:  CeremonyParameters,
 }
 
-impl From<&BatchContribution> for ContributionTypedData {
-    fn from(contribution: &BatchContribution) -> Self {
+impl ContributionTypedData {
+    /// The data a participant signs for `contribution` in the ceremony with
+    /// `parameters`.
+    #[must_use]
+    pub fn new(contribution: &BatchContribution, parameters: &CeremonyParameters) -> Self {
         Self {
             pot_pubkeys: contribution
                 .contributions
@@ -139,6 +223,25 @@ impl From<&BatchContribution> for ContributionTypedData {
                     pot_pubkey:    c.pot_pubkey,
                 })
                 .collect(),
+            parameters:  parameters.clone(),
+        }
+    }
+
+    /// Rebuilds the data signed by participant `index` of `transcript` from
+    /// the witness.
+    #[must_use]
+    pub fn from_witness(transcript: &BatchTranscript, index: usize) -> Self {
+        Self {
+            pot_pubkeys: transcript
+                .transcripts
+                .iter()
+                .map(|t| PubkeyTypedData {
+                    num_g1_powers: t.powers.g1.len(),
+                    num_g2_powers: t.powers.g2.len(),
+                    pot_pubkey:    t.witness.pubkeys[index],
+                })
+                .collect(),
+            parameters:  transcript.parameters.clone(),
         }
     }
 }
@@ -163,9 +266,9 @@ impl From<ContributionTypedData> for TypedData {
             },
             "primaryType": "PoTPubkeys",
             "domain": {
-                "name": "Ethereum KZG Ceremony",
-                "version": "1.0",
-                "chainId": 1
+                "name": contrib.parameters.eip712_name,
+                "version": contrib.parameters.eip712_version,
+                "chainId": contrib.parameters.chain_id
             },
             "message": contrib
         });
@@ -193,8 +296,11 @@ impl Eip712 for ContributionTypedData {
 #[cfg(all(test, feature = "arkworks", feature = "blst"))]
 mod tests {
     use crate::{
-        engine::tests::arb_f, signature::BlsSignature, Arkworks, Both, Engine, Entropy, BLST, F, G2,
+        engine::tests::arb_f,
+        signature::{BlsSignature, ContributionTypedData},
+        Arkworks, BatchTranscript, Both, CeremonyParameters, Engine, Entropy, BLST, F, G2,
     };
+    use ethers_core::types::transaction::eip712::Eip712;
     use proptest::proptest;
     use rand::{thread_rng, Rng};
     use secrecy::Secret;
@@ -203,26 +309,28 @@ mod tests {
 
     #[test]
     fn test_sign_both_engines() {
+        let params = CeremonyParameters::default();
         proptest!(|(f in arb_f(), msg in ".*")| {
             let bytes = msg.as_bytes();
             let tau = Secret::new(f);
-            let signed_blst = BlsSignature::sign::<BLST>(bytes, &tau);
-            let signed_ark = BlsSignature::sign::<Arkworks>(bytes, &tau);
+            let signed_blst = BlsSignature::sign::<BLST>(bytes, &tau, &params);
+            let signed_ark = BlsSignature::sign::<Arkworks>(bytes, &tau, &params);
             assert_eq!(signed_blst, signed_ark);
         });
     }
 
     #[test]
     fn test_bls_prune_after_encode() {
+        let params = CeremonyParameters::default();
         proptest!(|(f in arb_f(), msg in ".*")| {
             let bytes = msg.as_bytes();
             let tau = Secret::new(f);
-            let signed = BlsSignature::sign::<BothEngines>(bytes, &tau);
+            let signed = BlsSignature::sign::<BothEngines>(bytes, &tau, &params);
             assert!(signed.0.is_some());
             let mut tmp = vec![G2::one(), G2::one()];
             BothEngines::add_tau_g2(&tau, &mut tmp).unwrap();
             let pubkey = tmp[1];
-            let recovered = signed.prune::<BothEngines>(bytes, pubkey);
+            let recovered = signed.prune::<BothEngines>(bytes, pubkey, &params);
             assert_eq!(signed, recovered);
         });
     }
@@ -231,27 +339,60 @@ mod tests {
     fn test_bls_prune_wrong_msg() {
         let message = b"git|1234|foobar";
         let wrong_msg = b"git|4567|bazbaz";
+        let params = CeremonyParameters::default();
         let tau = Secret::new(F::one());
-        let signed = BlsSignature::sign::<BothEngines>(message, &tau);
+        let signed = BlsSignature::sign::<BothEngines>(message, &tau, &params);
         assert!(signed.0.is_some());
         let mut tmp = vec![G2::one(), G2::one()];
         BothEngines::add_tau_g2(&tau, &mut tmp).unwrap();
         let pubkey = tmp[1];
-        let recovered = signed.prune::<BothEngines>(wrong_msg, pubkey);
+        let recovered = signed.prune::<BothEngines>(wrong_msg, pubkey, &params);
         assert_eq!(recovered, BlsSignature(None));
     }
 
     #[test]
     fn test_bls_prune_wrong_sig() {
         let message = b"git|1234|foobar";
+        let params = CeremonyParameters::default();
         let tau = BothEngines::generate_tau(&Entropy::new(thread_rng().gen()));
         let wrong_tau = BothEngines::generate_tau(&Entropy::new(thread_rng().gen()));
-        let signed = BlsSignature::sign::<BothEngines>(message, &tau);
+        let signed = BlsSignature::sign::<BothEngines>(message, &tau, &params);
         assert!(signed.0.is_some());
         let mut tmp = vec![G2::one(), G2::one()];
         BothEngines::add_tau_g2(&wrong_tau, &mut tmp).unwrap();
         let wrong_pubkey = tmp[1];
-        let recovered = signed.prune::<BothEngines>(message, wrong_pubkey);
+        let recovered = signed.prune::<BothEngines>(message, wrong_pubkey, &params);
         assert_eq!(recovered, BlsSignature(None));
     }
+
+    #[test]
+    fn test_signatures_bound_to_parameters() {
+        let message = b"git|1234|foobar";
+        let mainnet = CeremonyParameters::mainnet();
+        let testnet = CeremonyParameters {
+            eip712_name: "Ethereum KZG Ceremony Rehearsal".to_string(),
+            chain_id: 5,
+            bls_cipher_suite: "BLS_SIG_BLS12381G1_XMD:SHA-256_SSWU_RO_POP_TESTNET_".to_string(),
+            ..CeremonyParameters::mainnet()
+        };
+        // The pubkey of τ = 1 is the generator.
+        let tau = Secret::new(F::one());
+        let pubkey = G2::one();
+        let signed = BlsSignature::sign::<BothEngines>(message, &tau, &testnet);
+        assert!(signed
+            .verify::<BothEngines>(message, pubkey, &testnet)
+            .is_ok());
+        assert!(signed
+            .verify::<BothEngines>(message, pubkey, &mainnet)
+            .is_err());
+
+        let transcript = BatchTranscript::new([(2, 2)].iter());
+        let contribution = transcript.contribution();
+        let mainnet_data = ContributionTypedData::new(&contribution, &mainnet);
+        let testnet_data = ContributionTypedData::new(&contribution, &testnet);
+        assert_ne!(
+            mainnet_data.encode_eip712().unwrap(),
+            testnet_data.encode_eip712().unwrap()
+        );
+    }
 }
@@ -0,0 +1,257 @@
+//! Receipts the sequencer signs for included contributions.
+//!
+//! A [`ContributionReceipt`] is signed as EIP-712 typed data under
+//! [`receipt_domain`], which binds it to the ceremony's
+//! [`CeremonyParameters`] and to the transcript that includes the
+//! contribution. Older sequencers signed the JSON string
+//! `{"identity":…,"witness":[…]}` with `personal_sign`, which
+//! [`verify_legacy_receipt`] checks.
+
+use crate::{CeremonyParameters, ErrorCode, G2};
+use ethers_core::types::{
+    transaction::eip712::{EIP712Domain, Eip712, Eip712Error, TypedData},
+    Address, RecoveryMessage, Signature,
+};
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use strum::IntoStaticStr;
+use thiserror::Error;
+
+/// Appended to the ceremony's EIP-712 domain name to form the domain name of
+/// its receipts, e.g. `Ethereum KZG Ceremony Receipt` on mainnet.
+pub const RECEIPT_DOMAIN_NAME_SUFFIX: &str = " Receipt";
+
+#[derive(Clone, Debug, Error, IntoStaticStr)]
+pub enum ReceiptError {
+    #[error("could not encode receipt: {0}")]
+    Encoding(String),
+    #[error("receipt is not signed by the sequencer")]
+    InvalidSignature,
+}
+
+impl ErrorCode for ReceiptError {
+    fn to_error_code(&self) -> String {
+        format!("ReceiptError::{}", <&str>::from(self))
+    }
+}
+
+impl From<Eip712Error> for ReceiptError {
+    fn from(error: Eip712Error) -> Self {
+        Self::Encoding(error.to_string())
+    }
+}
+
+/// Statement by the sequencer that a participant's contribution is part of the
+/// transcript.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ContributionReceipt {
+    /// The participant, in the format of the transcript's `participantIds`.
+    pub identity:         String,
+    /// The `potPubkey` of every sub-ceremony, in order.
+    pub pot_pubkeys:      Vec<G2>,
+    /// Position of the contribution in the transcript's `participantIds`.
+    pub transcript_index: usize,
+    /// `0x` prefixed keccak256 hash of the transcript after the contribution.
+    pub transcript_hash:  String,
+    /// Unix timestamp in seconds at which the contribution was included.
+    pub timestamp:        u64,
+}
+
+/// The EIP-712 domain receipts of the ceremony with `parameters` are signed
+/// under.
+#[must_use]
+pub fn receipt_domain(parameters: &CeremonyParameters) -> EIP712Domain {
+    EIP712Domain {
+        name:               Some(receipt_domain_name(parameters)),
+        version:            Some(parameters.eip712_version.clone()),
+        chain_id:           Some(parameters.chain_id.into()),
+        verifying_contract: None,
+        salt:               None,
+    }
+}
+
+fn receipt_domain_name(parameters: &CeremonyParameters) -> String {
+    format!("{}{RECEIPT_DOMAIN_NAME_SUFFIX}", parameters.eip712_name)
+}
+
+/// A [`ContributionReceipt`] as signed in the ceremony with the given
+/// parameters.
+#[derive(Clone, Debug)]
+pub struct ReceiptTypedData {
+    receipt:    ContributionReceipt,
+    /// Determines the EIP-712 domain.
+    parameters: CeremonyParameters,
+}
+
+impl ReceiptTypedData {
+    #[must_use]
+    pub fn new(receipt: &ContributionReceipt, parameters: &CeremonyParameters) -> Self {
+        Self {
+            receipt:    receipt.clone(),
+            parameters: parameters.clone(),
+        }
+    }
+}
+
+impl From<ReceiptTypedData> for TypedData {
+    fn from(typed: ReceiptTypedData) -> Self {
+        let json = json!({
+            "types": {
+                "EIP712Domain": [
+                    {"name":"name", "type":"string"},
+                    {"name":"version", "type":"string"},
+                    {"name":"chainId", "type":"uint256"}
+                ],
+                "ContributionReceipt": [
+                    {"name": "identity", "type": "string"},
+                    {"name": "potPubkeys", "type": "bytes[]"},
+                    {"name": "transcriptIndex", "type": "uint256"},
+                    {"name": "transcriptHash", "type": "bytes32"},
+                    {"name": "timestamp", "type": "uint256"}
+                ]
+            },
+            "primaryType": "ContributionReceipt",
+            "domain": {
+                "name": receipt_domain_name(&typed.parameters),
+                "version": typed.parameters.eip712_version,
+                "chainId": typed.parameters.chain_id
+            },
+            "message": typed.receipt
+        });
+        serde_json::from_value(json)
+            .expect("Impossible, constructed from a literal and therefore must be valid json")
+    }
+}
+
+impl Eip712 for ReceiptTypedData {
+    type Error = Eip712Error;
+
+    fn domain(&self) -> Result<EIP712Domain, Self::Error> {
+        Ok(receipt_domain(&self.parameters))
+    }
+
+    fn type_hash() -> Result<[u8; 32], Self::Error> {
+        TypedData::type_hash()
+    }
+
+    fn struct_hash(&self) -> Result<[u8; 32], Self::Error> {
+        TypedData::from(self.clone()).struct_hash()
+    }
+}
+
+impl ContributionReceipt {
+    /// Checks that the receipt was signed by `sequencer`, the
+    /// `sequencer_address` reported at `/info/status`, in the ceremony with
+    /// `parameters`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the receipt can not be encoded or the signature is
+    /// by another address or for another ceremony.
+    pub fn verify(
+        &self,
+        signature: &Signature,
+        sequencer: Address,
+        parameters: &CeremonyParameters,
+    ) -> Result<(), ReceiptError> {
+        let digest = ReceiptTypedData::new(self, parameters).encode_eip712()?;
+        signature
+            .verify(digest, sequencer)
+            .map_err(|_| ReceiptError::InvalidSignature)
+    }
+}
+
+/// Checks a receipt in the legacy format, the exact JSON `message` signed
+/// with `personal_sign` by `sequencer`.
+///
+/// # Errors
+///
+/// Returns an error if the signature is by another address.
+pub fn verify_legacy_receipt(
+    message: &str,
+    signature: &Signature,
+    sequencer: Address,
+) -> Result<(), ReceiptError> {
+    signature
+        .verify(
+            RecoveryMessage::Data(message.as_bytes().to_vec()),
+            sequencer,
+        )
+        .map_err(|_| ReceiptError::InvalidSignature)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn receipt() -> ContributionReceipt {
+        ContributionReceipt {
+            identity:         "git|1234|test_user".to_string(),
+            pot_pubkeys:      vec![G2::one(), G2::one()],
+            transcript_index: 1,
+            transcript_hash:  format!("0x{}", "ab".repeat(32)),
+            timestamp:        1_667_000_000,
+        }
+    }
+
+    fn receipt_digest(receipt: &ContributionReceipt, parameters: &CeremonyParameters) -> [u8; 32] {
+        ReceiptTypedData::new(receipt, parameters)
+            .encode_eip712()
+            .unwrap()
+    }
+
+    #[test]
+    fn commits_to_all_fields() {
+        let mainnet = CeremonyParameters::mainnet();
+        let digest = receipt_digest(&receipt(), &mainnet);
+        let mut other = receipt();
+        other.transcript_index = 2;
+        assert_ne!(receipt_digest(&other, &mainnet), digest);
+        let mut other = receipt();
+        other.transcript_hash = format!("0x{}", "cd".repeat(32));
+        assert_ne!(receipt_digest(&other, &mainnet), digest);
+        let mut other = receipt();
+        other.timestamp += 1;
+        assert_ne!(receipt_digest(&other, &mainnet), digest);
+        let mut other = receipt();
+        other.pot_pubkeys.pop();
+        assert_ne!(receipt_digest(&other, &mainnet), digest);
+    }
+
+    #[test]
+    fn uses_receipt_domain() {
+        let mainnet = CeremonyParameters::mainnet();
+        let domain = TypedData::from(ReceiptTypedData::new(&receipt(), &mainnet))
+            .domain()
+            .unwrap();
+        assert_eq!(domain, receipt_domain(&mainnet));
+        assert_eq!(
+            domain.name.as_deref(),
+            Some("Ethereum KZG Ceremony Receipt")
+        );
+        assert_eq!(domain.version.as_deref(), Some("1.0"));
+        assert_eq!(domain.chain_id, Some(1.into()));
+    }
+
+    #[test]
+    fn separates_ceremonies() {
+        let mainnet = CeremonyParameters::mainnet();
+        let digest = receipt_digest(&receipt(), &mainnet);
+        let testnet = CeremonyParameters {
+            eip712_name: "Ethereum KZG Ceremony Testnet".to_string(),
+            ..mainnet.clone()
+        };
+        assert_ne!(receipt_digest(&receipt(), &testnet), digest);
+        let other_chain = CeremonyParameters {
+            chain_id: 5,
+            ..mainnet.clone()
+        };
+        assert_ne!(receipt_digest(&receipt(), &other_chain), digest);
+        let other_version = CeremonyParameters {
+            eip712_version: "2.0".to_string(),
+            ..mainnet
+        };
+        assert_ne!(receipt_digest(&receipt(), &other_version), digest);
+    }
+}
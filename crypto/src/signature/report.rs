@@ -0,0 +1,249 @@
+//! Audit of the signatures in a published transcript.
+//!
+//! [`BatchTranscript::verify_add`] drops signatures that do not verify, unless
+//! the [`SignaturePolicy`] requires them, so a transcript can hold any mix of
+//! valid and missing signatures. [`SignatureReport`] checks all of them again.
+//!
+//! BLS signatures are verified in batches with random linear combinations
+//! ([`Engine::verify_signatures`]). A batch that fails is split in halves until
+//! the invalid signatures are found, so a transcript with few invalid
+//! signatures costs little more than a single batch check.
+//!
+//! [`BatchTranscript::verify_add`]: crate::BatchTranscript::verify_add
+//! [`SignaturePolicy`]: super::SignaturePolicy
+
+use super::{identity::Identity, ContributionTypedData};
+use crate::{
+    parallel::{join, prelude::*},
+    BatchTranscript, Engine, G1, G2,
+};
+use serde::Serialize;
+use std::collections::BTreeSet;
+use tracing::instrument;
+
+/// State of a single signature.
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum SignatureStatus {
+    Valid,
+    Invalid,
+    Missing,
+}
+
+/// The signatures of one participant.
+#[derive(Clone, PartialEq, Eq, Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ParticipantSignatures {
+    /// Position of the participant in `participantIds`.
+    pub index:           usize,
+    pub identity:        Identity,
+    /// The BLS signature in every sub-ceremony, in order.
+    pub bls_signatures:  Vec<SignatureStatus>,
+    pub ecdsa_signature: SignatureStatus,
+}
+
+impl ParticipantSignatures {
+    /// True if no signature is present but invalid.
+    #[must_use]
+    pub fn is_consistent(&self) -> bool {
+        self.ecdsa_signature != SignatureStatus::Invalid
+            && !self.bls_signatures.contains(&SignatureStatus::Invalid)
+    }
+}
+
+/// Per participant report of the signatures in a [`BatchTranscript`].
+#[derive(Clone, PartialEq, Eq, Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SignatureReport {
+    pub participants: Vec<ParticipantSignatures>,
+}
+
+impl SignatureReport {
+    /// Checks every BLS and ECDSA signature of the participants in
+    /// `transcript`.
+    ///
+    /// ECDSA signatures are checked against the [`ContributionTypedData`]
+    /// rebuilt from the witness.
+    #[must_use]
+    #[instrument(level = "info", skip_all, fields(n=transcript.num_participants()))]
+    pub fn new<E: Engine>(transcript: &BatchTranscript) -> Self {
+        let invalid_bls = invalid_bls_signatures::<E>(transcript);
+        let participants = (1..transcript.participant_ids.len())
+            .into_par_iter()
+            .map(|index| {
+                let bls_signatures = transcript
+                    .transcripts
+                    .iter()
+                    .enumerate()
+                    .map(|(ceremony, t)| {
+                        if t.witness.signatures[index].0.is_none() {
+                            SignatureStatus::Missing
+                        } else if invalid_bls.contains(&(index, ceremony)) {
+                            SignatureStatus::Invalid
+                        } else {
+                            SignatureStatus::Valid
+                        }
+                    })
+                    .collect();
+                ParticipantSignatures {
+                    index,
+                    identity: transcript.participant_ids[index].clone(),
+                    bls_signatures,
+                    ecdsa_signature: ecdsa_status(transcript, index),
+                }
+            })
+            .collect();
+        Self { participants }
+    }
+
+    /// Participants with at least one invalid signature.
+    pub fn inconsistent(&self) -> impl Iterator<Item = &ParticipantSignatures> {
+        self.participants.iter().filter(|p| !p.is_consistent())
+    }
+}
+
+fn ecdsa_status(transcript: &BatchTranscript, index: usize) -> SignatureStatus {
+    let signature = &transcript.participant_ecdsa_signatures[index];
+    if signature.0.is_none() {
+        return SignatureStatus::Missing;
+    }
+    match &transcript.participant_ids[index] {
+        Identity::Ethereum { address } => {
+            let typed_data = ContributionTypedData::from_witness(transcript, index);
+            if signature.verify(*address, &typed_data).is_ok() {
+                SignatureStatus::Valid
+            } else {
+                SignatureStatus::Invalid
+            }
+        }
+        // Only Ethereum participants can sign their contribution.
+        _ => SignatureStatus::Invalid,
+    }
+}
+
+/// A BLS signature to verify, with its `(participant, sub-ceremony)` position.
+struct BlsItem {
+    position: (usize, usize),
+    sig:      G1,
+    message:  Vec<u8>,
+    pk:       G2,
+}
+
+/// Returns the `(participant, sub-ceremony)` positions of all BLS signatures
+/// that are present but do not verify.
+fn invalid_bls_signatures<E: Engine>(transcript: &BatchTranscript) -> BTreeSet<(usize, usize)> {
+    let items = (1..transcript.participant_ids.len())
+        .flat_map(|index| {
+            let message = transcript.participant_ids[index].to_string().into_bytes();
+            transcript
+                .transcripts
+                .iter()
+                .enumerate()
+                .filter_map(move |(ceremony, t)| {
+                    t.witness.signatures[index].0.map(|sig| BlsItem {
+                        position: (index, ceremony),
+                        sig,
+                        message: message.clone(),
+                        pk: t.witness.pubkeys[index],
+                    })
+                })
+        })
+        .collect::<Vec<_>>();
+    bisect::<E>(&items, &transcript.parameters.bls_cipher_suite)
+}
+
+/// Verifies `items` as one batch, and bisects failing batches down to the
+/// invalid signatures.
+fn bisect<E: Engine>(items: &[BlsItem], cipher_suite: &str) -> BTreeSet<(usize, usize)> {
+    let batch = items
+        .iter()
+        .map(|item| (item.sig, item.message.as_slice(), item.pk))
+        .collect::<Vec<_>>();
+    if E::verify_signatures(&batch, cipher_suite) {
+        return BTreeSet::new();
+    }
+    if let [item] = items {
+        return BTreeSet::from([item.position]);
+    }
+    let (left, right) = items.split_at(items.len() / 2);
+    let (mut invalid, right) = join(
+        || bisect::<E>(left, cipher_suite),
+        || bisect::<E>(right, cipher_suite),
+    );
+    invalid.extend(right);
+    invalid
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{signature::EcdsaSignature, DefaultEngine, SignaturePolicy};
+    use ethers_core::types::Signature;
+    use secrecy::Secret;
+
+    fn transcript(num_participants: u8) -> BatchTranscript {
+        let mut transcript = BatchTranscript::new([(4, 2), (8, 2)].iter());
+        for no in 1..=num_participants {
+            let identity = Identity::Github {
+                id:       no.into(),
+                username: format!("user{no}"),
+            };
+            let mut contribution = transcript.contribution();
+            contribution
+                .add_entropy::<DefaultEngine>(
+                    &Secret::new([no; 32]),
+                    &identity,
+                    &transcript.parameters,
+                )
+                .unwrap();
+            transcript
+                .verify_add::<DefaultEngine>(contribution, identity, SignaturePolicy::strict())
+                .unwrap();
+        }
+        transcript
+    }
+
+    #[test]
+    fn reports_valid_signatures() {
+        let report = transcript(3).signature_report::<DefaultEngine>();
+        assert_eq!(report.participants.len(), 3);
+        for (participant, index) in report.participants.iter().zip(1..) {
+            assert_eq!(participant.index, index);
+            assert_eq!(participant.bls_signatures, vec![
+                SignatureStatus::Valid,
+                SignatureStatus::Valid
+            ]);
+            assert_eq!(participant.ecdsa_signature, SignatureStatus::Missing);
+        }
+        assert_eq!(report.inconsistent().count(), 0);
+    }
+
+    #[test]
+    fn locates_invalid_signatures() {
+        let mut transcript = transcript(5);
+        let swapped = transcript.transcripts[1].witness.signatures[2].clone();
+        transcript.transcripts[1].witness.signatures[4] = swapped;
+        transcript.transcripts[0].witness.signatures[1].0 = None;
+        transcript.participant_ecdsa_signatures[3] = EcdsaSignature(Some(Signature {
+            r: 1.into(),
+            s: 1.into(),
+            v: 27,
+        }));
+
+        let report = transcript.signature_report::<DefaultEngine>();
+        let inconsistent = report.inconsistent().map(|p| p.index).collect::<Vec<_>>();
+        assert_eq!(inconsistent, vec![3, 4]);
+        assert_eq!(report.participants[0].bls_signatures, vec![
+            SignatureStatus::Missing,
+            SignatureStatus::Valid
+        ]);
+        assert_eq!(
+            report.participants[2].ecdsa_signature,
+            SignatureStatus::Invalid
+        );
+        assert_eq!(report.participants[3].bls_signatures, vec![
+            SignatureStatus::Valid,
+            SignatureStatus::Invalid
+        ]);
+    }
+}
@@ -1,14 +1,83 @@
 use super::{CeremonyError, Contribution, Powers, G1, G2};
-use crate::{engine::Engine, signature::BlsSignature};
+use crate::{
+    engine::Engine,
+    parallel::prelude::*,
+    progress::{Stage, Tracker},
+    signature::BlsSignature,
+    CancellationToken, Progress,
+};
 use serde::{Deserialize, Serialize};
+use std::{collections::HashMap, fmt};
 use tracing::instrument;
 
 #[derive(Clone, PartialEq, Eq, Debug, Serialize, Deserialize)]
+#[serde(from = "TranscriptJson")]
 pub struct Transcript {
     #[serde(flatten)]
     pub powers: Powers,
 
     pub witness: Witness,
+
+    /// Index of `witness.pubkeys`, to detect reused entropy.
+    #[serde(skip)]
+    pubkey_index: PubkeyIndex,
+}
+
+#[derive(Deserialize)]
+struct TranscriptJson {
+    #[serde(flatten)]
+    powers:  Powers,
+    witness: Witness,
+}
+
+impl From<TranscriptJson> for Transcript {
+    fn from(json: TranscriptJson) -> Self {
+        Self {
+            pubkey_index: PubkeyIndex::new(&json.witness.pubkeys),
+            powers:       json.powers,
+            witness:      json.witness,
+        }
+    }
+}
+
+/// Maps the `potPubkey` of every contribution to its position in the witness.
+/// It is derived from the witness, so it does not take part in comparisons.
+#[derive(Clone, Default)]
+struct PubkeyIndex(HashMap<G2, usize>);
+
+impl PubkeyIndex {
+    fn new(pubkeys: &[G2]) -> Self {
+        let mut index = Self::default();
+        // The first pubkey is the generator, not a contribution, unless the
+        // powers were imported.
+        let skip = usize::from(pubkeys.first() == Some(&G2::one()));
+        for (position, pubkey) in pubkeys.iter().enumerate().skip(skip) {
+            index.insert(*pubkey, position);
+        }
+        index
+    }
+
+    fn insert(&mut self, pubkey: G2, position: usize) {
+        self.0.entry(pubkey).or_insert(position);
+    }
+
+    fn get(&self, pubkey: &G2) -> Option<usize> {
+        self.0.get(pubkey).copied()
+    }
+}
+
+impl PartialEq for PubkeyIndex {
+    fn eq(&self, _other: &Self) -> bool {
+        true
+    }
+}
+
+impl Eq for PubkeyIndex {}
+
+impl fmt::Debug for PubkeyIndex {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "PubkeyIndex({} pubkeys)", self.0.len())
+    }
 }
 
 #[derive(Clone, PartialEq, Eq, Debug, Serialize, Deserialize)]
@@ -24,6 +93,9 @@ pub struct Witness {
 }
 
 impl Transcript {
+    /// Number of stages of [`Transcript::verify`] reported to a [`Tracker`].
+    pub(crate) const VERIFY_STAGES: usize = 2;
+
     /// Create a new transcript for a ceremony of a given size.
     ///
     /// # Panics
@@ -36,13 +108,57 @@ impl Transcript {
         assert!(num_g2 >= 2);
         assert!(num_g1 >= num_g2);
         Self {
-            powers:  Powers::new(num_g1, num_g2),
-            witness: Witness {
+            powers:       Powers::new(num_g1, num_g2),
+            witness:      Witness {
                 products:   vec![G1::one()],
                 pubkeys:    vec![G2::one()],
                 signatures: vec![BlsSignature::empty()],
             },
+            pubkey_index: PubkeyIndex::default(),
+        }
+    }
+
+    /// Creates a transcript that starts from `powers` imported from an earlier
+    /// ceremony, see [`crate::import`]. The first witness entry holds
+    /// `g1[1]` and `g2[1]` of the imported powers, so that the pubkey chain
+    /// of later contributions starts from them.
+    ///
+    /// # Errors
+    /// Returns an error if there are fewer than two powers, more G2 than G1
+    /// powers, the powers do not start with the generators, or they are not
+    /// valid powers of a single $τ$.
+    pub fn import<E: Engine>(powers: Powers) -> Result<Self, CeremonyError> {
+        if powers.g1.len() < 2 {
+            return Err(CeremonyError::UnsupportedNumG1Powers(powers.g1.len()));
+        }
+        if powers.g2.len() < 2 {
+            return Err(CeremonyError::UnsupportedNumG2Powers(powers.g2.len()));
+        }
+        if powers.g2.len() > powers.g1.len() {
+            return Err(CeremonyError::UnsupportedMoreG2Powers(
+                powers.g1.len(),
+                powers.g2.len(),
+            ));
+        }
+        if powers.g1[0] != G1::one() {
+            return Err(CeremonyError::InvalidG1FirstValue);
+        }
+        if powers.g2[0] != G2::one() {
+            return Err(CeremonyError::InvalidG2FirstValue);
         }
+        let valid = E::validate_powers(&powers, powers.g2[1])?;
+        E::verify_powers(&valid, G1::one())?;
+
+        let pubkeys = vec![powers.g2[1]];
+        Ok(Self {
+            witness: Witness {
+                products:   vec![powers.g1[1]],
+                signatures: vec![BlsSignature::empty()],
+                pubkeys:    pubkeys.clone(),
+            },
+            pubkey_index: PubkeyIndex::new(&pubkeys),
+            powers,
+        })
     }
 
     /// Returns the number of participants that contributed to this transcript.
@@ -51,6 +167,43 @@ impl Transcript {
         self.witness.pubkeys.len() - 1
     }
 
+    /// Verifies the chain of running products and pubkeys in the witness,
+    /// from the starting point in the first entry to the current powers.
+    ///
+    /// # Errors
+    /// Returns an error if the witness lengths differ, any of the points is
+    /// invalid, a running product is not the previous one multiplied by the
+    /// $τ$ of its pubkey, or the last running product is not `g1[1]`.
+    #[instrument(level = "info", skip_all, fields(n=self.witness.pubkeys.len()))]
+    pub fn verify_witness<E: Engine>(&self) -> Result<(), CeremonyError> {
+        let Witness {
+            products, pubkeys, ..
+        } = &self.witness;
+        if products.len() != pubkeys.len() {
+            return Err(CeremonyError::WitnessLengthMismatch(
+                products.len(),
+                pubkeys.len(),
+            ));
+        }
+        // The first entry is checked against the generator, as the starting
+        // point is either the generators or imported powers.
+        products
+            .par_iter()
+            .zip(pubkeys)
+            .enumerate()
+            .try_for_each(|(i, (product, pubkey))| {
+                let previous = if i == 0 { G1::one() } else { products[i - 1] };
+                E::verify_pubkey(*product, previous, *pubkey).map_err(|e| match e {
+                    CeremonyError::PubKeyPairingFailed => CeremonyError::WitnessPairingFailed(i),
+                    e => e,
+                })
+            })?;
+        if products.last() != self.powers.g1.get(1) {
+            return Err(CeremonyError::WitnessPowersMismatch);
+        }
+        Ok(())
+    }
+
     /// True if there is at least one contribution.
     #[must_use]
     pub fn has_entropy(&self) -> bool {
@@ -68,8 +221,24 @@ impl Transcript {
     }
 
     /// Verifies a contribution.
-    #[instrument(level = "info", skip_all, fields(n1=self.powers.g1.len(), n2=self.powers.g2.len()))]
     pub fn verify<E: Engine>(&self, contribution: &Contribution) -> Result<(), CeremonyError> {
+        self.verify_tracked::<E>(
+            contribution,
+            &Tracker::new(
+                Stage::Verify,
+                Self::VERIFY_STAGES,
+                &|_: Progress| (),
+                &CancellationToken::default(),
+            ),
+        )
+    }
+
+    #[instrument(level = "info", skip_all, fields(n1=self.powers.g1.len(), n2=self.powers.g2.len()))]
+    pub(crate) fn verify_tracked<E: Engine>(
+        &self,
+        contribution: &Contribution,
+        tracker: &Tracker,
+    ) -> Result<(), CeremonyError> {
         // Compatibility checks
         if self.powers.g1.len() != contribution.powers.g1.len() {
             return Err(CeremonyError::UnexpectedNumG1Powers(
@@ -84,27 +253,26 @@ impl Transcript {
             ));
         }
 
-        // Verify the contribution points (encoding and subgroup checks).
-        E::validate_g1(&contribution.powers.g1)?;
-        E::validate_g2(&contribution.powers.g2)?;
-        E::validate_g2(&[contribution.pot_pubkey])?;
+        // Verify the contribution points (encoding and subgroup checks), and
+        // keep them decompressed for the pairing checks.
+        tracker.check()?;
+        let powers = E::validate_powers(&contribution.powers, contribution.pot_pubkey)?;
+        tracker.advance(1);
 
         // Non-zero check
         if contribution.pot_pubkey == G2::zero() {
             return Err(CeremonyError::ZeroPubkey);
         }
 
+        // Entropy must not be replayed from an earlier contribution.
+        if let Some(position) = self.pubkey_index.get(&contribution.pot_pubkey) {
+            return Err(CeremonyError::ReusedPubkey(position));
+        }
+
         // Verify pairings.
-        E::verify_pubkey(
-            contribution.powers.g1[1],
-            self.powers.g1[1],
-            contribution.pot_pubkey,
-        )?;
-        E::verify_g1(&contribution.powers.g1, contribution.powers.g2[1])?;
-        E::verify_g2(
-            &contribution.powers.g1[..contribution.powers.g2.len()],
-            &contribution.powers.g2,
-        )?;
+        tracker.check()?;
+        E::verify_powers(&powers, self.powers.g1[1])?;
+        tracker.advance(1);
 
         // Accept
         Ok(())
@@ -113,6 +281,8 @@ impl Transcript {
     /// Adds a contribution to the transcript. The contribution must be
     /// verified.
     pub fn add(&mut self, contribution: Contribution) {
+        self.pubkey_index
+            .insert(contribution.pot_pubkey, self.witness.pubkeys.len());
         self.witness.products.push(contribution.powers.g1[1]);
         self.witness.pubkeys.push(contribution.pot_pubkey);
         self.witness.signatures.push(contribution.bls_signature);
@@ -125,11 +295,13 @@ mod test {
     use super::*;
     use crate::{
         CeremonyError::{
-            G1PairingFailed, G2PairingFailed, InvalidG1Power, InvalidG2Power, PubKeyPairingFailed,
-            UnexpectedNumG1Powers, UnexpectedNumG2Powers,
+            G1PairingFailed, G2PairingFailed, InvalidG1FirstValue, InvalidG1Power, InvalidG2Power,
+            PubKeyPairingFailed, ReusedPubkey, UnexpectedNumG1Powers, UnexpectedNumG2Powers,
+            WitnessLengthMismatch, WitnessPairingFailed, WitnessPowersMismatch,
         },
-        DefaultEngine,
+        CeremonyParameters, DefaultEngine, Identity,
         ParseError::InvalidSubgroup,
+        Secret,
     };
     use ark_bls12_381::{Fr, G1Affine, G2Affine};
     use ark_ec::{AffineCurve, ProjectiveCurve};
@@ -340,4 +512,117 @@ mod test {
             .unwrap();
         assert_eq!(result, UnexpectedNumG2Powers(3, 2));
     }
+
+    #[test]
+    fn test_verify_reused_pubkey() {
+        let mut transcript = Transcript::new(3, 3);
+        let tau = DefaultEngine::generate_tau(&Secret::new([7; 32]));
+        let mut contribution = transcript.contribution();
+        contribution
+            .add_tau::<DefaultEngine>(&tau, &Identity::None, &CeremonyParameters::default())
+            .unwrap();
+        transcript.verify::<DefaultEngine>(&contribution).unwrap();
+        transcript.add(contribution);
+
+        // Same entropy again, applied on top of the new powers.
+        let mut replayed = transcript.contribution();
+        replayed
+            .add_tau::<DefaultEngine>(&tau, &Identity::None, &CeremonyParameters::default())
+            .unwrap();
+        assert_eq!(
+            transcript.verify::<DefaultEngine>(&replayed).err().unwrap(),
+            ReusedPubkey(1)
+        );
+
+        // The index is rebuilt when the transcript is loaded.
+        let json = serde_json::to_string(&transcript).unwrap();
+        let loaded = serde_json::from_str::<Transcript>(&json).unwrap();
+        assert_eq!(
+            loaded.verify::<DefaultEngine>(&replayed).err().unwrap(),
+            ReusedPubkey(1)
+        );
+    }
+
+    #[test]
+    fn test_import() {
+        let tau = DefaultEngine::generate_tau(&Secret::new([5; 32]));
+        let mut powers = Powers::new(4, 3);
+        DefaultEngine::add_tau_g1(&tau, &mut powers.g1).unwrap();
+        DefaultEngine::add_tau_g2(&tau, &mut powers.g2).unwrap();
+
+        let mut transcript = Transcript::import::<DefaultEngine>(powers.clone()).unwrap();
+        assert_eq!(transcript.num_participants(), 0);
+        assert_eq!(transcript.witness.products, vec![powers.g1[1]]);
+        assert_eq!(transcript.witness.pubkeys, vec![powers.g2[1]]);
+        assert_eq!(transcript.verify_witness::<DefaultEngine>(), Ok(()));
+
+        // Contributions build on the imported powers.
+        let mut contribution = transcript.contribution();
+        contribution
+            .add_tau::<DefaultEngine>(
+                &DefaultEngine::generate_tau(&Secret::new([6; 32])),
+                &Identity::None,
+                &CeremonyParameters::default(),
+            )
+            .unwrap();
+        transcript.verify::<DefaultEngine>(&contribution).unwrap();
+        transcript.add(contribution);
+        assert_eq!(transcript.verify_witness::<DefaultEngine>(), Ok(()));
+
+        // The imported entropy can not be contributed again.
+        let mut replayed = transcript.contribution();
+        replayed
+            .add_tau::<DefaultEngine>(&tau, &Identity::None, &CeremonyParameters::default())
+            .unwrap();
+        assert_eq!(
+            transcript.verify::<DefaultEngine>(&replayed).err().unwrap(),
+            ReusedPubkey(0)
+        );
+
+        let mut unordered = powers.clone();
+        unordered.g1.swap(2, 3);
+        assert!(Transcript::import::<DefaultEngine>(unordered).is_err());
+
+        let mut shifted = powers;
+        shifted.g1[0] = shifted.g1[1];
+        assert_eq!(
+            Transcript::import::<DefaultEngine>(shifted).err().unwrap(),
+            InvalidG1FirstValue
+        );
+    }
+
+    #[test]
+    fn test_verify_witness() {
+        let mut transcript = Transcript::new(3, 3);
+        assert_eq!(transcript.verify_witness::<DefaultEngine>(), Ok(()));
+
+        let tau = DefaultEngine::generate_tau(&Secret::new([7; 32]));
+        let mut contribution = transcript.contribution();
+        contribution
+            .add_tau::<DefaultEngine>(&tau, &Identity::None, &CeremonyParameters::default())
+            .unwrap();
+        transcript.add(contribution);
+        assert_eq!(transcript.verify_witness::<DefaultEngine>(), Ok(()));
+
+        let mut tampered = transcript.clone();
+        tampered.witness.pubkeys[1] = G2::one();
+        assert_eq!(
+            tampered.verify_witness::<DefaultEngine>(),
+            Err(WitnessPairingFailed(1))
+        );
+
+        let mut tampered = transcript.clone();
+        tampered.witness.products.push(G1::one());
+        assert_eq!(
+            tampered.verify_witness::<DefaultEngine>(),
+            Err(WitnessLengthMismatch(3, 2))
+        );
+
+        let mut tampered = transcript;
+        tampered.powers.g1.swap(1, 2);
+        assert_eq!(
+            tampered.verify_witness::<DefaultEngine>(),
+            Err(WitnessPowersMismatch)
+        );
+    }
 }
@@ -0,0 +1,75 @@
+//! WebAssembly bindings for in-browser contributions.
+//!
+//! Values are passed as JSON strings in the format of the sequencer API, so
+//! a client can forward them unchanged. The ceremony parameters default to
+//! mainnet when `parameters` is `undefined`.
+
+use crate::{
+    get_pot_pubkeys, signature::ContributionTypedData, Arkworks, BatchContribution,
+    CeremonyParameters, Identity,
+};
+use ethers_core::types::transaction::eip712::TypedData;
+use secrecy::Secret;
+use wasm_bindgen::prelude::*;
+
+fn parameters(parameters: Option<String>) -> Result<CeremonyParameters, JsError> {
+    parameters.map_or_else(
+        || Ok(CeremonyParameters::mainnet()),
+        |json| Ok(serde_json::from_str(&json)?),
+    )
+}
+
+fn entropy(entropy: &[u8]) -> Result<Secret<[u8; 32]>, JsError> {
+    <[u8; 32]>::try_from(entropy)
+        .map(Secret::new)
+        .map_err(|_| JsError::new("entropy must be 32 bytes"))
+}
+
+/// Adds `entropy` to the contribution returned by `/lobby/try_contribute` and
+/// signs `identity`, e.g. `eth|0x…` or `git|1234|username`.
+///
+/// # Errors
+/// Returns an error if any argument does not parse, or if the contribution
+/// contains invalid points.
+#[wasm_bindgen(js_name = addEntropy)]
+pub fn add_entropy(
+    contribution: &str,
+    entropy: &[u8],
+    identity: &str,
+    parameters: Option<String>,
+) -> Result<String, JsError> {
+    let mut contribution: BatchContribution = serde_json::from_str(contribution)?;
+    let identity: Identity = identity.parse()?;
+    contribution.add_entropy::<Arkworks>(
+        &self::entropy(entropy)?,
+        &identity,
+        &self::parameters(parameters)?,
+    )?;
+    Ok(serde_json::to_string(&contribution)?)
+}
+
+/// Returns the EIP-712 typed data for `eth_signTypedData_v4`, that an
+/// Ethereum participant signs for `contribution`.
+///
+/// # Errors
+/// Returns an error if any argument does not parse.
+#[wasm_bindgen(js_name = contributionTypedData)]
+pub fn contribution_typed_data(
+    contribution: &str,
+    parameters: Option<String>,
+) -> Result<String, JsError> {
+    let contribution: BatchContribution = serde_json::from_str(contribution)?;
+    let typed_data = ContributionTypedData::new(&contribution, &self::parameters(parameters)?);
+    Ok(serde_json::to_string(&TypedData::from(typed_data))?)
+}
+
+/// Returns the `potPubkey`s that `addEntropy` derives from `entropy`, so that
+/// they can be signed before contributing.
+///
+/// # Errors
+/// Returns an error if `entropy` is not 32 bytes.
+#[wasm_bindgen(js_name = getPotPubkeys)]
+pub fn pot_pubkeys(entropy: &[u8]) -> Result<String, JsError> {
+    let pubkeys = get_pot_pubkeys::<Arkworks>(&self::entropy(entropy)?);
+    Ok(serde_json::to_string(&pubkeys)?)
+}
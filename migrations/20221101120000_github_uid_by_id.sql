@@ -0,0 +1,7 @@
+-- Github participants used to be keyed by `git|{id}|{username}`, which
+-- changes when the account is renamed. Strip the username so the key only
+-- depends on the immutable numeric id. Sticks to functions that SQLite and
+-- Postgres share: the id ends where trimming its digits stops.
+UPDATE contributors
+SET uid = substr(uid, 1, length(uid) - length(ltrim(substr(uid, 5), '0123456789')))
+WHERE uid LIKE 'git|%|%';
@@ -0,0 +1,16 @@
+-- Record the full outcome of every contribution attempt, so support requests
+-- can be answered from the database.
+ALTER TABLE contributors ADD COLUMN provider TEXT;
+ALTER TABLE contributors ADD COLUMN identity TEXT;
+ALTER TABLE contributors ADD COLUMN session_id TEXT;
+ALTER TABLE contributors ADD COLUMN transcript_index INTEGER;
+ALTER TABLE contributors ADD COLUMN pot_pubkeys TEXT;
+ALTER TABLE contributors ADD COLUMN ecdsa_signature_valid BOOLEAN;
+ALTER TABLE contributors ADD COLUMN bls_signatures_valid TEXT;
+ALTER TABLE contributors ADD COLUMN receipt TEXT;
+ALTER TABLE contributors ADD COLUMN receipt_signature TEXT;
+ALTER TABLE contributors ADD COLUMN error_code TEXT;
+
+CREATE INDEX contributors_uid ON contributors (uid);
+CREATE INDEX contributors_session_id ON contributors (session_id);
+CREATE INDEX contributors_transcript_index ON contributors (transcript_index);
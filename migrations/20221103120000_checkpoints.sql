@@ -0,0 +1,8 @@
+-- Sequencer-signed commitments to the transcript after each accepted
+-- contribution, see `src/checkpoint.rs`.
+CREATE TABLE checkpoints (
+    transcript_index INTEGER PRIMARY KEY NOT NULL,
+    transcript_hash  TEXT                NOT NULL,
+    previous_hash    TEXT                NOT NULL,
+    signature        TEXT                NOT NULL
+);
@@ -0,0 +1,10 @@
+-- Hash-chained, sequencer-signed log of ceremony events, see `src/audit.rs`.
+-- Entries are only ever appended.
+CREATE TABLE audit_log (
+    sequence      INTEGER PRIMARY KEY NOT NULL,
+    timestamp     INTEGER             NOT NULL,
+    event         TEXT                NOT NULL,
+    previous_hash TEXT                NOT NULL,
+    hash          TEXT                NOT NULL,
+    signature     TEXT                NOT NULL
+);
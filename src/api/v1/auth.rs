@@ -1,4 +1,5 @@
 use crate::{
+    audit::{AuditEvent, AuditLog, SharedAuditLog},
     lobby::SharedLobbyState,
     oauth::{EthOAuthClient, GithubOAuthClient, SharedAuthState},
     sessions::IdToken,
@@ -89,7 +90,7 @@ impl IntoResponse for UserVerifiedResponse {
                 redirect_url
                     .query_pairs_mut()
                     .append_pair("session_id", &self.session_id)
-                    .append_pair("sub", &self.id_token.identity.unique_id())
+                    .append_pair("sub", &self.id_token.identity.to_string())
                     .append_pair("nickname", &self.id_token.identity.nickname())
                     .append_pair("provider", &self.id_token.identity.provider_name())
                     .append_pair("exp", &self.id_token.exp.to_string());
@@ -97,7 +98,7 @@ impl IntoResponse for UserVerifiedResponse {
             }
             None => Json(json!({
                 "id_token" : {
-                    "sub": &self.id_token.identity.unique_id(),
+                    "sub": &self.id_token.identity.to_string(),
                     "nickname": &self.id_token.identity.nickname(),
                     "provider": &self.id_token.identity.provider_name(),
                     "exp": &self.id_token.exp,
@@ -234,6 +235,7 @@ pub async fn github_callback(
     Extension(auth_state): Extension<SharedAuthState>,
     Extension(lobby_state): Extension<SharedLobbyState>,
     Extension(storage): Extension<PersistentStorage>,
+    Extension(audit): Extension<SharedAuditLog>,
     Extension(gh_oauth_client): Extension<GithubOAuthClient>,
     Extension(http_client): Extension<reqwest::Client>,
 ) -> Result<UserVerifiedResponse, AuthError> {
@@ -287,6 +289,7 @@ pub async fn github_callback(
         auth_state,
         lobby_state,
         storage,
+        &audit,
         user,
         payload.redirect_to,
         options.multi_contribution,
@@ -315,6 +318,7 @@ pub async fn eth_callback(
     Extension(auth_state): Extension<SharedAuthState>,
     Extension(lobby_state): Extension<SharedLobbyState>,
     Extension(storage): Extension<PersistentStorage>,
+    Extension(audit): Extension<SharedAuditLog>,
     Extension(oauth_client): Extension<EthOAuthClient>,
     Extension(http_client): Extension<reqwest::Client>,
 ) -> Result<UserVerifiedResponse, AuthError> {
@@ -383,6 +387,7 @@ pub async fn eth_callback(
         auth_state,
         lobby_state,
         storage,
+        &audit,
         user_data,
         payload.redirect_to,
         options.multi_contribution,
@@ -426,6 +431,7 @@ async fn post_authenticate(
     auth_state: SharedAuthState,
     lobby_state: SharedLobbyState,
     storage: PersistentStorage,
+    audit: &AuditLog,
     user_data: Identity,
     redirect_to: Option<String>,
     multi_contribution: bool,
@@ -484,6 +490,11 @@ async fn post_authenticate(
             redirect: redirect_to.clone(),
             payload:  AuthErrorPayload::LobbyIsFull,
         })?;
+    audit
+        .record(AuditEvent::Login {
+            uid: id_token.identity.unique_id(),
+        })
+        .await;
 
     Ok(UserVerifiedResponse {
         id_token,
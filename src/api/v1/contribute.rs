@@ -1,23 +1,31 @@
 use crate::{
-    io::{write_json_file, TranscriptIoError},
+    archive::{transcript_hash, ArchiveEntry, ArchiveError, ContributionArchive, ORPHANED_DIR},
+    audit::{AuditEvent, SharedAuditLog},
+    checkpoint::Checkpoint,
+    io::TranscriptIoError,
     keys::{SharedKeys, Signature, SignatureError},
     lobby::SharedLobbyState,
     receipt::Receipt,
-    storage::{PersistentStorage, StorageError},
-    Engine, Options, SessionId, SharedCeremonyStatus, SharedTranscript,
+    storage::{AcceptedContribution, PersistentStorage, StorageError},
+    transcript_store::{save_transcript, SharedTranscriptStore, TranscriptStore},
+    Options, SessionId, SharedCeremonyStatus, SharedTranscript,
 };
 use axum::{
     response::{IntoResponse, Response},
     Extension, Json,
 };
 use axum_extra::response::ErasedJson;
+use chrono::Utc;
 use http::StatusCode;
-use kzg_ceremony_crypto::{BatchContribution, CeremoniesError, ErrorCode};
+use kzg_ceremony_crypto::{BatchContribution, CancellationToken, CeremoniesError, ErrorCode};
 use serde::Serialize;
-use std::sync::atomic::Ordering;
+use std::{
+    mem,
+    sync::{atomic::Ordering, Arc},
+};
 use strum::IntoStaticStr;
 use thiserror::Error;
-use tokio::task::JoinError;
+use tokio::{sync::RwLock, task::JoinError};
 use tracing::error;
 
 #[derive(Serialize)]
@@ -40,12 +48,18 @@ pub enum ContributeError {
     InvalidContribution(#[from] CeremoniesError),
     #[error("receipt signing error: {0}")]
     ReceiptSigning(SignatureError),
+    #[error("checkpoint signing error: {0}")]
+    CheckpointSigning(SignatureError),
     #[error("storage error: {0}")]
     StorageError(#[from] StorageError),
     #[error("Transcript IO error: {0}")]
     TranscriptIOError(#[from] TranscriptIoError),
+    #[error("archive error: {0}")]
+    ArchiveError(#[from] ArchiveError),
     #[error("background task error: {0}")]
     TaskError(#[from] JoinError),
+    #[error("the sequencer is shutting down")]
+    ShuttingDown,
 }
 
 impl ErrorCode for ContributeError {
@@ -64,6 +78,10 @@ pub async fn contribute(
     Extension(storage): Extension<PersistentStorage>,
     Extension(num_contributions): Extension<SharedCeremonyStatus>,
     Extension(keys): Extension<SharedKeys>,
+    Extension(archive): Extension<ContributionArchive>,
+    Extension(store): Extension<SharedTranscriptStore>,
+    Extension(audit): Extension<SharedAuditLog>,
+    Extension(shutdown): Extension<CancellationToken>,
 ) -> Result<ContributeReceipt, ContributeError> {
     // Handle the contribution in the background, so that request cancelation
     // doesn't interrupt it.
@@ -73,48 +91,145 @@ pub async fn contribute(
             .await
             .map_err(|_| ContributeError::NotUsersTurn)?
             .token;
+        let uid = id_token.identity.unique_id();
 
+        // Verify against a copy, so that the shared transcript only changes
+        // once the contribution is archived and stored.
+        let mut transcript = shared_transcript.read().await.clone();
         let result = {
-            let mut transcript = shared_transcript.write().await;
-            transcript
-                .verify_add::<Engine>(contribution.clone(), id_token.identity.clone())
-                .map_err(ContributeError::InvalidContribution)
+            let previous_hash = transcript_hash(&transcript);
+            options
+                .engine
+                .verify_add(
+                    &mut transcript,
+                    contribution.clone(),
+                    id_token.identity.clone(),
+                    options.signature_policy(),
+                    &shutdown,
+                )
+                .map_err(|e| match e {
+                    CeremoniesError::Cancelled => ContributeError::ShuttingDown,
+                    e => ContributeError::InvalidContribution(e),
+                })
+                .and_then(|()| {
+                    let accepted = AcceptedContribution::from_transcript(
+                        &transcript,
+                        transcript.num_participants(),
+                    );
+                    let entry = ArchiveEntry {
+                        index:           accepted.transcript_index,
+                        identity:        id_token.identity.clone(),
+                        contribution:    contribution.clone(),
+                        previous_hash:   previous_hash.map_err(ArchiveError::from)?,
+                        transcript_hash: transcript_hash(&transcript)
+                            .map_err(ArchiveError::from)?,
+                    };
+                    Ok((accepted, entry))
+                })
         };
 
-        if let Err(e) = result {
-            lobby_state.clear_current_contributor().await;
-            storage
-                .expire_contribution(&id_token.unique_identifier())
-                .await?;
-            return Err(e);
-        }
+        let (mut accepted, entry) = match result {
+            Ok(result) => result,
+            Err(ContributeError::ShuttingDown) => {
+                // Not the participant's fault, so the attempt is not recorded
+                // as a rejection.
+                lobby_state.clear_current_contributor().await;
+                storage.expire_contribution(&session_id).await?;
+                return Err(ContributeError::ShuttingDown);
+            }
+            Err(e) => {
+                lobby_state.clear_current_contributor().await;
+                storage
+                    .reject_contribution(&session_id, &e.to_error_code())
+                    .await?;
+                audit
+                    .record(AuditEvent::Rejected {
+                        uid,
+                        error_code: e.to_error_code(),
+                    })
+                    .await;
+                return Err(e);
+            }
+        };
+        let accepted_event = AuditEvent::Accepted {
+            uid:              uid.clone(),
+            transcript_index: entry.index,
+            transcript_hash:  entry.transcript_hash.clone(),
+        };
 
-        let result = write_json_file(
-            options.transcript_file,
-            options.transcript_in_progress_file,
-            shared_transcript,
-        )
-        .await;
+        let receipt = Receipt {
+            identity:         id_token.identity,
+            witness:          contribution.receipt(),
+            transcript_index: entry.index,
+            transcript_hash:  entry.transcript_hash.clone(),
+            timestamp:        u64::try_from(Utc::now().timestamp()).unwrap_or_default(),
+        };
+        let signed_receipt = receipt
+            .sign(&keys, options.receipt_format, &transcript.parameters)
+            .await;
+        accepted.receipt = signed_receipt
+            .as_ref()
+            .ok()
+            .map(|(message, signature)| (message.clone(), signature.0.clone()));
+
+        let checkpoint = Checkpoint::from(&entry).sign(&keys).await;
+
+        let transcript = Arc::new(RwLock::new(transcript));
+        let result = match checkpoint {
+            Ok(checkpoint) => persist(
+                archive,
+                entry,
+                &*store,
+                transcript.clone(),
+                options.transcript_store.transcript_publish_interval,
+            )
+            .await
+            .map(|()| checkpoint),
+            Err(e) => Err(ContributeError::CheckpointSigning(e)),
+        };
+        if result.is_ok() {
+            mem::swap(
+                &mut *shared_transcript.write().await,
+                &mut *transcript.write().await,
+            );
+        }
 
         lobby_state.clear_current_contributor().await;
-        storage.finish_contribution(&session_id.0).await?;
 
-        if let Err(e) = result {
-            error!("failed to write transcript: {}", e);
-            return Err(ContributeError::TranscriptIOError(e));
-        }
+        let checkpoint = match result {
+            Ok(checkpoint) => checkpoint,
+            Err(e) => {
+                error!("failed to persist transcript: {}", e);
+                storage
+                    .reject_contribution(&session_id, &e.to_error_code())
+                    .await?;
+                audit
+                    .record(AuditEvent::Rejected {
+                        uid,
+                        error_code: e.to_error_code(),
+                    })
+                    .await;
+                return Err(e);
+            }
+        };
 
+        // Only mark the attempt as finished, and publish the checkpoint, once
+        // the transcript containing it is stored. Should this fail, both are
+        // recovered from the transcript at the next start.
+        let recorded = async {
+            let mut transaction = storage.begin().await?;
+            transaction
+                .finish_contribution(&session_id, &accepted)
+                .await?;
+            transaction.insert_checkpoint(&checkpoint).await?;
+            transaction.commit().await
+        }
+        .await;
+        audit.record(accepted_event).await;
         num_contributions.fetch_add(1, Ordering::Relaxed);
+        recorded?;
 
-        let receipt = Receipt {
-            identity: id_token.identity,
-            witness:  contribution.receipt(),
-        };
-
-        let (signed_msg, signature) = receipt
-            .sign(&keys)
-            .await
-            .map_err(ContributeError::ReceiptSigning)?;
+        let (signed_msg, signature) = signed_receipt.map_err(ContributeError::ReceiptSigning)?;
 
         Ok(ContributeReceipt {
             receipt: signed_msg,
@@ -127,7 +242,9 @@ pub async fn contribute(
         if matches!(
             err,
             ContributeError::ReceiptSigning(_)
+                | ContributeError::CheckpointSigning(_)
                 | ContributeError::StorageError(_)
+                | ContributeError::ArchiveError(_)
                 | ContributeError::TaskError(_)
         ) {
             error!(?err, "unexpected error recording contribution");
@@ -136,20 +253,47 @@ pub async fn contribute(
     res
 }
 
+/// Archives the contribution and stores the transcript containing it. If the
+/// transcript can not be stored, the archived entry is set aside again, so
+/// that the archive does not get ahead of the transcript.
+async fn persist(
+    archive: ContributionArchive,
+    entry: ArchiveEntry,
+    store: &dyn TranscriptStore,
+    transcript: SharedTranscript,
+    publish_interval: usize,
+) -> Result<(), ContributeError> {
+    let index = entry.index;
+    let appending = archive.clone();
+    tokio::task::spawn_blocking(move || appending.append(&entry)).await??;
+    if let Err(e) = save_transcript(store, transcript, publish_interval).await {
+        tokio::task::spawn_blocking(move || archive.set_aside_after(index - 1, ORPHANED_DIR))
+            .await??;
+        return Err(e.into());
+    }
+    Ok(())
+}
+
 pub async fn contribute_abort(
     session_id: SessionId,
     Extension(lobby_state): Extension<SharedLobbyState>,
     Extension(storage): Extension<PersistentStorage>,
+    Extension(audit): Extension<SharedAuditLog>,
 ) -> Result<(), ContributeError> {
     // Abort the contribution in the background,
     // so that request cancelation doesn't interrupt it inbetween the lobby_state
     // and storage calls.
     tokio::spawn(async move {
-        lobby_state
+        let info = lobby_state
             .abort_contribution(&session_id)
             .await
             .map_err(|_| ContributeError::NotUsersTurn)?;
-        storage.expire_contribution(&session_id.0).await?;
+        storage.expire_contribution(&session_id).await?;
+        audit
+            .record(AuditEvent::Aborted {
+                uid: info.token.identity.unique_id(),
+            })
+            .await;
         Ok(())
     })
     .await
@@ -166,32 +310,40 @@ mod tests {
         },
         contribute,
         io::read_json_file,
-        keys,
         keys::SharedKeys,
         lobby::SharedLobbyState,
-        storage::storage_client,
-        test_util::{create_test_session_info, test_options},
+        storage::{storage_client, IN_MEMORY_URL},
+        test_util::{create_test_session_info, test_audit_log, test_keys, test_options},
         tests::{invalid_contribution, test_transcript, valid_contribution},
-        Keys, SessionId,
+        transcript_store::transcript_store,
+        Engine, SessionId,
     };
-    use axum::{Extension, Json};
-    use clap::Parser;
-    use kzg_ceremony_crypto::{signature::identity::Identity, BatchTranscript};
-    use std::{
-        sync::{atomic::AtomicUsize, Arc},
-        time::Duration,
+    use axum::{
+        async_trait,
+        body::{BoxBody, Bytes},
+        Extension, Json,
     };
-    use tokio::sync::RwLock;
+    use kzg_ceremony_crypto::{signature::identity::Identity, BatchTranscript, SignaturePolicy};
+    use std::{io, sync::atomic::AtomicUsize, time::Duration};
+    use tempfile::tempdir;
+
+    fn test_archive(opts: &Options) -> ContributionArchive {
+        ContributionArchive::new(opts.archive.transcript_archive_dir.clone())
+    }
+
+    fn test_store(opts: &Options) -> SharedTranscriptStore {
+        transcript_store(opts).unwrap()
+    }
 
     fn shared_keys() -> SharedKeys {
-        let options = keys::Options::parse_from(Vec::<&str>::new());
-        Arc::new(Keys::new(&options).unwrap())
+        Arc::new(test_keys())
     }
 
     #[tokio::test]
     async fn rejects_out_of_turn_contribution() {
         let opts = test_options();
         let db = storage_client(&opts.storage).await.unwrap();
+        let audit = test_audit_log(db.clone()).await;
         let lobby_state = SharedLobbyState::new(opts.lobby.clone());
         let transcript = test_transcript();
         let contrbution = valid_contribution(&transcript, 1);
@@ -199,11 +351,15 @@ mod tests {
             SessionId::new(),
             Json(contrbution),
             Extension(lobby_state),
-            Extension(opts),
+            Extension(opts.clone()),
             Extension(Arc::new(RwLock::new(transcript))),
             Extension(db),
             Extension(Arc::new(AtomicUsize::new(0))),
             Extension(shared_keys()),
+            Extension(test_archive(&opts)),
+            Extension(test_store(&opts)),
+            Extension(audit.clone()),
+            Extension(CancellationToken::new()),
         )
         .await;
         assert!(matches!(result, Err(ContributeError::NotUsersTurn)));
@@ -213,6 +369,7 @@ mod tests {
     async fn rejects_invalid_contribution() {
         let opts = test_options();
         let db = storage_client(&opts.storage).await.unwrap();
+        let audit = test_audit_log(db.clone()).await;
         let lobby_state = SharedLobbyState::new(opts.lobby.clone());
         let participant = SessionId::new();
         lobby_state
@@ -221,7 +378,12 @@ mod tests {
             .unwrap();
         lobby_state.enter_lobby(&participant).await.unwrap();
         lobby_state
-            .set_current_contributor(&participant, opts.lobby.compute_deadline, db.clone())
+            .set_current_contributor(
+                &participant,
+                opts.lobby.compute_deadline,
+                db.clone(),
+                audit.clone(),
+            )
             .await
             .unwrap();
         let transcript = test_transcript();
@@ -230,11 +392,15 @@ mod tests {
             participant,
             Json(contribution),
             Extension(lobby_state),
-            Extension(opts),
+            Extension(opts.clone()),
             Extension(Arc::new(RwLock::new(transcript))),
             Extension(db),
             Extension(Arc::new(AtomicUsize::new(0))),
             Extension(shared_keys()),
+            Extension(test_archive(&opts)),
+            Extension(test_store(&opts)),
+            Extension(audit.clone()),
+            Extension(CancellationToken::new()),
         )
         .await;
         assert!(matches!(
@@ -243,22 +409,179 @@ mod tests {
         ));
     }
 
+    /// A transcript store that is unavailable.
+    #[derive(Debug)]
+    struct FailingStore;
+
+    #[async_trait]
+    impl TranscriptStore for FailingStore {
+        async fn load(&self) -> Result<Option<Bytes>, TranscriptIoError> {
+            Ok(None)
+        }
+
+        async fn serve(&self) -> Result<Option<BoxBody>, TranscriptIoError> {
+            Ok(None)
+        }
+
+        async fn store(&self, _json: Bytes) -> Result<(), TranscriptIoError> {
+            Err(TranscriptIoError::IoError(io::ErrorKind::Other.into()))
+        }
+
+        async fn store_version(
+            &self,
+            _version: usize,
+            _json: Bytes,
+        ) -> Result<(), TranscriptIoError> {
+            Err(TranscriptIoError::IoError(io::ErrorKind::Other.into()))
+        }
+    }
+
+    #[tokio::test]
+    async fn keeps_transcript_when_store_fails() {
+        keeps_transcript_when_store_fails_with(test_options()).await;
+    }
+
+    #[tokio::test]
+    async fn keeps_transcript_when_store_fails_in_memory() {
+        let mut opts = test_options();
+        opts.storage.database_url = IN_MEMORY_URL.to_string();
+        keeps_transcript_when_store_fails_with(opts).await;
+    }
+
+    async fn keeps_transcript_when_store_fails_with(opts: Options) {
+        let db = storage_client(&opts.storage).await.unwrap();
+        let audit = test_audit_log(db.clone()).await;
+        let lobby_state = SharedLobbyState::new(opts.lobby.clone());
+        let participant = SessionId::new();
+        lobby_state
+            .insert_session(participant.clone(), create_test_session_info(100))
+            .await
+            .unwrap();
+        lobby_state.enter_lobby(&participant).await.unwrap();
+        lobby_state
+            .set_current_contributor(
+                &participant,
+                opts.lobby.compute_deadline,
+                db.clone(),
+                audit.clone(),
+            )
+            .await
+            .unwrap();
+        let transcript = test_transcript();
+        let contribution = valid_contribution(&transcript, 1);
+        let shared_transcript = Arc::new(RwLock::new(transcript.clone()));
+        let dir = tempdir().unwrap();
+        let archive = ContributionArchive::new(dir.path().to_path_buf());
+        let result = contribute(
+            participant,
+            Json(contribution),
+            Extension(lobby_state),
+            Extension(opts),
+            Extension(shared_transcript.clone()),
+            Extension(db.clone()),
+            Extension(Arc::new(AtomicUsize::new(0))),
+            Extension(shared_keys()),
+            Extension(archive.clone()),
+            Extension(Arc::new(FailingStore) as SharedTranscriptStore),
+            Extension(audit.clone()),
+            Extension(CancellationToken::new()),
+        )
+        .await;
+        assert!(matches!(result, Err(ContributeError::TranscriptIOError(_))));
+        assert_eq!(shared_transcript.read().await.num_participants(), 0);
+        assert_eq!(*shared_transcript.read().await, transcript);
+        assert!(matches!(
+            archive.entries(1),
+            Err(ArchiveError::MissingEntry(1))
+        ));
+        assert!(db
+            .contribution_by_transcript_index(1)
+            .await
+            .unwrap()
+            .is_none());
+    }
+
+    #[tokio::test]
+    async fn stops_verification_on_shutdown() {
+        let opts = test_options();
+        let db = storage_client(&opts.storage).await.unwrap();
+        let audit = test_audit_log(db.clone()).await;
+        let lobby_state = SharedLobbyState::new(opts.lobby.clone());
+        let participant = SessionId::new();
+        lobby_state
+            .insert_session(participant.clone(), create_test_session_info(100))
+            .await
+            .unwrap();
+        lobby_state.enter_lobby(&participant).await.unwrap();
+        lobby_state
+            .set_current_contributor(
+                &participant,
+                opts.lobby.compute_deadline,
+                db.clone(),
+                audit.clone(),
+            )
+            .await
+            .unwrap();
+        let transcript = test_transcript();
+        let contribution = valid_contribution(&transcript, 1);
+        let shared_transcript = Arc::new(RwLock::new(transcript));
+        let shutdown = CancellationToken::new();
+        shutdown.cancel();
+        let result = contribute(
+            participant,
+            Json(contribution),
+            Extension(lobby_state),
+            Extension(opts.clone()),
+            Extension(shared_transcript.clone()),
+            Extension(db.clone()),
+            Extension(Arc::new(AtomicUsize::new(0))),
+            Extension(shared_keys()),
+            Extension(test_archive(&opts)),
+            Extension(test_store(&opts)),
+            Extension(audit.clone()),
+            Extension(shutdown),
+        )
+        .await;
+        assert!(matches!(result, Err(ContributeError::ShuttingDown)));
+        assert_eq!(shared_transcript.read().await.num_participants(), 0);
+        let entries = db.audit_entries(0, 10).await.unwrap();
+        assert!(!entries
+            .iter()
+            .any(|entry| matches!(entry.record.event, AuditEvent::Rejected { .. })));
+    }
+
     #[tokio::test]
     async fn accepts_valid_contribution() {
-        let cfg = test_options();
+        accepts_valid_contribution_with(test_options()).await;
+    }
+
+    #[tokio::test]
+    async fn accepts_valid_contribution_in_memory() {
+        let mut cfg = test_options();
+        cfg.storage.database_url = IN_MEMORY_URL.to_string();
+        accepts_valid_contribution_with(cfg).await;
+    }
+
+    #[allow(clippy::too_many_lines)]
+    async fn accepts_valid_contribution_with(cfg: Options) {
         let keys = shared_keys();
         let lobby_state = SharedLobbyState::new(cfg.lobby.clone());
         let participant = SessionId::new();
         let db = storage_client(&cfg.storage).await.unwrap();
+        let audit = test_audit_log(db.clone()).await;
         let transcript = test_transcript();
         let contribution_1 = valid_contribution(&transcript, 1);
         let transcript_1 = {
             let mut transcript = transcript.clone();
             transcript
-                .verify_add::<Engine>(contribution_1.clone(), Identity::Github {
-                    id:       1234,
-                    username: "test_user".to_string(),
-                })
+                .verify_add::<Engine>(
+                    contribution_1.clone(),
+                    Identity::Github {
+                        id:       1234,
+                        username: "test_user".to_string(),
+                    },
+                    SignaturePolicy::default(),
+                )
                 .unwrap();
             transcript
         };
@@ -266,10 +589,14 @@ mod tests {
         let transcript_2 = {
             let mut transcript = transcript_1.clone();
             transcript
-                .verify_add::<Engine>(contribution_2.clone(), Identity::Github {
-                    id:       1234,
-                    username: "test_user".to_string(),
-                })
+                .verify_add::<Engine>(
+                    contribution_2.clone(),
+                    Identity::Github {
+                        id:       1234,
+                        username: "test_user".to_string(),
+                    },
+                    SignaturePolicy::default(),
+                )
                 .unwrap();
             transcript
         };
@@ -282,7 +609,12 @@ mod tests {
         lobby_state.enter_lobby(&participant).await.unwrap();
 
         lobby_state
-            .set_current_contributor(&participant, cfg.lobby.compute_deadline, db.clone())
+            .set_current_contributor(
+                &participant,
+                cfg.lobby.compute_deadline,
+                db.clone(),
+                audit.clone(),
+            )
             .await
             .unwrap();
         let result = contribute(
@@ -294,10 +626,14 @@ mod tests {
             Extension(db.clone()),
             Extension(Arc::new(AtomicUsize::new(0))),
             Extension(keys.clone()),
+            Extension(test_archive(&cfg)),
+            Extension(test_store(&cfg)),
+            Extension(audit.clone()),
+            Extension(CancellationToken::new()),
         )
         .await;
 
-        assert!(matches!(result, Ok(_)));
+        assert!(result.is_ok());
         let transcript = read_json_file::<BatchTranscript>(cfg.transcript_file.clone())
             .await
             .unwrap();
@@ -308,7 +644,12 @@ mod tests {
             .unwrap();
         lobby_state.enter_lobby(&participant).await.unwrap();
         lobby_state
-            .set_current_contributor(&participant, cfg.lobby.compute_deadline, db.clone())
+            .set_current_contributor(
+                &participant,
+                cfg.lobby.compute_deadline,
+                db.clone(),
+                audit.clone(),
+            )
             .await
             .unwrap();
         let result = contribute(
@@ -320,14 +661,32 @@ mod tests {
             Extension(db.clone()),
             Extension(Arc::new(AtomicUsize::new(0))),
             Extension(keys.clone()),
+            Extension(test_archive(&cfg)),
+            Extension(test_store(&cfg)),
+            Extension(audit.clone()),
+            Extension(CancellationToken::new()),
         )
         .await;
 
-        assert!(matches!(result, Ok(_)));
+        assert!(result.is_ok());
         let transcript = read_json_file::<BatchTranscript>(cfg.transcript_file.clone())
             .await
             .unwrap();
         assert_eq!(transcript, transcript_2);
+
+        let events = db
+            .audit_entries(0, 10)
+            .await
+            .unwrap()
+            .into_iter()
+            .map(|entry| entry.record.event)
+            .collect::<Vec<_>>();
+        assert_eq!(events.len(), 2);
+        assert_eq!(events[1], AuditEvent::Accepted {
+            uid:              "git|1234".to_string(),
+            transcript_index: 2,
+            transcript_hash:  transcript_hash(&transcript_2).unwrap(),
+        });
     }
 
     #[tokio::test]
@@ -336,6 +695,7 @@ mod tests {
         let lobby_state = SharedLobbyState::new(opts.lobby.clone());
         let transcript = Arc::new(RwLock::new(test_transcript()));
         let db = storage_client(&opts.storage).await.unwrap();
+        let audit = test_audit_log(db.clone()).await;
 
         let session_id = SessionId::new();
         let other_session_id = SessionId::new();
@@ -352,7 +712,12 @@ mod tests {
         lobby_state.enter_lobby(&other_session_id).await.unwrap();
 
         lobby_state
-            .set_current_contributor(&session_id, opts.lobby.compute_deadline, db.clone())
+            .set_current_contributor(
+                &session_id,
+                opts.lobby.compute_deadline,
+                db.clone(),
+                audit.clone(),
+            )
             .await
             .unwrap();
 
@@ -362,6 +727,7 @@ mod tests {
             Extension(db.clone()),
             Extension(transcript.clone()),
             Extension(test_options()),
+            Extension(audit.clone()),
         )
         .await;
 
@@ -374,6 +740,7 @@ mod tests {
             session_id,
             Extension(lobby_state.clone()),
             Extension(db.clone()),
+            Extension(audit.clone()),
         )
         .await
         .unwrap();
@@ -387,9 +754,15 @@ mod tests {
             Extension(db.clone()),
             Extension(transcript.clone()),
             Extension(test_options()),
+            Extension(audit.clone()),
         )
         .await;
 
         assert!(matches!(success_response, Ok(TryContributeResponse { .. })));
+        let entries = db.audit_entries(0, 10).await.unwrap();
+        assert!(entries.iter().any(|entry| entry.record.event
+            == AuditEvent::Aborted {
+                uid: "git|1234".to_string(),
+            }));
     }
 }
@@ -84,11 +84,12 @@ impl IntoResponse for ContributeError {
         let (status, body) = match self {
             Self::NotUsersTurn => (StatusCode::BAD_REQUEST, error_to_json(&self)),
             Self::InvalidContribution(e) => return CeremoniesErrorFormatter(e).into_response(),
-            Self::ReceiptSigning(err) => return err.into_response(),
+            Self::ReceiptSigning(err) | Self::CheckpointSigning(err) => return err.into_response(),
             Self::StorageError(err) => return err.into_response(),
-            Self::TaskError(_) | Self::TranscriptIOError(_) => {
+            Self::TaskError(_) | Self::TranscriptIOError(_) | Self::ArchiveError(_) => {
                 (StatusCode::INTERNAL_SERVER_ERROR, error_to_json(&self))
             }
+            Self::ShuttingDown => (StatusCode::SERVICE_UNAVAILABLE, error_to_json(&self)),
         };
 
         (status, body).into_response()
@@ -1,24 +1,34 @@
 use crate::{
+    audit::AuditEntry,
+    checkpoint::SignedCheckpoint,
+    engine::EngineChoice,
     keys::{Address, SharedKeys},
     lobby::SharedLobbyState,
+    storage::{PersistentStorage, StorageError},
+    transcript_store::SharedTranscriptStore,
     Options, SharedCeremonyStatus,
 };
 use axum::{
-    body::StreamBody,
+    extract::Query,
     response::{IntoResponse, Response},
     Extension, Json,
 };
 use http::StatusCode;
-use serde::Serialize;
+use kzg_ceremony_crypto::CeremonyParameters;
+use serde::{Deserialize, Serialize};
 use std::sync::atomic::Ordering;
-use tokio::fs::File;
-use tokio_util::io::ReaderStream;
+use tracing::error;
 
 #[derive(Debug, Serialize, PartialEq, Eq)]
 pub struct StatusResponse {
     lobby_size:        usize,
     num_contributions: usize,
     sequencer_address: Address,
+    /// The EIP-712 domain and BLS ciphersuite contributions are signed with,
+    /// which also determine the domain of receipts.
+    parameters:        CeremonyParameters,
+    /// The cryptography engine contributions are verified with.
+    engine:            EngineChoice,
 }
 
 impl IntoResponse for StatusResponse {
@@ -32,6 +42,7 @@ pub async fn status(
     Extension(lobby_state): Extension<SharedLobbyState>,
     Extension(ceremony_status): Extension<SharedCeremonyStatus>,
     Extension(keys): Extension<SharedKeys>,
+    Extension(options): Extension<Options>,
 ) -> StatusResponse {
     let lobby_size = lobby_state.get_lobby_size().await;
 
@@ -42,20 +53,52 @@ pub async fn status(
         lobby_size,
         num_contributions,
         sequencer_address,
+        parameters: options.ceremony_parameters(),
+        engine: options.engine,
     }
 }
 
-pub async fn current_state(Extension(options): Extension<Options>) -> impl IntoResponse {
-    let f = match File::open(options.transcript_file).await {
-        Ok(file) => file,
-        Err(_) => {
-            return Err((
+pub async fn current_state(
+    Extension(store): Extension<SharedTranscriptStore>,
+) -> Result<Response, (StatusCode, &'static str)> {
+    match store.serve().await {
+        Ok(Some(body)) => Ok((StatusCode::OK, body).into_response()),
+        Ok(None) => Err((StatusCode::NOT_FOUND, "transcript not found")),
+        Err(err) => {
+            error!(?err, "could not read transcript");
+            Err((
                 StatusCode::INTERNAL_SERVER_ERROR,
-                "could not open transcript file",
+                "could not read transcript",
             ))
         }
-    };
-    let stream = ReaderStream::new(f);
-    let body = StreamBody::new(stream);
-    Ok((StatusCode::OK, body))
+    }
+}
+
+/// Returns the sequencer-signed checkpoint chain, ordered by index.
+pub async fn checkpoints(
+    Extension(storage): Extension<PersistentStorage>,
+) -> Result<Json<Vec<SignedCheckpoint>>, StorageError> {
+    Ok(Json(storage.checkpoints().await?))
+}
+
+/// Maximum number of audit log entries returned per request.
+pub const AUDIT_LOG_PAGE_SIZE: usize = 1000;
+
+#[derive(Debug, Deserialize)]
+pub struct AuditLogQuery {
+    /// Sequence number of the first entry to return.
+    #[serde(default)]
+    from:  u64,
+    limit: Option<usize>,
+}
+
+/// Returns a page of the audit log, ordered by sequence number.
+pub async fn audit_log(
+    Query(query): Query<AuditLogQuery>,
+    Extension(storage): Extension<PersistentStorage>,
+) -> Result<Json<Vec<AuditEntry>>, StorageError> {
+    let limit = query
+        .limit
+        .map_or(AUDIT_LOG_PAGE_SIZE, |limit| limit.min(AUDIT_LOG_PAGE_SIZE));
+    Ok(Json(storage.audit_entries(query.from, limit).await?))
 }
@@ -1,4 +1,5 @@
 use crate::{
+    audit::{AuditEvent, SharedAuditLog},
     lobby::{ActiveContributorError, SharedLobbyState},
     storage::{PersistentStorage, StorageError},
     SessionId, SharedTranscript,
@@ -67,6 +68,7 @@ pub async fn try_contribute(
     Extension(storage): Extension<PersistentStorage>,
     Extension(transcript): Extension<SharedTranscript>,
     Extension(options): Extension<crate::Options>,
+    Extension(audit): Extension<SharedAuditLog>,
 ) -> Result<TryContributeResponse<BatchContribution>, TryContributeError> {
     let res = lobby_state
         .modify_participant(&session_id, |mut info| {
@@ -78,11 +80,11 @@ pub async fn try_contribute(
             }
             info.is_first_ping_attempt = false;
             info.last_ping_time = now;
-            Ok(info.token.unique_identifier())
+            Ok(info.token.identity.clone())
         })
         .await;
 
-    let uid = if let Some(inner) = res {
+    let identity = if let Some(inner) = res {
         inner?
     } else {
         // Session not found. Check if they're the active contributor, and
@@ -101,14 +103,25 @@ pub async fn try_contribute(
     // so that request cancelation doesn't interrupt it inbetween the lobby_state
     // and storage calls.
     tokio::spawn(async move {
-        lobby_state.enter_lobby(&session_id).await?;
+        let uid = identity.unique_id();
+        if lobby_state.enter_lobby(&session_id).await? {
+            audit
+                .record(AuditEvent::LobbyEntry { uid: uid.clone() })
+                .await;
+        }
 
         lobby_state
-            .set_current_contributor(&session_id, options.lobby.compute_deadline, storage.clone())
+            .set_current_contributor(
+                &session_id,
+                options.lobby.compute_deadline,
+                storage.clone(),
+                audit.clone(),
+            )
             .await
             .map_err(TryContributeError::from)?;
 
-        storage.insert_contributor(&uid).await?;
+        storage.insert_contributor(&session_id, &identity).await?;
+        audit.record(AuditEvent::SlotGranted { uid }).await;
         let transcript = transcript.read().await;
 
         Ok(TryContributeResponse {
@@ -125,7 +138,7 @@ mod tests {
     use crate::{
         api::v1::lobby::TryContributeError,
         storage::storage_client,
-        test_util::{create_test_session_info, test_options},
+        test_util::{create_test_session_info, test_audit_log, test_options},
         tests::test_transcript,
     };
     use std::{sync::Arc, time::Duration};
@@ -138,6 +151,7 @@ mod tests {
         let lobby_state = SharedLobbyState::new(opts.lobby.clone());
         let transcript = Arc::new(RwLock::new(test_transcript()));
         let db = storage_client(&opts.storage).await.unwrap();
+        let audit = test_audit_log(db.clone()).await;
 
         let session_id = SessionId::new();
         let other_session_id = SessionId::new();
@@ -149,6 +163,7 @@ mod tests {
             Extension(db.clone()),
             Extension(transcript.clone()),
             Extension(opts),
+            Extension(audit.clone()),
         )
         .await;
         assert!(matches!(
@@ -171,6 +186,7 @@ mod tests {
             Extension(db.clone()),
             Extension(transcript.clone()),
             Extension(test_options()),
+            Extension(audit.clone()),
         )
         .await
         .unwrap();
@@ -180,6 +196,7 @@ mod tests {
             Extension(db.clone()),
             Extension(transcript.clone()),
             Extension(test_options()),
+            Extension(audit.clone()),
         )
         .await;
 
@@ -198,6 +215,7 @@ mod tests {
             Extension(db.clone()),
             Extension(transcript.clone()),
             Extension(test_options()),
+            Extension(audit.clone()),
         )
         .await;
 
@@ -218,6 +236,7 @@ mod tests {
             Extension(db.clone()),
             Extension(transcript.clone()),
             Extension(test_options()),
+            Extension(audit.clone()),
         )
         .await;
         assert!(matches!(
@@ -235,6 +254,7 @@ mod tests {
             Extension(db.clone()),
             Extension(transcript.clone()),
             Extension(test_options()),
+            Extension(audit.clone()),
         )
         .await
         .expect("try_contribute that should succeed failed");
@@ -246,6 +266,7 @@ mod tests {
             Extension(db.clone()),
             Extension(transcript.clone()),
             Extension(test_options()),
+            Extension(audit.clone()),
         )
         .await;
         assert!(matches!(check_again, Err(TryContributeError::RateLimited)));
@@ -261,9 +282,25 @@ mod tests {
             Extension(db.clone()),
             Extension(transcript.clone()),
             Extension(test_options()),
+            Extension(audit.clone()),
         )
         .await
         .expect("re-fetching the transcript with try_contribute failed");
         assert_eq!(success_response, refetch_transcript);
+
+        let uid = create_test_session_info(100).token.identity.unique_id();
+        let events = db
+            .audit_entries(0, 10)
+            .await
+            .unwrap()
+            .into_iter()
+            .map(|entry| entry.record.event)
+            .collect::<Vec<_>>();
+        assert_eq!(events, vec![
+            AuditEvent::LobbyEntry { uid: uid.clone() },
+            AuditEvent::SlotGranted { uid: uid.clone() },
+            AuditEvent::LobbyEntry { uid: uid.clone() },
+            AuditEvent::SlotGranted { uid },
+        ]);
     }
 }
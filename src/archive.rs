@@ -0,0 +1,619 @@
+//! Append-only archive of accepted contributions.
+//!
+//! Every accepted [`BatchContribution`] is stored as
+//! `<index>-<transcript hash>.json` in the archive directory, where `index` is
+//! its position in the transcript's `participant_ids` and the hash is that of
+//! the transcript after adding it. Together with the ceremony sizes this is
+//! enough to rebuild the transcript at any earlier index by replaying the
+//! contributions from genesis.
+//!
+//! Genesis is the empty transcript, unless powers from an earlier ceremony
+//! were imported. The imported transcript is then kept as `genesis.json` in
+//! the archive directory.
+
+use crate::{
+    io::{sync_dir, sync_parent_dir, write_file_durably, CeremonySizes},
+    Engine,
+};
+use clap::Parser;
+use ethers_core::utils::keccak256;
+use kzg_ceremony_crypto::{
+    signature::identity::Identity, BatchContribution, BatchTranscript, CeremoniesError,
+    CeremonyParameters, SignaturePolicy,
+};
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::BTreeMap,
+    fs::{self, File, OpenOptions},
+    io::{BufReader, BufWriter, ErrorKind, Write},
+    path::PathBuf,
+    time::{SystemTime, UNIX_EPOCH},
+};
+use thiserror::Error;
+use tracing::{info, warn};
+
+/// Subdirectory for entries written after the transcript was last persisted,
+/// e.g. because of a crash.
+pub const ORPHANED_DIR: &str = "orphaned";
+
+/// File in the archive directory with the transcript imported as genesis.
+pub const GENESIS_FILE: &str = "genesis.json";
+
+/// Error code recorded for contributions removed by a rollback.
+pub const ROLLED_BACK: &str = "ArchiveError::RolledBack";
+
+#[derive(Clone, Debug, PartialEq, Eq, Parser)]
+#[group(skip)]
+pub struct Options {
+    /// Directory in which every accepted contribution is archived.
+    #[clap(long, env, default_value = "./transcript-archive")]
+    pub transcript_archive_dir: PathBuf,
+}
+
+#[derive(Debug, Error)]
+pub enum ArchiveError {
+    #[error("archive IO error: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("malformed archive entry: {0}")]
+    Serialization(#[from] serde_json::Error),
+    #[error("archive has no entry for contribution #{0}")]
+    MissingEntry(usize),
+    #[error("archive has more than one entry for contribution #{0}")]
+    DuplicateEntry(usize),
+    #[error("archived contribution #{0} is invalid: {1}")]
+    InvalidContribution(usize, CeremoniesError),
+    #[error(
+        "replaying contribution #{index} gives transcript {actual}, but {expected} was archived"
+    )]
+    HashMismatch {
+        index:    usize,
+        expected: String,
+        actual:   String,
+    },
+}
+
+/// A single accepted contribution, as stored in the archive.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ArchiveEntry {
+    /// Position in the transcript's `participant_ids`.
+    pub index:           usize,
+    pub identity:        Identity,
+    /// The contribution as it was submitted, before signatures were pruned.
+    pub contribution:    BatchContribution,
+    /// Hash of the transcript before this contribution.
+    pub previous_hash:   String,
+    /// Hash of the transcript after this contribution.
+    pub transcript_hash: String,
+}
+
+/// Returns the keccak256 hash of the compact JSON serialization of the
+/// transcript, as a `0x`-prefixed hex string.
+///
+/// # Errors
+///
+/// Returns an error if the transcript can not be serialized.
+pub fn transcript_hash(transcript: &BatchTranscript) -> Result<String, serde_json::Error> {
+    let json = serde_json::to_vec(transcript)?;
+    Ok(format!("0x{}", hex::encode(keccak256(json))))
+}
+
+#[derive(Clone, Debug)]
+pub struct ContributionArchive {
+    dir: PathBuf,
+}
+
+impl ContributionArchive {
+    #[must_use]
+    pub const fn new(dir: PathBuf) -> Self {
+        Self { dir }
+    }
+
+    /// Durably stores an entry. Storing an entry that is already archived is
+    /// a no-op, as entries are addressed by their content.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the entry can not be written.
+    pub fn append(&self, entry: &ArchiveEntry) -> Result<(), ArchiveError> {
+        fs::create_dir_all(&self.dir)?;
+        let path = self
+            .dir
+            .join(format!("{:06}-{}.json", entry.index, entry.transcript_hash));
+        let file = match OpenOptions::new().write(true).create_new(true).open(&path) {
+            Ok(file) => file,
+            Err(e) if e.kind() == ErrorKind::AlreadyExists => return Ok(()),
+            Err(e) => return Err(e.into()),
+        };
+        let mut writer = BufWriter::new(file);
+        serde_json::to_writer(&mut writer, entry)?;
+        writer.flush()?;
+        writer.get_ref().sync_all()?;
+        sync_parent_dir(&path)?;
+        Ok(())
+    }
+
+    /// Returns the transcript before the first contribution: the imported
+    /// one, if there is one, or else an empty transcript.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the imported transcript can not be read.
+    pub fn genesis(
+        &self,
+        ceremony_sizes: &CeremonySizes,
+        parameters: &CeremonyParameters,
+    ) -> Result<BatchTranscript, ArchiveError> {
+        let path = self.dir.join(GENESIS_FILE);
+        if !path.exists() {
+            return Ok(ceremony_sizes.empty_transcript(parameters));
+        }
+        Ok(serde_json::from_reader(BufReader::new(File::open(path)?))?)
+    }
+
+    /// Durably stores `transcript` as the genesis of the ceremony.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the transcript can not be written.
+    pub fn set_genesis(&self, transcript: &BatchTranscript) -> Result<(), ArchiveError> {
+        fs::create_dir_all(&self.dir)?;
+        let path = self.dir.join(GENESIS_FILE);
+        let json = serde_json::to_vec(transcript)?;
+        write_file_durably(&path, &path.with_extension("json.next"), &json)?;
+        Ok(())
+    }
+
+    /// Returns the paths of all archived entries, with their index.
+    fn entry_paths(&self) -> Result<Vec<(usize, PathBuf)>, ArchiveError> {
+        if !self.dir.exists() {
+            return Ok(Vec::new());
+        }
+        let mut paths = Vec::new();
+        for dir_entry in fs::read_dir(&self.dir)? {
+            let path = dir_entry?.path();
+            let index = path
+                .extension()
+                .filter(|extension| *extension == "json")
+                .and_then(|_| path.file_stem())
+                .and_then(|stem| stem.to_str())
+                .and_then(|stem| stem.split('-').next())
+                .and_then(|index| index.parse().ok());
+            if let Some(index) = index {
+                paths.push((index, path));
+            }
+        }
+        paths.sort();
+        Ok(paths)
+    }
+
+    /// Returns the transcript hash after each archived contribution, by index.
+    /// Only file names are read, which include the hash.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the archive can not be read, or has more than one
+    /// entry for an index.
+    pub fn hashes(&self) -> Result<BTreeMap<usize, String>, ArchiveError> {
+        let mut hashes = BTreeMap::new();
+        for (index, path) in self.entry_paths()? {
+            let hash = path
+                .file_stem()
+                .and_then(|stem| stem.to_str())
+                .and_then(|stem| stem.split_once('-'))
+                .map(|(_, hash)| hash.to_string())
+                .unwrap_or_default();
+            if hashes.insert(index, hash).is_some() {
+                return Err(ArchiveError::DuplicateEntry(index));
+            }
+        }
+        Ok(hashes)
+    }
+
+    /// Reads the entries for contributions `1..=index`, in order.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if an entry is missing, duplicated or unreadable.
+    pub fn entries(&self, index: usize) -> Result<Vec<ArchiveEntry>, ArchiveError> {
+        let paths = self.entry_paths()?;
+        (1..=index)
+            .map(|i| {
+                let mut matching = paths.iter().filter(|(index, _)| *index == i);
+                let (_, path) = matching.next().ok_or(ArchiveError::MissingEntry(i))?;
+                if matching.next().is_some() {
+                    return Err(ArchiveError::DuplicateEntry(i));
+                }
+                let entry: ArchiveEntry =
+                    serde_json::from_reader(BufReader::new(File::open(path)?))?;
+                Ok(entry)
+            })
+            .collect()
+    }
+
+    /// Moves all entries after `index` into the given subdirectory, returning
+    /// how many were moved.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if an entry can not be moved.
+    pub fn set_aside_after(&self, index: usize, subdir: &str) -> Result<usize, ArchiveError> {
+        let target = self.dir.join(subdir);
+        let mut moved = 0;
+        for (_, path) in self.entry_paths()?.into_iter().filter(|(i, _)| *i > index) {
+            fs::create_dir_all(&target)?;
+            // Entries are only ever created, so a name can only be taken by
+            // identical contents.
+            fs::rename(&path, target.join(path.file_name().unwrap_or_default()))?;
+            moved += 1;
+        }
+        if moved > 0 {
+            sync_dir(&target)?;
+            sync_dir(&self.dir)?;
+        }
+        Ok(moved)
+    }
+
+    /// Rebuilds the transcript as it was after contribution `index`, by
+    /// replaying archived contributions from genesis. Every intermediate
+    /// transcript is checked against the archived hashes.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the archive is incomplete, or a replayed
+    /// contribution is invalid or does not result in the archived transcript.
+    pub fn replay(
+        &self,
+        ceremony_sizes: &CeremonySizes,
+        parameters: &CeremonyParameters,
+        index: usize,
+    ) -> Result<BatchTranscript, ArchiveError> {
+        let mut transcript = self.genesis(ceremony_sizes, parameters)?;
+        let mut hash = transcript_hash(&transcript)?;
+        for entry in self.entries(index)? {
+            if entry.previous_hash != hash {
+                return Err(ArchiveError::HashMismatch {
+                    index:    entry.index - 1,
+                    expected: entry.previous_hash,
+                    actual:   hash,
+                });
+            }
+            // Replay what was accepted, even if the signature policy changed since.
+            transcript
+                .verify_add::<Engine>(
+                    entry.contribution,
+                    entry.identity,
+                    SignaturePolicy::permissive(),
+                )
+                .map_err(|e| ArchiveError::InvalidContribution(entry.index, e))?;
+            hash = transcript_hash(&transcript)?;
+            if entry.transcript_hash != hash {
+                return Err(ArchiveError::HashMismatch {
+                    index:    entry.index,
+                    expected: entry.transcript_hash,
+                    actual:   hash,
+                });
+            }
+            info!(index = entry.index, %hash, "Replayed contribution");
+        }
+        Ok(transcript)
+    }
+}
+
+/// Rewinds the ceremony to contribution `index`.
+///
+/// Rebuilds the transcript from the archive and returns it. Archive entries
+/// after `index` are moved to a `rolled-back-<timestamp>` subdirectory, where
+/// a copy of the `current` transcript is also kept.
+///
+/// # Errors
+///
+/// Returns an error if the transcript can not be rebuilt, or the archive can
+/// not be updated.
+pub fn roll_back(
+    archive: &ContributionArchive,
+    ceremony_sizes: &CeremonySizes,
+    parameters: &CeremonyParameters,
+    current: Option<&[u8]>,
+    index: usize,
+) -> Result<BatchTranscript, ArchiveError> {
+    let transcript = archive.replay(ceremony_sizes, parameters, index)?;
+
+    let timestamp = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs();
+    let subdir = format!("rolled-back-{timestamp}");
+    if let Some(current) = current {
+        let backup = archive.dir.join(&subdir);
+        fs::create_dir_all(&backup)?;
+        fs::write(backup.join("transcript.json"), current)?;
+    }
+    let moved = archive.set_aside_after(index, &subdir)?;
+    warn!(index, moved, subdir, "Rolled back archived contributions");
+    Ok(transcript)
+}
+
+/// Finalizes the ceremony with the random `beacon`, see
+/// [`kzg_ceremony_crypto::beacon`].
+///
+/// Adds the contribution derived from the beacon to `transcript` and archives
+/// it like any other contribution, returning the archive entry.
+///
+/// # Errors
+///
+/// Returns an error if the transcript was already finalized, `beacon` is not
+/// a beacon identity, its contribution can not be added, or the archive can
+/// not be updated.
+pub fn apply_beacon(
+    archive: &ContributionArchive,
+    transcript: &mut BatchTranscript,
+    beacon: Identity,
+) -> Result<ArchiveEntry, ArchiveError> {
+    let index = transcript.num_participants() + 1;
+    if transcript.is_finalized() {
+        return Err(ArchiveError::InvalidContribution(
+            index,
+            CeremoniesError::Finalized,
+        ));
+    }
+    let previous_hash = transcript_hash(transcript)?;
+    let mut contribution = transcript.contribution();
+    contribution
+        .add_beacon::<Engine>(&beacon, &transcript.parameters)
+        .map_err(|e| ArchiveError::InvalidContribution(index, e))?;
+    let mut next = transcript.clone();
+    next.verify_add::<Engine>(
+        contribution.clone(),
+        beacon.clone(),
+        SignaturePolicy::default(),
+    )
+    .map_err(|e| ArchiveError::InvalidContribution(index, e))?;
+
+    let entry = ArchiveEntry {
+        index,
+        identity: beacon,
+        contribution,
+        previous_hash,
+        transcript_hash: transcript_hash(&next)?,
+    };
+    archive.append(&entry)?;
+    *transcript = next;
+    info!(index, hash = %entry.transcript_hash, "Applied random beacon");
+    Ok(entry)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::valid_contribution;
+    use kzg_ceremony_crypto::import::ImportFormat;
+    use tempfile::tempdir;
+
+    fn participant(id: u64) -> Identity {
+        Identity::Github {
+            id,
+            username: format!("user{id}"),
+        }
+    }
+
+    /// Archives `count` contributions the way the sequencer does, returning
+    /// the transcript after each of them.
+    fn archive_contributions(
+        archive: &ContributionArchive,
+        sizes: &CeremonySizes,
+        count: u8,
+    ) -> Vec<BatchTranscript> {
+        let mut transcript = archive
+            .genesis(sizes, &CeremonyParameters::default())
+            .unwrap();
+        let mut transcripts = vec![transcript.clone()];
+        for no in 1..=count {
+            let contribution = valid_contribution(&transcript, no);
+            let previous_hash = transcript_hash(&transcript).unwrap();
+            transcript
+                .verify_add::<Engine>(
+                    contribution.clone(),
+                    participant(no.into()),
+                    SignaturePolicy::default(),
+                )
+                .unwrap();
+            archive
+                .append(&ArchiveEntry {
+                    index: no.into(),
+                    identity: participant(no.into()),
+                    contribution,
+                    previous_hash,
+                    transcript_hash: transcript_hash(&transcript).unwrap(),
+                })
+                .unwrap();
+            transcripts.push(transcript.clone());
+        }
+        transcripts
+    }
+
+    fn sizes() -> CeremonySizes {
+        CeremonySizes::parse_from_cmd("4,2").unwrap()
+    }
+
+    #[test]
+    fn replays_to_any_index() {
+        let dir = tempdir().unwrap();
+        let archive = ContributionArchive::new(dir.path().to_path_buf());
+        let transcripts = archive_contributions(&archive, &sizes(), 3);
+
+        for (index, expected) in transcripts.iter().enumerate() {
+            assert_eq!(
+                &archive
+                    .replay(&sizes(), &CeremonyParameters::default(), index)
+                    .unwrap(),
+                expected
+            );
+        }
+        assert!(matches!(
+            archive.replay(&sizes(), &CeremonyParameters::default(), 4),
+            Err(ArchiveError::MissingEntry(4))
+        ));
+    }
+
+    #[test]
+    fn lists_hashes() {
+        let dir = tempdir().unwrap();
+        let archive = ContributionArchive::new(dir.path().to_path_buf());
+        let transcripts = archive_contributions(&archive, &sizes(), 2);
+
+        let hashes = archive.hashes().unwrap();
+        assert_eq!(hashes.len(), 2);
+        assert_eq!(hashes[&2], transcript_hash(&transcripts[2]).unwrap());
+    }
+
+    #[test]
+    fn append_is_idempotent() {
+        let dir = tempdir().unwrap();
+        let archive = ContributionArchive::new(dir.path().to_path_buf());
+        archive_contributions(&archive, &sizes(), 1);
+        archive_contributions(&archive, &sizes(), 1);
+        assert_eq!(archive.entries(1).unwrap().len(), 1);
+    }
+
+    #[test]
+    fn detects_tampered_entries() {
+        let dir = tempdir().unwrap();
+        let archive = ContributionArchive::new(dir.path().to_path_buf());
+        archive_contributions(&archive, &sizes(), 2);
+
+        let (_, path) = archive.entry_paths().unwrap().remove(0);
+        let mut entry = archive.entries(1).unwrap().remove(0);
+        entry.identity = participant(99);
+        fs::write(&path, serde_json::to_vec(&entry).unwrap()).unwrap();
+
+        assert!(matches!(
+            archive.replay(&sizes(), &CeremonyParameters::default(), 2),
+            Err(ArchiveError::HashMismatch { index: 1, .. })
+        ));
+    }
+
+    #[test]
+    fn rolls_back_to_earlier_index() {
+        let dir = tempdir().unwrap();
+        let archive = ContributionArchive::new(dir.path().join("archive"));
+        let transcripts = archive_contributions(&archive, &sizes(), 3);
+        let transcript = roll_back(
+            &archive,
+            &sizes(),
+            &CeremonyParameters::default(),
+            Some(b"current"),
+            1,
+        )
+        .unwrap();
+        assert_eq!(transcript, transcripts[1]);
+        assert_eq!(archive.entry_paths().unwrap().len(), 1);
+
+        // Contributions after the rollback point are kept, but no longer
+        // part of the archive.
+        let rolled_back = fs::read_dir(dir.path().join("archive"))
+            .unwrap()
+            .map(|entry| entry.unwrap().path())
+            .find(|path| path.is_dir())
+            .unwrap();
+        assert_eq!(fs::read_dir(&rolled_back).unwrap().count(), 3);
+
+        // New contributions can be archived at the rolled back indices.
+        archive_contributions(&archive, &sizes(), 2);
+        assert_eq!(
+            archive
+                .replay(&sizes(), &CeremonyParameters::default(), 2)
+                .unwrap(),
+            transcripts[2]
+        );
+    }
+
+    /// A powers of tau response file with the powers of a transcript after
+    /// one contribution.
+    fn response_file() -> Vec<u8> {
+        let mut donor = BatchTranscript::new(&[(5, 3)]);
+        let contribution = valid_contribution(&donor, 42);
+        donor
+            .verify_add::<Engine>(contribution, participant(42), SignaturePolicy::default())
+            .unwrap();
+        let powers = &donor.transcripts[0].powers;
+        let mut file = vec![0_u8; 64];
+        file.extend(powers.g1.iter().flat_map(|p| p.0));
+        file.extend(powers.g2.iter().flat_map(|p| p.0));
+        file.extend(vec![0_u8; 2 * 3 * 48 + 96 + 6 * 96 + 3 * 192]);
+        file
+    }
+
+    #[test]
+    fn replays_from_imported_genesis() {
+        let dir = tempdir().unwrap();
+        let archive = ContributionArchive::new(dir.path().join("archive"));
+        let genesis = sizes()
+            .import_transcript(
+                &CeremonyParameters::default(),
+                ImportFormat::Response,
+                &response_file(),
+            )
+            .unwrap();
+        archive.set_genesis(&genesis).unwrap();
+        assert_eq!(
+            archive
+                .genesis(&sizes(), &CeremonyParameters::default())
+                .unwrap(),
+            genesis
+        );
+
+        let transcripts = archive_contributions(&archive, &sizes(), 2);
+        assert_eq!(transcripts[0], genesis);
+        assert_eq!(archive.hashes().unwrap().len(), 2);
+        for (index, expected) in transcripts.iter().enumerate() {
+            assert_eq!(
+                &archive
+                    .replay(&sizes(), &CeremonyParameters::default(), index)
+                    .unwrap(),
+                expected
+            );
+        }
+        transcripts[2].verify_witnesses::<Engine>().unwrap();
+    }
+
+    #[test]
+    fn applies_beacon() {
+        let dir = tempdir().unwrap();
+        let archive = ContributionArchive::new(dir.path().to_path_buf());
+        let mut transcript = archive_contributions(&archive, &sizes(), 2).remove(2);
+
+        assert!(matches!(
+            apply_beacon(&archive, &mut transcript, participant(3)),
+            Err(ArchiveError::InvalidContribution(
+                3,
+                CeremoniesError::NotABeacon
+            ))
+        ));
+        assert_eq!(transcript.num_participants(), 2);
+
+        let beacon = Identity::beacon("drand-quicknet-1234", &[0xab; 32]).unwrap();
+        let entry = apply_beacon(&archive, &mut transcript, beacon.clone()).unwrap();
+        assert_eq!(entry.index, 3);
+        assert_eq!(transcript.participant_ids[3], beacon);
+        assert_eq!(transcript.verify_beacons::<Engine>(), Ok(()));
+        assert_eq!(
+            archive
+                .replay(&sizes(), &CeremonyParameters::default(), 3)
+                .unwrap(),
+            transcript
+        );
+
+        // A second beacon is refused.
+        let beacon = Identity::beacon("drand-quicknet-1235", &[0xcd; 32]).unwrap();
+        assert!(matches!(
+            apply_beacon(&archive, &mut transcript, beacon),
+            Err(ArchiveError::InvalidContribution(
+                4,
+                CeremoniesError::Finalized
+            ))
+        ));
+        assert_eq!(transcript.num_participants(), 3);
+        assert_eq!(archive.hashes().unwrap().len(), 3);
+    }
+}
@@ -0,0 +1,322 @@
+//! Tamper-evident log of ceremony events.
+//!
+//! Every login, lobby entry, slot grant, expiry, abort, acceptance and
+//! rejection is appended to an audit log in storage. Each [`AuditEntry`]
+//! commits to the hash of the entry before it and is signed by the sequencer,
+//! so that the log served at `/info/audit_log` can be checked offline with
+//! [`verify_audit_log`] (or the `verify-audit-log` binary). Removing,
+//! reordering or altering an entry breaks the chain.
+//!
+//! Participants are identified by their `uid`, never by their session id.
+
+use crate::{
+    keys::{SharedKeys, SignatureError},
+    storage::{PersistentStorage, StorageError},
+};
+use chrono::Utc;
+use ethers_core::{
+    types::{RecoveryMessage, Signature, H160},
+    utils::keccak256,
+};
+use kzg_ceremony_crypto::ErrorCode;
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+use strum::IntoStaticStr;
+use thiserror::Error;
+use tokio::sync::Mutex;
+use tracing::error;
+
+/// The `previousHash` of the first entry.
+pub const GENESIS_HASH: &str = "0x0000000000000000000000000000000000000000000000000000000000000000";
+
+/// Something that happened to a participant.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "camelCase")]
+pub enum AuditEvent {
+    /// The participant signed in.
+    Login { uid: String },
+    /// The participant asked to contribute and entered the lobby.
+    LobbyEntry { uid: String },
+    /// The participant was given the slot to contribute.
+    SlotGranted { uid: String },
+    /// The participant did not contribute before the compute deadline.
+    Expired { uid: String },
+    /// The participant gave up their slot.
+    Aborted { uid: String },
+    /// The contribution was added to the transcript.
+    #[serde(rename_all = "camelCase")]
+    Accepted {
+        uid:              String,
+        transcript_index: usize,
+        transcript_hash:  String,
+    },
+    /// The contribution was rejected with the given error code.
+    #[serde(rename_all = "camelCase")]
+    Rejected {
+        uid:        String,
+        error_code: String,
+    },
+}
+
+/// The signed part of an [`AuditEntry`].
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AuditRecord {
+    /// Position in the log, starting at zero.
+    pub sequence:      u64,
+    /// Unix timestamp in seconds.
+    pub timestamp:     i64,
+    pub event:         AuditEvent,
+    /// [`AuditEntry::hash`] of the previous entry, or [`GENESIS_HASH`].
+    pub previous_hash: String,
+}
+
+/// An entry of the audit log.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AuditEntry {
+    #[serde(flatten)]
+    pub record:    AuditRecord,
+    /// `0x` prefixed keccak256 hash of the compact JSON serialization of the
+    /// record.
+    pub hash:      String,
+    /// Hex encoded EIP-191 signature of `hash` by the sequencer address.
+    pub signature: String,
+}
+
+#[derive(Debug, Error, IntoStaticStr)]
+pub enum AuditError {
+    #[error("could not serialize entry: {0}")]
+    Serialization(#[from] serde_json::Error),
+    #[error("could not sign entry: {0}")]
+    Signing(#[from] SignatureError),
+    #[error("storage error: {0}")]
+    Storage(#[from] StorageError),
+    #[error("entry {position} has sequence number {sequence}")]
+    SequenceMismatch { position: u64, sequence: u64 },
+    #[error("entry {sequence} does not commit to the previous entry")]
+    BrokenChain { sequence: u64 },
+    #[error("entry {sequence} does not match its hash")]
+    HashMismatch { sequence: u64 },
+    #[error("entry {sequence} is not signed by the sequencer")]
+    InvalidSignature { sequence: u64 },
+}
+
+impl ErrorCode for AuditError {
+    fn to_error_code(&self) -> String {
+        format!("AuditError::{}", <&str>::from(self))
+    }
+}
+
+impl AuditRecord {
+    /// Returns the `0x` prefixed keccak256 hash of the compact JSON
+    /// serialization of the record.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the record can not be serialized.
+    pub fn hash(&self) -> Result<String, serde_json::Error> {
+        let json = serde_json::to_vec(self)?;
+        Ok(format!("0x{}", hex::encode(keccak256(json))))
+    }
+}
+
+impl AuditEntry {
+    /// Checks that the entry matches its hash and was signed by `address`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the hash or signature does not match.
+    pub fn verify(&self, address: H160) -> Result<(), AuditError> {
+        let sequence = self.record.sequence;
+        if self.record.hash()? != self.hash {
+            return Err(AuditError::HashMismatch { sequence });
+        }
+        let signature = hex::decode(&self.signature)
+            .ok()
+            .and_then(|bytes| Signature::try_from(bytes.as_ref()).ok())
+            .ok_or(AuditError::InvalidSignature { sequence })?;
+        signature
+            .verify(
+                RecoveryMessage::Data(self.hash.clone().into_bytes()),
+                address,
+            )
+            .map_err(|_| AuditError::InvalidSignature { sequence })
+    }
+}
+
+/// Checks a complete audit log, starting from the first entry: sequence
+/// numbers are consecutive, every entry commits to the one before it, and all
+/// entries are signed by `address`.
+///
+/// # Errors
+///
+/// Returns an error describing the first entry that fails a check.
+pub fn verify_audit_log(entries: &[AuditEntry], address: H160) -> Result<(), AuditError> {
+    let mut previous_hash = GENESIS_HASH;
+    for (position, entry) in (0_u64..).zip(entries) {
+        let sequence = entry.record.sequence;
+        if sequence != position {
+            return Err(AuditError::SequenceMismatch { position, sequence });
+        }
+        if entry.record.previous_hash != previous_hash {
+            return Err(AuditError::BrokenChain { sequence });
+        }
+        entry.verify(address)?;
+        previous_hash = &entry.hash;
+    }
+    Ok(())
+}
+
+/// Shared handle to the [`AuditLog`].
+pub type SharedAuditLog = Arc<AuditLog>;
+
+/// Appends signed entries to the audit log in storage.
+pub struct AuditLog {
+    storage: PersistentStorage,
+    keys:    SharedKeys,
+    /// Sequence number and previous hash of the next entry. Holding the lock
+    /// while appending keeps the chain linear.
+    head:    Mutex<(u64, String)>,
+}
+
+impl AuditLog {
+    /// Continues the audit log kept in `storage`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the last entry can not be read.
+    pub async fn new(storage: PersistentStorage, keys: SharedKeys) -> Result<Self, AuditError> {
+        let head = storage.last_audit_entry().await?.map_or_else(
+            || (0, GENESIS_HASH.to_string()),
+            |entry| (entry.record.sequence + 1, entry.hash),
+        );
+        Ok(Self {
+            storage,
+            keys,
+            head: Mutex::new(head),
+        })
+    }
+
+    /// Signs the event and appends it to the log.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the entry can not be signed or stored. The log is
+    /// left unchanged in that case.
+    pub async fn append(&self, event: AuditEvent) -> Result<AuditEntry, AuditError> {
+        let mut head = self.head.lock().await;
+        let record = AuditRecord {
+            sequence: head.0,
+            timestamp: Utc::now().timestamp(),
+            event,
+            previous_hash: head.1.clone(),
+        };
+        let hash = record.hash()?;
+        let signature = self.keys.sign(&hash).await?;
+        let entry = AuditEntry {
+            record,
+            hash,
+            signature: signature.0,
+        };
+        self.storage.append_audit_entry(&entry).await?;
+        *head = (entry.record.sequence + 1, entry.hash.clone());
+        Ok(entry)
+    }
+
+    /// Like [`AuditLog::append`], but logs failures instead of returning
+    /// them, so that auditing never gets in the way of the ceremony.
+    pub async fn record(&self, event: AuditEvent) {
+        if let Err(err) = self.append(event.clone()).await {
+            error!(?err, ?event, "could not append to audit log");
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{storage::InMemoryStorage, test_util::test_keys};
+
+    async fn test_log() -> (AuditLog, PersistentStorage, H160) {
+        let keys = Arc::new(test_keys());
+        let address = keys.address().0;
+        let storage: PersistentStorage = Arc::new(InMemoryStorage::default());
+        let log = AuditLog::new(storage.clone(), keys).await.unwrap();
+        (log, storage, address)
+    }
+
+    fn login(uid: &str) -> AuditEvent {
+        AuditEvent::Login {
+            uid: uid.to_string(),
+        }
+    }
+
+    #[test]
+    fn serializes_events() {
+        let event = AuditEvent::Rejected {
+            uid:        "git|1234".to_string(),
+            error_code: "CeremonyError::G1PairingFailed".to_string(),
+        };
+        assert_eq!(
+            serde_json::to_string(&event).unwrap(),
+            r#"{"type":"rejected","uid":"git|1234","errorCode":"CeremonyError::G1PairingFailed"}"#
+        );
+    }
+
+    #[tokio::test]
+    async fn chains_and_verifies_entries() {
+        let (log, storage, address) = test_log().await;
+        log.append(login("a")).await.unwrap();
+        log.append(login("b")).await.unwrap();
+
+        // A restarted sequencer continues the same chain.
+        let log = AuditLog::new(storage.clone(), log.keys.clone())
+            .await
+            .unwrap();
+        log.append(login("c")).await.unwrap();
+
+        let entries = storage.audit_entries(0, 10).await.unwrap();
+        assert_eq!(entries.len(), 3);
+        assert_eq!(entries[0].record.previous_hash, GENESIS_HASH);
+        assert_eq!(entries[2].record.previous_hash, entries[1].hash);
+        verify_audit_log(&entries, address).unwrap();
+        assert!(matches!(
+            verify_audit_log(&entries, H160::zero()),
+            Err(AuditError::InvalidSignature { sequence: 0 })
+        ));
+    }
+
+    #[tokio::test]
+    async fn detects_tampering() {
+        let (log, storage, address) = test_log().await;
+        for uid in ["a", "b", "c"] {
+            log.append(login(uid)).await.unwrap();
+        }
+        let entries = storage.audit_entries(0, 10).await.unwrap();
+
+        let mut altered = entries.clone();
+        altered[1].record.event = login("mallory");
+        assert!(matches!(
+            verify_audit_log(&altered, address),
+            Err(AuditError::HashMismatch { sequence: 1 })
+        ));
+
+        let mut removed = entries.clone();
+        removed.remove(1);
+        assert!(matches!(
+            verify_audit_log(&removed, address),
+            Err(AuditError::SequenceMismatch {
+                position: 1,
+                sequence: 2,
+            })
+        ));
+
+        assert!(matches!(
+            verify_audit_log(&entries[1..], address),
+            Err(AuditError::SequenceMismatch {
+                position: 0,
+                sequence: 1,
+            })
+        ));
+    }
+}
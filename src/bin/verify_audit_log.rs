@@ -0,0 +1,48 @@
+//! Checks an audit log exported from `/info/audit_log` without access to the
+//! sequencer.
+//!
+//! Pages of the export are given in order as separate files. Their entries
+//! are concatenated and must form the complete log, starting at sequence
+//! number zero.
+
+use clap::Parser;
+use ethers_core::types::H160;
+use eyre::{eyre, WrapErr};
+use kzg_ceremony_sequencer::audit::{verify_audit_log, AuditEntry};
+use std::{fs::File, io::BufReader, path::PathBuf};
+
+#[derive(Debug, Parser)]
+struct Options {
+    /// Address of the sequencer, as reported by `/info/status`.
+    #[clap(long)]
+    address: String,
+
+    /// Exported pages of the audit log, in order.
+    #[clap(required = true)]
+    files: Vec<PathBuf>,
+}
+
+fn main() -> eyre::Result<()> {
+    let options = Options::parse();
+    let address: H160 = options
+        .address
+        .parse()
+        .map_err(|e| eyre!("invalid sequencer address: {e}"))?;
+
+    let mut entries = Vec::new();
+    for path in &options.files {
+        let file =
+            File::open(path).wrap_err_with(|| format!("could not open {}", path.display()))?;
+        let page: Vec<AuditEntry> = serde_json::from_reader(BufReader::new(file))
+            .wrap_err_with(|| format!("could not parse {}", path.display()))?;
+        entries.extend(page);
+    }
+
+    verify_audit_log(&entries, address)?;
+    println!(
+        "Verified {} audit log entries signed by {:?}",
+        entries.len(),
+        address
+    );
+    Ok(())
+}
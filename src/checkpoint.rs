@@ -0,0 +1,127 @@
+//! Sequencer-signed commitments to the published transcript.
+//!
+//! After every accepted contribution the sequencer signs a [`Checkpoint`] that
+//! binds the contribution's index to the [`transcript_hash`] of the transcript
+//! before and after it. Starting from the empty transcript, checkpoints form a
+//! hash chain, which is served at `/info/checkpoints`.
+//!
+//! Anyone who fetched `/info/current_state` can check its hash against the
+//! latest checkpoint. Two checkpoints for the same index with different hashes
+//! prove that the sequencer equivocated.
+//!
+//! [`transcript_hash`]: crate::archive::transcript_hash
+
+use crate::{
+    archive::ArchiveEntry,
+    keys::{Keys, SignatureError},
+};
+use ethers_core::types::{RecoveryMessage, Signature, H160};
+use serde::{Deserialize, Serialize};
+
+/// Commitment to the transcript after contribution `index`.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Checkpoint {
+    /// Position of the contribution in the transcript's `participant_ids`.
+    pub index:           usize,
+    /// Hash of the transcript after the contribution.
+    pub transcript_hash: String,
+    /// Hash of the transcript before the contribution.
+    pub previous_hash:   String,
+}
+
+/// A [`Checkpoint`] with the sequencer's signature.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SignedCheckpoint {
+    #[serde(flatten)]
+    pub checkpoint: Checkpoint,
+    /// Hex encoded EIP-191 signature of [`Checkpoint::message`] by the
+    /// sequencer address.
+    pub signature:  String,
+}
+
+impl From<&ArchiveEntry> for Checkpoint {
+    fn from(entry: &ArchiveEntry) -> Self {
+        Self {
+            index:           entry.index,
+            transcript_hash: entry.transcript_hash.clone(),
+            previous_hash:   entry.previous_hash.clone(),
+        }
+    }
+}
+
+impl Checkpoint {
+    /// The signed message, which is the compact JSON serialization of the
+    /// checkpoint.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the checkpoint can not be serialized.
+    pub fn message(&self) -> Result<String, serde_json::Error> {
+        serde_json::to_string(self)
+    }
+
+    /// Signs the checkpoint with the sequencer's key.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if signing fails.
+    pub async fn sign(self, keys: &Keys) -> Result<SignedCheckpoint, SignatureError> {
+        let message = self
+            .message()
+            .map_err(|_| SignatureError::SignatureCreation)?;
+        let signature = keys.sign(&message).await?;
+        Ok(SignedCheckpoint {
+            checkpoint: self,
+            signature:  signature.0,
+        })
+    }
+}
+
+impl SignedCheckpoint {
+    /// Checks that the checkpoint was signed by `address`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the signature is malformed or by another address.
+    pub fn verify(&self, address: H160) -> Result<(), SignatureError> {
+        let message = self
+            .checkpoint
+            .message()
+            .map_err(|_| SignatureError::InvalidSignature)?;
+        let bytes = hex::decode(&self.signature).map_err(|_| SignatureError::InvalidToken)?;
+        let signature =
+            Signature::try_from(bytes.as_ref()).map_err(|_| SignatureError::InvalidSignature)?;
+        signature
+            .verify(RecoveryMessage::Data(message.into_bytes()), address)
+            .map_err(|_| SignatureError::InvalidSignature)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::test_keys;
+    use ethers_core::types::Address;
+
+    #[tokio::test]
+    async fn signs_and_verifies_checkpoints() {
+        let keys = test_keys();
+        let checkpoint = Checkpoint {
+            index:           1,
+            transcript_hash: "0x02".to_string(),
+            previous_hash:   "0x01".to_string(),
+        };
+        assert_eq!(
+            checkpoint.message().unwrap(),
+            r#"{"index":1,"transcriptHash":"0x02","previousHash":"0x01"}"#
+        );
+
+        let mut signed = checkpoint.sign(&keys).await.unwrap();
+        signed.verify(keys.address().0).unwrap();
+        assert!(signed.verify(Address::zero()).is_err());
+
+        signed.checkpoint.transcript_hash = "0x03".to_string();
+        assert!(signed.verify(keys.address().0).is_err());
+    }
+}
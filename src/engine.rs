@@ -0,0 +1,131 @@
+use clap::ValueEnum;
+use kzg_ceremony_crypto::{
+    signature::identity::Identity, Arkworks, BatchContribution, BatchTranscript, Both,
+    CancellationToken, CeremoniesError, Engine, Progress, SignaturePolicy, BLST,
+};
+use serde::Serialize;
+use strum::IntoStaticStr;
+use tracing::instrument;
+
+/// Cryptography engine used to verify contributions.
+///
+/// The `Engine` trait only has associated functions, so the choice is
+/// dispatched with a `match` to the monomorphized implementations.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, ValueEnum, Serialize, IntoStaticStr)]
+#[serde(rename_all = "lowercase")]
+#[strum(serialize_all = "lowercase")]
+pub enum EngineChoice {
+    /// Arkworks only.
+    Arkworks,
+    /// BLST only, the fastest.
+    Blst,
+    /// Both Arkworks and BLST, failing if they disagree.
+    #[default]
+    Both,
+}
+
+impl EngineChoice {
+    /// Verifies `contribution` and adds it to `transcript` with this engine.
+    /// Verification stops early once `cancel` is cancelled.
+    ///
+    /// # Errors
+    ///
+    /// See [`BatchTranscript::verify_add_with`].
+    #[instrument(level = "info", skip_all, fields(engine = <&str>::from(self)))]
+    pub fn verify_add(
+        self,
+        transcript: &mut BatchTranscript,
+        contribution: BatchContribution,
+        identity: Identity,
+        policy: SignaturePolicy,
+        cancel: &CancellationToken,
+    ) -> Result<(), CeremoniesError> {
+        match self {
+            Self::Arkworks => {
+                verify_add::<Arkworks>(transcript, contribution, identity, policy, cancel)
+            }
+            Self::Blst => verify_add::<BLST>(transcript, contribution, identity, policy, cancel),
+            Self::Both => verify_add::<Both<Arkworks, BLST>>(
+                transcript,
+                contribution,
+                identity,
+                policy,
+                cancel,
+            ),
+        }
+    }
+}
+
+fn verify_add<E: Engine>(
+    transcript: &mut BatchTranscript,
+    contribution: BatchContribution,
+    identity: Identity,
+    policy: SignaturePolicy,
+    cancel: &CancellationToken,
+) -> Result<(), CeremoniesError> {
+    transcript.verify_add_with::<E>(contribution, identity, policy, &|_: Progress| (), cancel)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::{invalid_contribution, test_transcript, valid_contribution};
+
+    #[test]
+    fn test_verify_add() {
+        for engine in EngineChoice::value_variants() {
+            let mut transcript = test_transcript();
+            let contribution = invalid_contribution(&transcript, 1);
+            assert!(engine
+                .verify_add(
+                    &mut transcript,
+                    contribution,
+                    Identity::None,
+                    SignaturePolicy::default(),
+                    &CancellationToken::new(),
+                )
+                .is_err());
+            let contribution = valid_contribution(&transcript, 1);
+            engine
+                .verify_add(
+                    &mut transcript,
+                    contribution,
+                    Identity::None,
+                    SignaturePolicy::default(),
+                    &CancellationToken::new(),
+                )
+                .unwrap();
+            assert_eq!(transcript.num_participants(), 1);
+        }
+    }
+
+    #[test]
+    fn test_verify_add_cancelled() {
+        let cancel = CancellationToken::new();
+        cancel.cancel();
+        for engine in EngineChoice::value_variants() {
+            let mut transcript = test_transcript();
+            let contribution = valid_contribution(&transcript, 1);
+            assert_eq!(
+                engine.verify_add(
+                    &mut transcript,
+                    contribution,
+                    Identity::None,
+                    SignaturePolicy::default(),
+                    &cancel,
+                ),
+                Err(CeremoniesError::Cancelled)
+            );
+            assert_eq!(transcript.num_participants(), 0);
+        }
+    }
+
+    #[test]
+    fn test_names() {
+        assert_eq!(<&str>::from(EngineChoice::Blst), "blst");
+        assert_eq!(
+            serde_json::to_value(EngineChoice::Both).unwrap(),
+            serde_json::json!("both")
+        );
+    }
+}
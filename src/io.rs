@@ -1,8 +1,17 @@
-use crate::SharedTranscript;
+use crate::{
+    archive::ContributionArchive,
+    transcript_store::{save_transcript, TranscriptStore},
+    Engine, SharedTranscript,
+};
 use eyre::eyre;
-use kzg_ceremony_crypto::BatchTranscript;
-use serde::{de::DeserializeOwned, Serialize};
-use std::{path::PathBuf, sync::Arc};
+use http::StatusCode;
+use kzg_ceremony_crypto::{import::ImportFormat, BatchTranscript, CeremonyParameters};
+use serde::de::DeserializeOwned;
+use std::{
+    io::Write,
+    path::{Path, PathBuf},
+    sync::Arc,
+};
 use tokio::sync::RwLock;
 use tracing::{info, warn};
 
@@ -43,6 +52,36 @@ impl CeremonySizes {
         })
     }
 
+    /// Creates a transcript of this shape without any contributions.
+    #[must_use]
+    pub fn empty_transcript(&self, parameters: &CeremonyParameters) -> BatchTranscript {
+        BatchTranscript::with_parameters(&self.sizes, parameters.clone())
+    }
+
+    /// Creates a transcript of this shape that starts from the powers in
+    /// `bytes`, an output of an earlier ceremony in `format`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if `bytes` can not be read in `format`, has too few
+    /// powers for a ceremony, or the powers are not valid.
+    pub fn import_transcript(
+        &self,
+        parameters: &CeremonyParameters,
+        format: ImportFormat,
+        bytes: &[u8],
+    ) -> eyre::Result<BatchTranscript> {
+        let num_g1 = self.sizes.iter().map(|(g1, _)| *g1).max().unwrap_or(0);
+        let num_g2 = self.sizes.iter().map(|(_, g2)| *g2).max().unwrap_or(0);
+        let powers = format.read_powers(bytes, num_g1, num_g2)?;
+        Ok(BatchTranscript::import::<Engine>(
+            &self.sizes,
+            parameters.clone(),
+            &powers,
+            format.provenance(bytes),
+        )?)
+    }
+
     /// Validates a batch transcript against this shape description
     ///
     /// # Errors:
@@ -89,28 +128,48 @@ pub enum TranscriptIoError {
     SerializationError(serde_json::Error),
     #[error("Task error {0}")]
     TaskError(tokio::task::JoinError),
+    #[error("Object store request failed {0}")]
+    RequestError(reqwest::Error),
+    #[error("Object store responded with {status}: {body}")]
+    ObjectStoreError { status: StatusCode, body: String },
 }
 
-/// Reads a transcript file from disk, or creates it, if it doesn't exist.
+/// Reads the transcript from the store, or creates it, if it doesn't exist.
 ///
 /// # Errors
 ///
-/// - when the transcript exists, but does not conform to the required shape.
+/// - when the transcript exists, but does not conform to the required shape or
+///   was created with other ceremony parameters.
+/// - when the store can not be accessed.
 pub async fn read_or_create_transcript(
-    path: PathBuf,
-    work_path: PathBuf,
+    store: &dyn TranscriptStore,
+    archive: &ContributionArchive,
     ceremony_sizes: &CeremonySizes,
+    parameters: &CeremonyParameters,
+    publish_interval: usize,
 ) -> eyre::Result<SharedTranscript> {
-    if path.exists() {
-        info!(?path, "Opening transcript file");
-        let transcript = read_json_file::<BatchTranscript>(path).await?;
+    if let Some(json) = store.load().await? {
+        info!(?store, "Opening transcript");
+        let transcript = tokio::task::spawn_blocking(move || {
+            serde_json::from_slice::<BatchTranscript>(&json)
+                .map_err(TranscriptIoError::SerializationError)
+        })
+        .await
+        .map_err(TranscriptIoError::TaskError)??;
         ceremony_sizes.validate_batch_transcript(&transcript)?;
+        if transcript.parameters != *parameters {
+            return Err(eyre!(
+                "Transcript was created with ceremony parameters {:?}, but {parameters:?} are \
+                 configured.",
+                transcript.parameters
+            ));
+        }
         Ok(Arc::new(RwLock::new(transcript)))
     } else {
-        warn!(?path, "No transcript found, creating new transcript file");
-        let transcript = BatchTranscript::new(&ceremony_sizes.sizes);
+        warn!(?store, "No transcript found, creating new transcript");
+        let transcript = archive.genesis(ceremony_sizes, parameters)?;
         let shared_transcript = Arc::new(RwLock::new(transcript));
-        write_json_file(path, work_path, shared_transcript.clone()).await?;
+        save_transcript(store, shared_transcript.clone(), publish_interval).await?;
         Ok(shared_transcript)
     }
 }
@@ -131,25 +190,51 @@ pub async fn read_json_file<T: DeserializeOwned + Send + 'static>(
     handle.await.map_err(TranscriptIoError::TaskError)?
 }
 
-/// Asynchroniously writes a JSON file to disk using a tempfile.
+/// Writes a file to disk using a tempfile.
+///
+/// The tempfile is synced to disk before it is renamed over the target, and
+/// the rename is synced afterwards, so that after a crash the target holds
+/// either the old or the new contents in full.
 ///
 /// # Errors
 /// If either file cannot be written.
-pub async fn write_json_file<T: Serialize + Send + Sync + 'static>(
-    target_path: PathBuf,
-    work_path: PathBuf,
-    data: Arc<RwLock<T>>,
-) -> Result<(), TranscriptIoError> {
-    let handle = tokio::task::spawn_blocking(move || {
-        let f = std::fs::OpenOptions::new()
-            .write(true)
-            .create(true)
-            .open(&work_path)
-            .map_err(TranscriptIoError::IoError)?;
-        let guard = data.blocking_read();
-        serde_json::to_writer_pretty(&f, &*guard).map_err(TranscriptIoError::SerializationError)?;
-        std::fs::rename(&work_path, &target_path).map_err(TranscriptIoError::IoError)?;
-        Ok(())
-    });
-    handle.await.map_err(TranscriptIoError::TaskError)?
+pub fn write_file_durably(
+    target_path: &Path,
+    work_path: &Path,
+    contents: &[u8],
+) -> std::io::Result<()> {
+    let mut f = std::fs::OpenOptions::new()
+        .write(true)
+        .create(true)
+        .truncate(true)
+        .open(work_path)?;
+    f.write_all(contents)?;
+    f.sync_all()?;
+    std::fs::rename(work_path, target_path)?;
+    sync_parent_dir(target_path)
+}
+
+/// Syncs the directory containing `path`, making a preceding create or rename
+/// of `path` durable.
+///
+/// # Errors
+/// If the directory can not be opened or synced.
+pub fn sync_parent_dir(path: &Path) -> std::io::Result<()> {
+    match path.parent() {
+        Some(parent) if !parent.as_os_str().is_empty() => sync_dir(parent),
+        _ => sync_dir(Path::new(".")),
+    }
+}
+
+/// Syncs a directory, making preceding creates and renames in it durable.
+///
+/// # Errors
+/// If the directory can not be opened or synced.
+pub fn sync_dir(dir: &Path) -> std::io::Result<()> {
+    // Directories can not be opened for syncing on Windows.
+    #[cfg(unix)]
+    std::fs::File::open(dir)?.sync_all()?;
+    #[cfg(not(unix))]
+    let _ = dir;
+    Ok(())
 }
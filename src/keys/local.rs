@@ -0,0 +1,89 @@
+use super::{ReceiptSigner, SignatureError};
+use axum::async_trait;
+use ethers_core::{
+    rand::thread_rng,
+    types::{Signature, H160, H256},
+    utils::keccak256,
+};
+use ethers_signers::{LocalWallet, Signer};
+use eyre::{Result, WrapErr};
+use std::{fs, path::Path};
+
+/// Signs with a key held in memory.
+pub struct LocalSigner(LocalWallet);
+
+impl LocalSigner {
+    /// Creates a signer from a hex encoded private key.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the key is not a valid private key.
+    pub fn from_key(key: &str) -> Result<Self> {
+        Ok(Self(key.parse::<LocalWallet>()?))
+    }
+
+    /// Decrypts an encrypted JSON keystore with the passphrase in
+    /// `passphrase_file`. Surrounding whitespace of the passphrase is ignored.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if either file can not be read or the keystore can not
+    /// be decrypted.
+    pub fn from_keystore(keystore: &Path, passphrase_file: &Path) -> Result<Self> {
+        let passphrase = fs::read_to_string(passphrase_file).wrap_err_with(|| {
+            format!(
+                "Could not read keystore passphrase from {}",
+                passphrase_file.display()
+            )
+        })?;
+        let wallet = LocalWallet::decrypt_keystore(keystore, passphrase.trim())
+            .wrap_err_with(|| format!("Could not decrypt keystore {}", keystore.display()))?;
+        Ok(Self(wallet))
+    }
+
+    #[must_use]
+    pub fn random() -> Self {
+        Self(LocalWallet::new(&mut thread_rng()))
+    }
+}
+
+#[async_trait]
+impl ReceiptSigner for LocalSigner {
+    fn address(&self) -> H160 {
+        self.0.address()
+    }
+
+    async fn sign_data(&self, data: &[u8]) -> Result<Signature, SignatureError> {
+        Ok(self.0.sign_hash(H256(keccak256(data))))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ethers_core::types::RecoveryMessage;
+    use tempfile::tempdir;
+
+    #[tokio::test]
+    async fn loads_keystore() {
+        let dir = tempdir().unwrap();
+        let (wallet, name) =
+            LocalWallet::new_keystore(dir.path(), &mut thread_rng(), "passphrase", None).unwrap();
+        let passphrase_file = dir.path().join("passphrase");
+        fs::write(&passphrase_file, "passphrase\n").unwrap();
+
+        let keystore = dir.path().join(name);
+        let signer = LocalSigner::from_keystore(&keystore, &passphrase_file).unwrap();
+        assert_eq!(signer.address(), wallet.address());
+        let signature = signer.sign_data(b"message").await.unwrap();
+        signature
+            .verify(
+                RecoveryMessage::Hash(H256(keccak256(b"message"))),
+                wallet.address(),
+            )
+            .unwrap();
+
+        fs::write(&passphrase_file, "wrong").unwrap();
+        assert!(LocalSigner::from_keystore(&keystore, &passphrase_file).is_err());
+    }
+}
@@ -0,0 +1,255 @@
+//! The sequencer's Ethereum key, used to sign receipts, checkpoints and the
+//! audit log.
+//!
+//! Signing is delegated to a [`ReceiptSigner`]. The key can be given directly
+//! with `--signing-key`, loaded from an encrypted JSON keystore with
+//! `--signing-keystore`, or held by a remote signer that implements the
+//! `Web3Signer` `eth1` API, with `--remote-signer-url`. Without any of these
+//! the sequencer refuses to start, unless `--allow-random-signing-key` is set
+//! for development.
+
+use axum::async_trait;
+use clap::Parser;
+use ethers_core::{
+    types::{transaction::eip712::Eip712, RecoveryMessage, Signature as EthSignature, H160},
+    utils::to_checksum,
+};
+use eyre::{bail, eyre, Result};
+use kzg_ceremony_crypto::ErrorCode;
+use serde::Serialize;
+use std::{fmt, path::PathBuf, sync::Arc};
+use strum::IntoStaticStr;
+use thiserror::Error;
+use tracing::{info, warn};
+use url::Url;
+
+mod local;
+mod remote;
+
+pub use self::{local::LocalSigner, remote::RemoteSigner};
+
+#[derive(Clone, Debug, PartialEq, Eq, Parser)]
+#[group(skip)]
+pub struct Options {
+    /// Ethereum private key to use for signing receipts.
+    #[clap(long, env)]
+    pub signing_key: Option<String>,
+
+    /// Encrypted JSON keystore holding the key to sign receipts with.
+    #[clap(long, env)]
+    pub signing_keystore: Option<PathBuf>,
+
+    /// File containing the passphrase of `--signing-keystore`.
+    #[clap(long, env)]
+    pub signing_keystore_passphrase_file: Option<PathBuf>,
+
+    /// Url of a remote signer implementing the `Web3Signer` `eth1` API, which
+    /// holds the key to sign receipts with.
+    ///
+    /// Example: `http://localhost:9000`
+    #[clap(long, env)]
+    pub remote_signer_url: Option<Url>,
+
+    /// Address of the key to use on `--remote-signer-url`.
+    #[clap(long, env)]
+    pub remote_signer_address: Option<String>,
+
+    /// Sign with a random key if none is configured. Receipts signed with it
+    /// can not be verified after a restart, so this is only for development.
+    #[clap(long, env, default_value = "false")]
+    pub allow_random_signing_key: bool,
+}
+
+#[derive(Serialize)]
+pub struct Signature(pub String);
+
+#[derive(Debug, Error, IntoStaticStr)]
+pub enum SignatureError {
+    #[error("couldn't sign the receipt")]
+    SignatureCreation,
+    #[error("signature is not a valid hex string")]
+    InvalidToken,
+    #[error("couldn't create signature from string")]
+    InvalidSignature,
+}
+
+impl ErrorCode for SignatureError {
+    fn to_error_code(&self) -> String {
+        format!("SignatureError::{}", <&str>::from(self))
+    }
+}
+
+/// Holds, or has access to, the sequencer's key.
+#[async_trait]
+pub trait ReceiptSigner: Send + Sync {
+    /// Address of the key.
+    fn address(&self) -> H160;
+
+    /// Signs the keccak256 hash of `data`.
+    async fn sign_data(&self, data: &[u8]) -> Result<EthSignature, SignatureError>;
+}
+
+pub struct Keys {
+    signer: Box<dyn ReceiptSigner>,
+}
+
+pub type SharedKeys = Arc<Keys>;
+
+#[derive(Debug, Eq, PartialEq)]
+pub struct Address(pub(crate) H160);
+
+impl fmt::Display for Address {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", to_checksum(&self.0, None))
+    }
+}
+
+impl Serialize for Address {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        serializer.serialize_str(&to_checksum(&self.0, None))
+    }
+}
+
+impl Keys {
+    /// Sets up the signer selected by the options.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the options select more than one signer, if the
+    /// key can not be loaded, or if no key is configured and random keys are
+    /// not allowed.
+    pub fn new(options: &Options) -> Result<Self> {
+        let signer: Box<dyn ReceiptSigner> = match (
+            &options.signing_key,
+            &options.signing_keystore,
+            &options.remote_signer_url,
+        ) {
+            (Some(signing_key), None, None) => {
+                let signer = LocalSigner::from_key(signing_key)?;
+                info!(address = ?signer.address(), "Wallet created from the provided signing key");
+                Box::new(signer)
+            }
+            (None, Some(keystore), None) => {
+                let passphrase_file = options
+                    .signing_keystore_passphrase_file
+                    .as_ref()
+                    .ok_or_else(|| {
+                        eyre!("--signing-keystore requires --signing-keystore-passphrase-file")
+                    })?;
+                let signer = LocalSigner::from_keystore(keystore, passphrase_file)?;
+                info!(address = ?signer.address(), keystore = %keystore.display(), "Wallet loaded from keystore");
+                Box::new(signer)
+            }
+            (None, None, Some(url)) => {
+                let address = options
+                    .remote_signer_address
+                    .as_ref()
+                    .ok_or_else(|| eyre!("--remote-signer-url requires --remote-signer-address"))?;
+                let signer = RemoteSigner::new(url.clone(), address.parse()?);
+                info!(address = ?signer.address(), %url, "Signing with remote signer");
+                Box::new(signer)
+            }
+            (None, None, None) => {
+                if !options.allow_random_signing_key {
+                    bail!(
+                        "No signing key configured. Use --signing-key, --signing-keystore or \
+                         --remote-signer-url, or --allow-random-signing-key for development."
+                    );
+                }
+                let signer = LocalSigner::random();
+                warn!(address = ?signer.address(), "Random wallet created. Make sure to provide a signing key in prod!");
+                Box::new(signer)
+            }
+            _ => bail!(
+                "Only one of --signing-key, --signing-keystore and --remote-signer-url can be used"
+            ),
+        };
+        Ok(Self { signer })
+    }
+
+    /// Signs `message` as an EIP-191 personal message.
+    pub async fn sign(&self, message: &str) -> Result<Signature, SignatureError> {
+        let mut data = format!("\x19Ethereum Signed Message:\n{}", message.len()).into_bytes();
+        data.extend_from_slice(message.as_bytes());
+        let signature = self.signer.sign_data(&data).await?;
+        Ok(Signature(hex::encode::<Vec<u8>>(signature.into())))
+    }
+
+    /// Signs the EIP-712 hash of `data`.
+    pub async fn sign_typed_data<T: Eip712 + Send + Sync>(
+        &self,
+        data: &T,
+    ) -> Result<Signature, SignatureError> {
+        let domain_separator = data
+            .domain_separator()
+            .map_err(|_| SignatureError::SignatureCreation)?;
+        let struct_hash = data
+            .struct_hash()
+            .map_err(|_| SignatureError::SignatureCreation)?;
+        let data = [&[0x19_u8, 0x01][..], &domain_separator, &struct_hash].concat();
+        let signature = self.signer.sign_data(&data).await?;
+        Ok(Signature(hex::encode::<Vec<u8>>(signature.into())))
+    }
+
+    #[allow(unused)]
+    pub fn verify(&self, message: &str, signature: &Signature) -> Result<(), SignatureError> {
+        let h = hex::decode(&signature.0).map_err(|_| SignatureError::InvalidToken)?;
+        let signature =
+            EthSignature::try_from(h.as_ref()).map_err(|_| SignatureError::InvalidSignature)?;
+        signature
+            .verify(
+                RecoveryMessage::Data(message.as_bytes().to_owned()),
+                self.signer.address(),
+            )
+            .map_err(|_| SignatureError::InvalidToken)
+    }
+
+    pub fn address(&self) -> Address {
+        Address(self.signer.address())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::test_keys;
+    use serde::{Deserialize, Serialize};
+
+    #[tokio::test]
+    async fn sign_and_verify() {
+        #[derive(Debug, Serialize, Deserialize, PartialEq, Eq)]
+        pub struct Token {
+            foo: String,
+            exp: u64,
+        }
+
+        let t = Token {
+            foo: String::from("hello world"),
+            exp: 200_000_000_000,
+        };
+
+        let keys = test_keys();
+
+        let message = serde_json::to_string(&t).unwrap();
+        let signature = keys.sign(&message).await.unwrap();
+
+        let result = keys.verify(&message, &signature);
+        println!("result {result:?}");
+    }
+
+    #[test]
+    fn refuses_random_key_by_default() {
+        assert!(Keys::new(&Options::parse_from(Vec::<&str>::new())).is_err());
+        assert!(Keys::new(&Options::parse_from([
+            "",
+            "--signing-key",
+            "0x0101010101010101010101010101010101010101010101010101010101010101",
+            "--remote-signer-url",
+            "http://localhost:9000",
+        ]))
+        .is_err());
+    }
+}
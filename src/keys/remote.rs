@@ -0,0 +1,147 @@
+use super::{ReceiptSigner, SignatureError};
+use axum::async_trait;
+use ethers_core::{
+    types::{RecoveryMessage, Signature, H160, H256},
+    utils::{keccak256, to_checksum},
+};
+use serde::Serialize;
+use tracing::error;
+use url::Url;
+
+/// Signs with a key held by a remote signer implementing the `Web3Signer`
+/// `eth1` API, `POST /api/v1/eth1/sign/{address}`, which signs the keccak256
+/// hash of the posted data.
+pub struct RemoteSigner {
+    client:  reqwest::Client,
+    url:     Url,
+    address: H160,
+}
+
+#[derive(Serialize)]
+struct SignRequest {
+    data: String,
+}
+
+impl RemoteSigner {
+    #[must_use]
+    pub fn new(url: Url, address: H160) -> Self {
+        Self {
+            client: reqwest::Client::new(),
+            url,
+            address,
+        }
+    }
+
+    fn sign_url(&self) -> String {
+        format!(
+            "{}/api/v1/eth1/sign/{}",
+            self.url.as_str().trim_end_matches('/'),
+            to_checksum(&self.address, None)
+        )
+    }
+}
+
+#[async_trait]
+impl ReceiptSigner for RemoteSigner {
+    fn address(&self) -> H160 {
+        self.address
+    }
+
+    async fn sign_data(&self, data: &[u8]) -> Result<Signature, SignatureError> {
+        let response = self
+            .client
+            .post(self.sign_url())
+            .json(&SignRequest {
+                data: format!("0x{}", hex::encode(data)),
+            })
+            .send()
+            .await
+            .and_then(reqwest::Response::error_for_status)
+            .map_err(|error| {
+                error!(?error, "Remote signer request failed");
+                SignatureError::SignatureCreation
+            })?;
+        let body = response.text().await.map_err(|error| {
+            error!(?error, "Could not read remote signer response");
+            SignatureError::SignatureCreation
+        })?;
+        let bytes = hex::decode(body.trim().trim_start_matches("0x")).map_err(|error| {
+            error!(?error, "Remote signer returned invalid hex");
+            SignatureError::SignatureCreation
+        })?;
+        let mut signature = Signature::try_from(bytes.as_slice()).map_err(|error| {
+            error!(?error, "Remote signer returned an invalid signature");
+            SignatureError::SignatureCreation
+        })?;
+        // Some signers return the recovery id as `v`, without the legacy offset.
+        if signature.v < 27 {
+            signature.v += 27;
+        }
+        signature
+            .verify(RecoveryMessage::Hash(H256(keccak256(data))), self.address)
+            .map_err(|error| {
+                error!(?error, "Remote signer signed with a different key");
+                SignatureError::SignatureCreation
+            })?;
+        Ok(signature)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::keys::LocalSigner;
+    use axum::{
+        extract::{Extension, Json, Path},
+        routing::post,
+        Router, Server,
+    };
+    use http::StatusCode;
+    use serde::Deserialize;
+    use std::{net::SocketAddr, sync::Arc};
+
+    #[derive(Deserialize)]
+    struct MockRequest {
+        data: String,
+    }
+
+    // Web3Signer stand-in that only knows the key of `signer`.
+    fn mock_signer(signer: LocalSigner) -> SocketAddr {
+        let handler = |Path(address): Path<String>,
+                       Extension(signer): Extension<Arc<LocalSigner>>,
+                       Json(request): Json<MockRequest>| async move {
+            if address != to_checksum(&signer.address(), None) {
+                return (StatusCode::NOT_FOUND, String::new());
+            }
+            let data = hex::decode(request.data.trim_start_matches("0x")).unwrap();
+            let signature = signer.sign_data(&data).await.unwrap();
+            (StatusCode::OK, format!("0x{signature}"))
+        };
+        let app = Router::new()
+            .route("/api/v1/eth1/sign/:address", post(handler))
+            .layer(Extension(Arc::new(signer)));
+        let server = Server::bind(&"127.0.0.1:0".parse().unwrap()).serve(app.into_make_service());
+        let addr = server.local_addr();
+        tokio::spawn(server);
+        addr
+    }
+
+    #[tokio::test]
+    async fn signs_with_remote_key() {
+        let local = LocalSigner::random();
+        let address = local.address();
+        let url: Url = format!("http://{}/", mock_signer(local)).parse().unwrap();
+
+        let signer = RemoteSigner::new(url.clone(), address);
+        let signature = signer.sign_data(b"message").await.unwrap();
+        signature
+            .verify(RecoveryMessage::Hash(H256(keccak256(b"message"))), address)
+            .unwrap();
+
+        let unknown = RemoteSigner::new(url, LocalSigner::random().address());
+        assert!(matches!(
+            unknown.sign_data(b"message").await,
+            Err(SignatureError::SignatureCreation)
+        ));
+    }
+}
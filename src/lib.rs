@@ -7,17 +7,23 @@ use crate::{
     api::v1::{
         auth::{auth_client_link, eth_callback, github_callback},
         contribute::{contribute, contribute_abort},
-        info::{current_state, status},
+        info::{audit_log, checkpoints, current_state, status},
         lobby::try_contribute,
     },
+    archive::{apply_beacon, roll_back, ContributionArchive, ORPHANED_DIR, ROLLED_BACK},
+    audit::AuditLog,
+    engine::EngineChoice,
     io::{read_or_create_transcript, CeremonySizes},
     keys::Keys,
     lobby::{clear_lobby_on_interval, SharedLobbyState},
     oauth::{
         eth_oauth_client, github_oauth_client, EthAuthOptions, GithubAuthOptions, SharedAuthState,
     },
+    receipt::ReceiptFormat,
+    reconcile::{reconcile, reconcile_checkpoints, set_aside_in_progress_file},
     sessions::{SessionId, SessionInfo},
     storage::storage_client,
+    transcript_store::{save_transcript, transcript_store},
     util::parse_url,
 };
 use axum::{
@@ -27,14 +33,21 @@ use axum::{
     routing::{get, post, IntoMakeService},
     Router, Server,
 };
-use clap::Parser;
+use clap::{Parser, Subcommand};
 use cli_batteries::await_shutdown;
-use eyre::Result as EyreResult;
+use eyre::{eyre, Result as EyreResult};
 use http::StatusCode;
 use hyper::server::conn::AddrIncoming;
-use kzg_ceremony_crypto::BatchTranscript;
+use kzg_ceremony_crypto::{
+    import::ImportFormat,
+    parameters::{
+        MAINNET_CHAIN_ID, MAINNET_CIPHER_SUITE, MAINNET_EIP712_NAME, MAINNET_EIP712_VERSION,
+    },
+    signature::identity::Identity,
+    BatchTranscript, CancellationToken, CeremonyParameters, SignaturePolicy,
+};
 use std::{
-    path::PathBuf,
+    path::{Path, PathBuf},
     sync::{atomic::AtomicUsize, Arc},
 };
 use tokio::sync::RwLock;
@@ -43,21 +56,29 @@ use tower_http::{
     limit::RequestBodyLimitLayer,
     trace::{DefaultMakeSpan, DefaultOnResponse, TraceLayer},
 };
-use tracing::{debug, info, Level};
+use tracing::{debug, info, warn, Level};
 use url::Url;
 
 mod api;
+pub mod archive;
+pub mod audit;
+pub mod checkpoint;
+pub mod engine;
 pub mod io;
 mod keys;
 mod lobby;
 mod oauth;
 mod receipt;
+pub mod reconcile;
 mod sessions;
-mod storage;
+pub mod storage;
 #[cfg(test)]
 pub mod test_util;
+pub mod transcript_store;
 mod util;
 
+/// Engine for replaying the contribution archive. Live contributions are
+/// verified with the engine chosen in [`Options::engine`].
 pub type Engine = kzg_ceremony_crypto::DefaultEngine;
 pub type SharedTranscript = Arc<RwLock<BatchTranscript>>;
 pub type SharedCeremonyStatus = Arc<AtomicUsize>;
@@ -85,7 +106,31 @@ pub struct Options {
     #[clap(long, env, default_value = "false")]
     pub multi_contribution: bool,
 
-    /// Storage location for the ceremony transcript json file.
+    /// Reject contributions without a valid BLS signature of the participant
+    /// in every sub-ceremony, instead of dropping invalid signatures from the
+    /// transcript.
+    #[clap(long, env, default_value = "false")]
+    pub require_bls_signatures: bool,
+
+    /// Reject contributions from Ethereum participants without a valid
+    /// EIP-712 signature of the pubkeys, instead of dropping invalid
+    /// signatures from the transcript.
+    #[clap(long, env, default_value = "false")]
+    pub require_ecdsa_signatures: bool,
+
+    /// Format of the receipts issued to participants. `legacy` receipts are
+    /// not bound to the transcript and only kept for old clients.
+    #[clap(long, env, value_enum, default_value = "eip712")]
+    pub receipt_format: ReceiptFormat,
+
+    /// Cryptography engine used to verify contributions. `both` cross-checks
+    /// the Arkworks and BLST implementations; a single engine is faster under
+    /// load.
+    #[clap(long, env, value_enum, default_value = "both")]
+    pub engine: EngineChoice,
+
+    /// Storage location for the ceremony transcript json file, unless it is
+    /// kept in an object store.
     #[clap(long, env, default_value = "./transcript.json")]
     pub transcript_file: PathBuf,
 
@@ -99,17 +144,117 @@ pub struct Options {
     #[clap(long, env, value_parser=CeremonySizes::parse_from_cmd, default_value=DEFAULT_CEREMONY_SIZES)]
     pub ceremony_sizes: CeremonySizes,
 
+    /// Name of the EIP-712 domain participants sign their contribution in.
+    /// Testnet and rehearsal ceremonies should use their own domain, so that
+    /// signatures can not be replayed between ceremonies.
+    #[clap(long, env, default_value = MAINNET_EIP712_NAME)]
+    pub eip712_name: String,
+
+    /// Version of the EIP-712 domain participants sign their contribution in.
+    #[clap(long, env, default_value = MAINNET_EIP712_VERSION)]
+    pub eip712_version: String,
+
+    /// Chain id of the EIP-712 domain participants sign their contribution
+    /// in.
+    #[clap(long, env, default_value_t = MAINNET_CHAIN_ID)]
+    pub eip712_chain_id: u64,
+
+    /// Ciphersuite of the BLS signatures participants sign their identity
+    /// with.
+    #[clap(long, env, default_value = MAINNET_CIPHER_SUITE)]
+    pub bls_cipher_suite: String,
+
     #[clap(flatten)]
     pub lobby: lobby::Options,
 
     #[clap(flatten)]
     pub storage: storage::Options,
+
+    #[clap(flatten)]
+    pub archive: archive::Options,
+
+    #[clap(flatten)]
+    pub transcript_store: transcript_store::Options,
+
+    #[clap(subcommand)]
+    pub command: Option<Command>,
+}
+
+impl Options {
+    /// Signatures contributions must carry.
+    #[must_use]
+    pub const fn signature_policy(&self) -> SignaturePolicy {
+        SignaturePolicy {
+            require_bls:   self.require_bls_signatures,
+            require_ecdsa: self.require_ecdsa_signatures,
+        }
+    }
+
+    /// Parameters of the ceremony, recorded in the transcript.
+    #[must_use]
+    pub fn ceremony_parameters(&self) -> CeremonyParameters {
+        CeremonyParameters {
+            eip712_name:      self.eip712_name.clone(),
+            eip712_version:   self.eip712_version.clone(),
+            chain_id:         self.eip712_chain_id,
+            bls_cipher_suite: self.bls_cipher_suite.clone(),
+        }
+    }
+}
+
+/// Operator commands. Without a command, the sequencer server is started.
+#[derive(Clone, Debug, PartialEq, Eq, Subcommand)]
+pub enum Command {
+    /// Rebuild the transcript file as it was after the given contribution, by
+    /// replaying archived contributions from genesis. All later contributions
+    /// are rolled back. The sequencer must not be running.
+    RollBack {
+        /// Index of the last contribution to keep, `0` to restart from
+        /// genesis.
+        #[clap(long)]
+        index: usize,
+    },
+    /// Finalize the ceremony with a public random beacon, added as the last
+    /// contribution. The sequencer must not be running.
+    ApplyBeacon {
+        /// Name of the beacon and round, such as `drand-quicknet-1234567`.
+        #[clap(long)]
+        source: String,
+        /// Hex encoded output of the beacon.
+        #[clap(long)]
+        value:  String,
+    },
+    /// Start the ceremony from the powers of an earlier ceremony, instead of
+    /// the generators. Only possible before the first contribution. The
+    /// sequencer must not be running.
+    ImportPowers {
+        /// Format of the file, `ptau` for snarkjs or `response` for powers of
+        /// tau response files.
+        #[clap(long)]
+        format: ImportFormat,
+        /// File with the powers to import.
+        #[clap(long)]
+        file:   PathBuf,
+    },
 }
 
 #[allow(clippy::missing_errors_doc)]
 pub async fn async_main(options: Options) -> EyreResult<()> {
     debug!(?options, "Options");
 
+    match &options.command {
+        Some(Command::RollBack { index }) => {
+            return roll_back_transcript(&options, *index).await;
+        }
+        Some(Command::ApplyBeacon { source, value }) => {
+            return apply_beacon_transcript(&options, source, value).await;
+        }
+        Some(Command::ImportPowers { format, file }) => {
+            return import_powers_transcript(&options, *format, file).await;
+        }
+        None => {}
+    }
+
     let addr = options.server.clone();
     let server = start_server(options).await?;
     info!("Listening on http://{}{}", server.local_addr(), addr.path());
@@ -117,21 +262,159 @@ pub async fn async_main(options: Options) -> EyreResult<()> {
     Ok(())
 }
 
+/// Rewinds the stored transcript and storage to the given contribution.
+///
+/// # Errors
+///
+/// Returns an error if the transcript can not be rebuilt from the archive, or
+/// if the transcript store or storage can not be updated.
+pub async fn roll_back_transcript(options: &Options, index: usize) -> EyreResult<()> {
+    let archive = ContributionArchive::new(options.archive.transcript_archive_dir.clone());
+    let store = transcript_store(options)?;
+    let current = store.load().await?;
+    let transcript = roll_back(
+        &archive,
+        &options.ceremony_sizes,
+        &options.ceremony_parameters(),
+        current.as_deref(),
+        index,
+    )?;
+    save_transcript(
+        &*store,
+        Arc::new(RwLock::new(transcript)),
+        options.transcript_store.transcript_publish_interval,
+    )
+    .await?;
+    let storage = storage_client(&options.storage).await?;
+    let rolled_back = storage.roll_back_contributions(index, ROLLED_BACK).await?;
+    info!(index, rolled_back, "Rolled back transcript");
+    Ok(())
+}
+
+/// Adds the contribution of a random beacon to the stored transcript, and
+/// archives it. Storage picks it up when the sequencer is started again.
+///
+/// # Errors
+///
+/// Returns an error if `value` is not hex, the beacon contribution can not be
+/// added, or the archive or transcript store can not be updated.
+pub async fn apply_beacon_transcript(
+    options: &Options,
+    source: &str,
+    value: &str,
+) -> EyreResult<()> {
+    let value = hex::decode(value.strip_prefix("0x").unwrap_or(value))?;
+    let beacon = Identity::beacon(source, &value)?;
+    let archive = ContributionArchive::new(options.archive.transcript_archive_dir.clone());
+    let store = transcript_store(options)?;
+    let transcript = read_or_create_transcript(
+        &*store,
+        &archive,
+        &options.ceremony_sizes,
+        &options.ceremony_parameters(),
+        options.transcript_store.transcript_publish_interval,
+    )
+    .await?;
+    let entry = apply_beacon(&archive, &mut *transcript.write().await, beacon)?;
+    save_transcript(
+        &*store,
+        transcript,
+        options.transcript_store.transcript_publish_interval,
+    )
+    .await?;
+    info!(index = entry.index, identity = %entry.identity, "Finalized transcript with random beacon");
+    Ok(())
+}
+
+/// Replaces the stored transcript by one that starts from the powers in
+/// `file`, and archives it as the genesis of the ceremony.
+///
+/// # Errors
+///
+/// Returns an error if there are contributions already, if `file` can not be
+/// read or imported, or if the archive or transcript store can not be
+/// updated.
+pub async fn import_powers_transcript(
+    options: &Options,
+    format: ImportFormat,
+    file: &Path,
+) -> EyreResult<()> {
+    let archive = ContributionArchive::new(options.archive.transcript_archive_dir.clone());
+    let store = transcript_store(options)?;
+    let parameters = options.ceremony_parameters();
+    let transcript = read_or_create_transcript(
+        &*store,
+        &archive,
+        &options.ceremony_sizes,
+        &parameters,
+        options.transcript_store.transcript_publish_interval,
+    )
+    .await?;
+    if transcript.read().await.num_participants() > 0 {
+        return Err(eyre!(
+            "Powers can only be imported before the first contribution"
+        ));
+    }
+    let bytes = tokio::fs::read(file).await?;
+    let genesis = options
+        .ceremony_sizes
+        .import_transcript(&parameters, format, &bytes)?;
+    archive.set_genesis(&genesis)?;
+    let provenance = genesis.participant_ids[0].clone();
+    *transcript.write().await = genesis;
+    save_transcript(
+        &*store,
+        transcript,
+        options.transcript_store.transcript_publish_interval,
+    )
+    .await?;
+    info!(%provenance, "Imported powers as the start of the ceremony");
+    Ok(())
+}
+
 #[allow(clippy::missing_errors_doc)]
 pub async fn start_server(
     options: Options,
 ) -> EyreResult<Server<AddrIncoming, IntoMakeService<Router>>> {
-    info!(size=?options.ceremony_sizes, "Starting sequencer for KZG ceremony.");
+    let parameters = options.ceremony_parameters();
+    info!(size=?options.ceremony_sizes, ?parameters, engine = ?options.engine, "Starting sequencer for KZG ceremony.");
 
     let keys = Arc::new(Keys::new(&options.keys)?);
 
+    set_aside_in_progress_file(&options.transcript_in_progress_file)?;
+    let store = transcript_store(&options)?;
+    let archive = ContributionArchive::new(options.archive.transcript_archive_dir.clone());
     let transcript = read_or_create_transcript(
-        options.transcript_file.clone(),
-        options.transcript_in_progress_file.clone(),
+        &*store,
+        &archive,
         &options.ceremony_sizes,
+        &parameters,
+        options.transcript_store.transcript_publish_interval,
     )
     .await?;
 
+    let orphaned =
+        archive.set_aside_after(transcript.read().await.num_participants(), ORPHANED_DIR)?;
+    if orphaned > 0 {
+        warn!(
+            orphaned,
+            "Moved archived contributions missing from the transcript aside"
+        );
+    }
+
+    let storage = storage_client(&options.storage).await?;
+    reconcile(&*transcript.read().await, &storage).await?;
+    reconcile_checkpoints(
+        &*transcript.read().await,
+        &options.ceremony_sizes,
+        &parameters,
+        &archive,
+        &storage,
+        &keys,
+    )
+    .await?;
+    let audit = Arc::new(AuditLog::new(storage.clone(), keys.clone()).await?);
+
     let ceremony_status = {
         let lock = transcript.read().await;
         Arc::new(AtomicUsize::new(lock.num_participants()))
@@ -146,6 +429,17 @@ pub async fn start_server(
         options.lobby.clone(),
     ));
 
+    // Abort a verification in progress on shutdown, instead of holding up the
+    // graceful shutdown until it completes.
+    let shutdown = CancellationToken::new();
+    tokio::spawn({
+        let shutdown = shutdown.clone();
+        async move {
+            await_shutdown().await;
+            shutdown.cancel();
+        }
+    });
+
     let app = Router::new()
         .route("/auth/request_link", get(auth_client_link))
         .route("/auth/callback/github", get(github_callback))
@@ -155,6 +449,8 @@ pub async fn start_server(
         .route("/contribute/abort", post(contribute_abort))
         .route("/info/status", get(status))
         .route("/info/current_state", get(current_state))
+        .route("/info/checkpoints", get(checkpoints))
+        .route("/info/audit_log", get(audit_log))
         .layer(CorsLayer::permissive())
         .layer(Extension(lobby_state))
         .layer(Extension(auth_state))
@@ -163,7 +459,11 @@ pub async fn start_server(
         .layer(Extension(eth_oauth_client(&options.ethereum)))
         .layer(Extension(github_oauth_client(&options.github)))
         .layer(Extension(reqwest::Client::new()))
-        .layer(Extension(storage_client(&options.storage).await?))
+        .layer(Extension(storage))
+        .layer(Extension(audit))
+        .layer(Extension(shutdown))
+        .layer(Extension(archive))
+        .layer(Extension(store))
         .layer(Extension(transcript))
         .layer(Extension(options.clone()))
         .layer(DefaultBodyLimit::disable())
@@ -204,7 +504,7 @@ mod tests {
         let entropy = Secret::new([no; 32]);
         let mut contribution = transcript.contribution();
         contribution
-            .add_entropy::<Engine>(&entropy, &Identity::None)
+            .add_entropy::<Engine>(&entropy, &Identity::None, &transcript.parameters)
             .unwrap();
         contribution
     }
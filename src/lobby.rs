@@ -1,4 +1,5 @@
 use crate::{
+    audit::{AuditEvent, SharedAuditLog},
     sessions::{SessionId, SessionInfo},
     storage::PersistentStorage,
 };
@@ -120,6 +121,7 @@ impl SharedLobbyState {
         participant: &SessionId,
         compute_deadline: Duration,
         storage: PersistentStorage,
+        audit: SharedAuditLog,
     ) -> Result<(), ActiveContributorError> {
         let mut state = self.inner.lock().await;
 
@@ -145,6 +147,7 @@ impl SharedLobbyState {
                 participant,
                 compute_deadline,
                 storage,
+                audit,
             ));
 
             return Ok(());
@@ -176,17 +179,19 @@ impl SharedLobbyState {
     pub async fn abort_contribution(
         &self,
         participant: &SessionId,
-    ) -> Result<(), ActiveContributorError> {
+    ) -> Result<SessionInfo, ActiveContributorError> {
         let mut state = self.inner.lock().await;
 
-        if !matches!(&state.active_contributor, ActiveContributor::AwaitingContribution { session: x, .. } if &x.id == participant)
-        {
-            return Err(ActiveContributorError::NotUsersTurn);
-        }
+        let info = match &state.active_contributor {
+            ActiveContributor::AwaitingContribution { session: x, .. } if &x.id == participant => {
+                x.info.clone()
+            }
+            _ => return Err(ActiveContributorError::NotUsersTurn),
+        };
 
         state.active_contributor = ActiveContributor::None;
 
-        Ok(())
+        Ok(info)
     }
 
     pub async fn clear_current_contributor(&self) {
@@ -268,7 +273,12 @@ impl SharedLobbyState {
         Ok(())
     }
 
-    pub async fn enter_lobby(&self, session_id: &SessionId) -> Result<(), ActiveContributorError> {
+    /// Moves the session into the lobby. Returns whether it was not in the
+    /// lobby yet.
+    pub async fn enter_lobby(
+        &self,
+        session_id: &SessionId,
+    ) -> Result<bool, ActiveContributorError> {
         let mut state = self.inner.lock().await;
 
         // If session is not in sessions_out_of_lobby, it was already moved to lobby or
@@ -280,9 +290,10 @@ impl SharedLobbyState {
                 return Err(ActiveContributorError::LobbySizeLimitExceeded);
             }
             lobby.insert(session_id.clone(), session);
+            return Ok(true);
         }
 
-        Ok(())
+        Ok(false)
     }
 
     #[cfg(test)]
@@ -304,17 +315,23 @@ impl SharedLobbyState {
         participant: SessionId,
         compute_deadline: Duration,
         storage: PersistentStorage,
+        audit: SharedAuditLog,
     ) {
         tokio::time::sleep(compute_deadline).await;
 
         let mut state = inner.lock().await;
 
-        if matches!(&state.active_contributor, ActiveContributor::AwaitingContribution{ session: x, .. } if x.id == participant)
+        if let ActiveContributor::AwaitingContribution { session: x, .. } =
+            &state.active_contributor
         {
-            state.active_contributor = ActiveContributor::None;
+            if x.id == participant {
+                let uid = x.info.token.identity.unique_id();
+                state.active_contributor = ActiveContributor::None;
 
-            drop(state);
-            storage.expire_contribution(&participant.0).await.unwrap();
+                drop(state);
+                storage.expire_contribution(&participant).await.unwrap();
+                audit.record(AuditEvent::Expired { uid }).await;
+            }
         }
     }
 
@@ -1,21 +1,152 @@
 use crate::keys::{Keys, Signature, SignatureError};
-use kzg_ceremony_crypto::{signature::identity::Identity, G2};
+use clap::ValueEnum;
+use kzg_ceremony_crypto::{
+    signature::{
+        identity::Identity,
+        receipt::{ContributionReceipt, ReceiptTypedData},
+    },
+    CeremonyParameters, G2,
+};
 use serde::Serialize;
 
+/// How receipts are encoded and signed.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, ValueEnum)]
+pub enum ReceiptFormat {
+    /// EIP-712 typed [`ContributionReceipt`], bound to the transcript.
+    Eip712,
+    /// JSON `{identity, witness}` signed with `personal_sign`, as issued by
+    /// earlier versions of the sequencer.
+    Legacy,
+}
+
 // Receipt for contributor that sequencer has
 // included their contribution
-#[derive(Serialize)]
 pub struct Receipt {
-    pub(crate) identity: Identity,
-    pub witness:         Vec<G2>,
+    pub(crate) identity:  Identity,
+    pub witness:          Vec<G2>,
+    /// Position of the contribution in the transcript's `participant_ids`.
+    pub transcript_index: usize,
+    /// Hash of the transcript after the contribution.
+    pub transcript_hash:  String,
+    /// Unix timestamp in seconds.
+    pub timestamp:        u64,
+}
+
+#[derive(Serialize)]
+struct LegacyReceipt<'a> {
+    identity: &'a Identity,
+    witness:  &'a [G2],
 }
 
 impl Receipt {
-    pub async fn sign(&self, keys: &Keys) -> Result<(String, Signature), SignatureError> {
-        let receipt_message =
-            serde_json::to_string(self).map_err(|_| SignatureError::SignatureCreation)?;
-        keys.sign(&receipt_message)
+    /// Signs the receipt, returning the signed message and the signature.
+    ///
+    /// In [`ReceiptFormat::Eip712`] the message is the JSON serialization of
+    /// the [`ContributionReceipt`] whose typed data hash was signed, under the
+    /// receipt domain of the ceremony with `parameters`.
+    pub async fn sign(
+        &self,
+        keys: &Keys,
+        format: ReceiptFormat,
+        parameters: &CeremonyParameters,
+    ) -> Result<(String, Signature), SignatureError> {
+        match format {
+            ReceiptFormat::Eip712 => {
+                let receipt = ContributionReceipt {
+                    identity:         self.identity.to_string(),
+                    pot_pubkeys:      self.witness.clone(),
+                    transcript_index: self.transcript_index,
+                    transcript_hash:  self.transcript_hash.clone(),
+                    timestamp:        self.timestamp,
+                };
+                let receipt_message = serde_json::to_string(&receipt)
+                    .map_err(|_| SignatureError::SignatureCreation)?;
+                keys.sign_typed_data(&ReceiptTypedData::new(&receipt, parameters))
+                    .await
+                    .map(|sig| (receipt_message, sig))
+            }
+            ReceiptFormat::Legacy => {
+                let receipt_message = serde_json::to_string(&LegacyReceipt {
+                    identity: &self.identity,
+                    witness:  &self.witness,
+                })
+                .map_err(|_| SignatureError::SignatureCreation)?;
+                keys.sign(&receipt_message)
+                    .await
+                    .map(|sig| (receipt_message, sig))
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::test_keys;
+    use ethers_core::types::Signature as EthSignature;
+    use kzg_ceremony_crypto::signature::receipt::verify_legacy_receipt;
+
+    fn receipt() -> Receipt {
+        Receipt {
+            identity:         Identity::Github {
+                id:       1234,
+                username: "test_user".to_string(),
+            },
+            witness:          vec![G2::one()],
+            transcript_index: 3,
+            transcript_hash:  format!("0x{}", "ab".repeat(32)),
+            timestamp:        1_667_000_000,
+        }
+    }
+
+    fn parse_signature(signature: &Signature) -> EthSignature {
+        EthSignature::try_from(hex::decode(&signature.0).unwrap().as_ref()).unwrap()
+    }
+
+    #[tokio::test]
+    async fn signs_typed_receipts() {
+        let keys = test_keys();
+        let parameters = CeremonyParameters {
+            eip712_name: "Ethereum KZG Ceremony Rehearsal".to_string(),
+            ..CeremonyParameters::mainnet()
+        };
+        let (message, signature) = receipt()
+            .sign(&keys, ReceiptFormat::Eip712, &parameters)
+            .await
+            .unwrap();
+
+        let mut signed: ContributionReceipt = serde_json::from_str(&message).unwrap();
+        assert_eq!(signed.identity, "git|1234|test_user");
+        assert_eq!(signed.transcript_index, 3);
+        let signature = parse_signature(&signature);
+        signed
+            .verify(&signature, keys.address().0, &parameters)
+            .unwrap();
+        assert!(signed
+            .verify(&signature, keys.address().0, &CeremonyParameters::mainnet())
+            .is_err());
+
+        signed.transcript_index = 4;
+        assert!(signed
+            .verify(&signature, keys.address().0, &parameters)
+            .is_err());
+    }
+
+    #[tokio::test]
+    async fn signs_legacy_receipts() {
+        let keys = test_keys();
+        let (message, signature) = receipt()
+            .sign(&keys, ReceiptFormat::Legacy, &CeremonyParameters::mainnet())
             .await
-            .map(|sig| (receipt_message, sig))
+            .unwrap();
+
+        assert_eq!(
+            message,
+            format!(
+                r#"{{"identity":"git|1234|test_user","witness":["{}"]}}"#,
+                serde_json::to_value(G2::one()).unwrap().as_str().unwrap()
+            )
+        );
+        verify_legacy_receipt(&message, &parse_signature(&signature), keys.address().0).unwrap();
     }
 }
@@ -0,0 +1,543 @@
+//! Startup consistency checks between the transcript file and storage.
+//!
+//! A contribution is first added to the in-memory transcript, then written to
+//! the transcript store, and only then marked as finished in storage. The
+//! local store writes to `transcript_in_progress_file` and renames it over
+//! `transcript_file`. A crash can therefore leave:
+//!
+//! * a stale in-progress file. Its contents were never acknowledged to anyone,
+//!   so it is moved aside and ignored.
+//! * contributions in the transcript that storage does not know about. These
+//!   are recovered from the transcript.
+//! * attempts that are still open. Nobody can be contributing before the server
+//!   starts, so these are expired.
+//! * contributions without a signed checkpoint. These are signed from the
+//!   hashes recorded in the contribution archive.
+//!
+//! Storage claiming contributions or checkpoints that are not in the
+//! transcript can not be repaired automatically, as the transcript is the
+//! source of truth. In that case the sequencer refuses to start.
+
+use crate::{
+    archive::{transcript_hash, ArchiveError, ContributionArchive},
+    checkpoint::Checkpoint,
+    io::CeremonySizes,
+    keys::{Keys, SignatureError},
+    storage::{AcceptedContribution, PersistentStorage, StorageError},
+};
+use kzg_ceremony_crypto::{BatchTranscript, CeremonyParameters};
+use std::{
+    collections::BTreeSet,
+    path::{Path, PathBuf},
+};
+use thiserror::Error;
+use tracing::{info, warn};
+
+#[derive(Debug, Error)]
+pub enum ReconciliationError {
+    #[error("storage error: {0}")]
+    Storage(#[from] StorageError),
+    #[error("archive error: {0}")]
+    Archive(#[from] ArchiveError),
+    #[error("failed to hash transcript: {0}")]
+    Hashing(#[from] serde_json::Error),
+    #[error("failed to sign checkpoint: {0}")]
+    CheckpointSigning(#[from] SignatureError),
+    #[error("failed to move aside stale transcript file {0}: {1}")]
+    StaleFile(PathBuf, std::io::Error),
+    #[error(
+        "storage records contribution #{transcript_index}, but the transcript only has \
+         {num_participants} participants"
+    )]
+    MissingFromTranscript {
+        transcript_index: usize,
+        num_participants: usize,
+    },
+    #[error("storage records contribution #{0} more than once")]
+    DuplicateRecord(usize),
+    #[error(
+        "contribution #{transcript_index} is by {transcript} according to the transcript, but by \
+         {record} according to storage"
+    )]
+    IdentityMismatch {
+        transcript_index: usize,
+        transcript:       String,
+        record:           String,
+    },
+    #[error(
+        "storage has a checkpoint for contribution #{transcript_index}, but the transcript only \
+         has {num_participants} participants"
+    )]
+    UnknownCheckpoint {
+        transcript_index: usize,
+        num_participants: usize,
+    },
+    #[error(
+        "the checkpoint for contribution #{transcript_index} commits to {checkpoint}, but the \
+         transcript hashes to {transcript}"
+    )]
+    CheckpointMismatch {
+        transcript_index: usize,
+        checkpoint:       String,
+        transcript:       String,
+    },
+}
+
+/// Moves a leftover in-progress transcript file out of the way, so that it
+/// can not be mistaken for, or corrupt, the next transcript write. It is kept
+/// next to the original with a `.stale` suffix for inspection.
+///
+/// # Errors
+///
+/// Returns an error if the file exists but can not be moved.
+pub fn set_aside_in_progress_file(work_path: &Path) -> Result<(), ReconciliationError> {
+    if !work_path.exists() {
+        return Ok(());
+    }
+    let mut stale_path = work_path.as_os_str().to_owned();
+    stale_path.push(".stale");
+    let stale_path = PathBuf::from(stale_path);
+    warn!(
+        ?work_path,
+        ?stale_path,
+        "Found stale in-progress transcript file, moving it aside"
+    );
+    std::fs::rename(work_path, &stale_path)
+        .map_err(|e| ReconciliationError::StaleFile(work_path.to_path_buf(), e))
+}
+
+/// Brings storage in line with the transcript, see the module documentation.
+///
+/// # Errors
+///
+/// Returns an error if storage disagrees with the transcript in a way that
+/// can not be repaired, or if a storage operation fails.
+pub async fn reconcile(
+    transcript: &BatchTranscript,
+    storage: &PersistentStorage,
+) -> Result<(), ReconciliationError> {
+    let num_participants = transcript.num_participants();
+
+    let mut recorded = BTreeSet::new();
+    for record in storage.accepted_contributions().await? {
+        let transcript_index = record.transcript_index.unwrap_or_default();
+        if transcript_index == 0 || transcript_index > num_participants {
+            return Err(ReconciliationError::MissingFromTranscript {
+                transcript_index,
+                num_participants,
+            });
+        }
+        let identity = &transcript.participant_ids[transcript_index];
+        if identity.unique_id() != record.uid {
+            return Err(ReconciliationError::IdentityMismatch {
+                transcript_index,
+                transcript: identity.to_string(),
+                record: record.uid,
+            });
+        }
+        if !recorded.insert(transcript_index) {
+            return Err(ReconciliationError::DuplicateRecord(transcript_index));
+        }
+    }
+
+    let mut recovered = 0_usize;
+    for transcript_index in 1..=num_participants {
+        if recorded.contains(&transcript_index) {
+            continue;
+        }
+        let identity = &transcript.participant_ids[transcript_index];
+        warn!(
+            transcript_index,
+            %identity,
+            "Contribution missing from storage, recovering it from the transcript"
+        );
+        storage
+            .recover_contribution(
+                identity,
+                &AcceptedContribution::from_transcript(transcript, transcript_index),
+            )
+            .await?;
+        recovered += 1;
+    }
+
+    let expired = storage.expire_open_contributions().await?;
+
+    info!(
+        num_participants,
+        recovered, expired, "Storage is consistent with the transcript"
+    );
+    Ok(())
+}
+
+/// Signs checkpoints for contributions that do not have one yet, e.g. because
+/// the sequencer crashed before recording it, or because they were made
+/// before checkpoints were introduced. Hashes are taken from the transcript
+/// and the contribution archive. Contributions for which neither has the
+/// hashes are skipped.
+///
+/// # Errors
+///
+/// Returns an error if a recorded checkpoint contradicts the transcript or the
+/// archive, or if the archive, storage or signing fails.
+pub async fn reconcile_checkpoints(
+    transcript: &BatchTranscript,
+    ceremony_sizes: &CeremonySizes,
+    parameters: &CeremonyParameters,
+    archive: &ContributionArchive,
+    storage: &PersistentStorage,
+    keys: &Keys,
+) -> Result<(), ReconciliationError> {
+    let num_participants = transcript.num_participants();
+    let current_hash = transcript_hash(transcript)?;
+    let checkpoints = storage.checkpoints().await?;
+
+    let mut hashes = archive.hashes()?;
+    hashes.insert(
+        0,
+        transcript_hash(&archive.genesis(ceremony_sizes, parameters)?)?,
+    );
+    hashes.insert(num_participants, current_hash.clone());
+    let mut signed = BTreeSet::new();
+    for checkpoint in checkpoints.iter().map(|signed| &signed.checkpoint) {
+        if checkpoint.index == 0 || checkpoint.index > num_participants {
+            return Err(ReconciliationError::UnknownCheckpoint {
+                transcript_index: checkpoint.index,
+                num_participants,
+            });
+        }
+        for (index, hash) in [
+            (checkpoint.index, &checkpoint.transcript_hash),
+            (checkpoint.index - 1, &checkpoint.previous_hash),
+        ] {
+            match hashes.get(&index) {
+                Some(known) if known != hash => {
+                    return Err(ReconciliationError::CheckpointMismatch {
+                        transcript_index: index,
+                        checkpoint:       hash.clone(),
+                        transcript:       known.clone(),
+                    });
+                }
+                Some(_) => {}
+                None => {
+                    hashes.insert(index, hash.clone());
+                }
+            }
+        }
+        signed.insert(checkpoint.index);
+    }
+
+    let (mut recovered, mut skipped) = (0_usize, 0_usize);
+    for index in 1..=num_participants {
+        if signed.contains(&index) {
+            continue;
+        }
+        if let Some((previous_hash, transcript_hash)) =
+            hashes.get(&(index - 1)).zip(hashes.get(&index))
+        {
+            let checkpoint = Checkpoint {
+                index,
+                transcript_hash: transcript_hash.clone(),
+                previous_hash: previous_hash.clone(),
+            }
+            .sign(keys)
+            .await?;
+            storage.insert_checkpoint(&checkpoint).await?;
+            recovered += 1;
+        } else {
+            skipped += 1;
+        }
+    }
+
+    if skipped > 0 {
+        warn!(
+            skipped,
+            "Could not sign checkpoints for contributions missing from the archive"
+        );
+    }
+    info!(
+        num_participants,
+        recovered, "Checkpoints are consistent with the transcript"
+    );
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        archive::ArchiveEntry,
+        storage::InMemoryStorage,
+        test_util::test_keys,
+        tests::{test_transcript, valid_contribution},
+        Engine, SessionId,
+    };
+    use kzg_ceremony_crypto::{signature::identity::Identity, SignaturePolicy};
+    use std::sync::Arc;
+    use tempfile::tempdir;
+
+    fn participant(id: u64) -> Identity {
+        Identity::Github {
+            id,
+            username: format!("user{id}"),
+        }
+    }
+
+    fn transcript_with(num_participants: u8) -> BatchTranscript {
+        let mut transcript = test_transcript();
+        for no in 1..=num_participants {
+            let contribution = valid_contribution(&transcript, no);
+            transcript
+                .verify_add::<Engine>(
+                    contribution,
+                    participant(no.into()),
+                    SignaturePolicy::default(),
+                )
+                .unwrap();
+        }
+        transcript
+    }
+
+    #[tokio::test]
+    async fn recovers_unrecorded_contributions() {
+        let transcript = transcript_with(2);
+        let storage: PersistentStorage = Arc::new(InMemoryStorage::default());
+        let session_id = SessionId::new();
+        let abandoned = SessionId::new();
+
+        // First contribution was recorded, the second crashed before it was.
+        storage
+            .insert_contributor(&session_id, &participant(1))
+            .await
+            .unwrap();
+        storage
+            .finish_contribution(
+                &session_id,
+                &AcceptedContribution::from_transcript(&transcript, 1),
+            )
+            .await
+            .unwrap();
+        storage
+            .insert_contributor(&SessionId::new(), &participant(2))
+            .await
+            .unwrap();
+        storage
+            .insert_contributor(&abandoned, &participant(3))
+            .await
+            .unwrap();
+
+        reconcile(&transcript, &storage).await.unwrap();
+
+        let records = storage.accepted_contributions().await.unwrap();
+        assert_eq!(records.len(), 2);
+        assert_eq!(records[1].uid, participant(2).unique_id());
+        assert_eq!(
+            records[1].pot_pubkeys,
+            Some(AcceptedContribution::from_transcript(&transcript, 2).pot_pubkeys)
+        );
+        let abandoned = storage
+            .contributions_by_uid(&participant(3).unique_id())
+            .await
+            .unwrap();
+        assert!(abandoned[0].expired_at.is_some());
+
+        // Reconciling again is a no-op.
+        reconcile(&transcript, &storage).await.unwrap();
+        assert_eq!(storage.accepted_contributions().await.unwrap(), records);
+    }
+
+    #[tokio::test]
+    async fn refuses_records_missing_from_transcript() {
+        let storage: PersistentStorage = Arc::new(InMemoryStorage::default());
+        reconcile(&transcript_with(2), &storage).await.unwrap();
+
+        let result = reconcile(&transcript_with(1), &storage).await;
+        assert!(matches!(
+            result,
+            Err(ReconciliationError::MissingFromTranscript {
+                transcript_index: 2,
+                num_participants: 1,
+            })
+        ));
+    }
+
+    #[tokio::test]
+    async fn refuses_mismatched_identities() {
+        let storage: PersistentStorage = Arc::new(InMemoryStorage::default());
+        let transcript = transcript_with(1);
+        storage
+            .recover_contribution(
+                &participant(2),
+                &AcceptedContribution::from_transcript(&transcript, 1),
+            )
+            .await
+            .unwrap();
+
+        let result = reconcile(&transcript, &storage).await;
+        assert!(matches!(
+            result,
+            Err(ReconciliationError::IdentityMismatch {
+                transcript_index: 1,
+                ..
+            })
+        ));
+    }
+
+    #[tokio::test]
+    async fn signs_missing_checkpoints() {
+        let dir = tempdir().unwrap();
+        let archive = ContributionArchive::new(dir.path().to_path_buf());
+        let storage: PersistentStorage = Arc::new(InMemoryStorage::default());
+        let keys = test_keys();
+        let sizes = CeremonySizes::parse_from_cmd("4,2").unwrap();
+        let parameters = CeremonyParameters::default();
+        let transcript = transcript_with(1);
+
+        reconcile_checkpoints(&transcript, &sizes, &parameters, &archive, &storage, &keys)
+            .await
+            .unwrap();
+        let checkpoints = storage.checkpoints().await.unwrap();
+        assert_eq!(checkpoints.len(), 1);
+        checkpoints[0].verify(keys.address().0).unwrap();
+        assert_eq!(checkpoints[0].checkpoint, Checkpoint {
+            index:           1,
+            transcript_hash: transcript_hash(&transcript).unwrap(),
+            previous_hash:   transcript_hash(&test_transcript()).unwrap(),
+        });
+
+        // Reconciling again is a no-op.
+        reconcile_checkpoints(&transcript, &sizes, &parameters, &archive, &storage, &keys)
+            .await
+            .unwrap();
+        assert_eq!(storage.checkpoints().await.unwrap(), checkpoints);
+
+        // Without archived hashes for the first contribution, only the current
+        // transcript is known, which is not enough to sign the second.
+        let transcript = transcript_with(2);
+        let storage: PersistentStorage = Arc::new(InMemoryStorage::default());
+        reconcile_checkpoints(&transcript, &sizes, &parameters, &archive, &storage, &keys)
+            .await
+            .unwrap();
+        assert!(storage.checkpoints().await.unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn refuses_contradicting_checkpoints() {
+        let dir = tempdir().unwrap();
+        let archive = ContributionArchive::new(dir.path().to_path_buf());
+        let storage: PersistentStorage = Arc::new(InMemoryStorage::default());
+        let keys = test_keys();
+        let sizes = CeremonySizes::parse_from_cmd("4,2").unwrap();
+        let parameters = CeremonyParameters::default();
+        let checkpoint = Checkpoint {
+            index:           1,
+            transcript_hash: "0x01".to_string(),
+            previous_hash:   "0x00".to_string(),
+        }
+        .sign(&keys)
+        .await
+        .unwrap();
+        storage.insert_checkpoint(&checkpoint).await.unwrap();
+
+        let result = reconcile_checkpoints(
+            &transcript_with(1),
+            &sizes,
+            &parameters,
+            &archive,
+            &storage,
+            &keys,
+        )
+        .await;
+        assert!(matches!(
+            result,
+            Err(ReconciliationError::CheckpointMismatch {
+                transcript_index: 1,
+                ..
+            })
+        ));
+        let result = reconcile_checkpoints(
+            &test_transcript(),
+            &sizes,
+            &parameters,
+            &archive,
+            &storage,
+            &keys,
+        )
+        .await;
+        assert!(matches!(
+            result,
+            Err(ReconciliationError::UnknownCheckpoint {
+                transcript_index: 1,
+                num_participants: 0,
+            })
+        ));
+    }
+
+    #[tokio::test]
+    async fn refuses_checkpoints_contradicting_the_archive() {
+        let dir = tempdir().unwrap();
+        let archive = ContributionArchive::new(dir.path().to_path_buf());
+        let storage: PersistentStorage = Arc::new(InMemoryStorage::default());
+        let keys = test_keys();
+        let sizes = CeremonySizes::parse_from_cmd("4,2").unwrap();
+        let parameters = CeremonyParameters::default();
+        let mut transcript = test_transcript();
+        for no in 1..=2 {
+            let previous_hash = transcript_hash(&transcript).unwrap();
+            let contribution = valid_contribution(&transcript, no);
+            transcript
+                .verify_add::<Engine>(
+                    contribution.clone(),
+                    participant(no.into()),
+                    SignaturePolicy::default(),
+                )
+                .unwrap();
+            archive
+                .append(&ArchiveEntry {
+                    index: no.into(),
+                    identity: participant(no.into()),
+                    contribution,
+                    previous_hash,
+                    transcript_hash: transcript_hash(&transcript).unwrap(),
+                })
+                .unwrap();
+        }
+
+        // The first contribution is not the head, so only the archive can tell
+        // that its checkpoint is wrong.
+        let checkpoint = Checkpoint {
+            index:           1,
+            transcript_hash: "0x01".to_string(),
+            previous_hash:   transcript_hash(&test_transcript()).unwrap(),
+        }
+        .sign(&keys)
+        .await
+        .unwrap();
+        storage.insert_checkpoint(&checkpoint).await.unwrap();
+
+        let result =
+            reconcile_checkpoints(&transcript, &sizes, &parameters, &archive, &storage, &keys)
+                .await;
+        assert!(matches!(
+            result,
+            Err(ReconciliationError::CheckpointMismatch {
+                transcript_index: 1,
+                ..
+            })
+        ));
+    }
+
+    #[test]
+    fn sets_aside_stale_in_progress_file() {
+        let dir = tempdir().unwrap();
+        let work_path = dir.path().join("transcript.json.next");
+        set_aside_in_progress_file(&work_path).unwrap();
+
+        std::fs::write(&work_path, "{").unwrap();
+        set_aside_in_progress_file(&work_path).unwrap();
+        assert!(!work_path.exists());
+        assert_eq!(
+            std::fs::read_to_string(dir.path().join("transcript.json.next.stale")).unwrap(),
+            "{"
+        );
+    }
+}
@@ -53,16 +53,6 @@ pub struct IdToken {
     pub exp:      u64,
 }
 
-impl IdToken {
-    // The sub field is used as a unique identifier
-    // For example, see: https://developers.google.com/identity/protocols/oauth2/openid-connect#obtainuserinfo
-    // We can use this to identify when a user signs in with the same
-    // login and signup
-    pub fn unique_identifier(&self) -> String {
-        self.identity.unique_id()
-    }
-}
-
 #[derive(Debug, Clone)]
 pub struct SessionInfo {
     pub token:                 IdToken,
@@ -0,0 +1,453 @@
+use crate::{
+    audit::AuditEntry,
+    checkpoint::SignedCheckpoint,
+    sessions::SessionId,
+    storage::{
+        AcceptedContribution, CeremonyStorage, ContributorRecord, StorageError, StorageTransaction,
+    },
+};
+use axum::async_trait;
+use chrono::{DateTime, Utc};
+use kzg_ceremony_crypto::signature::identity::Identity;
+use std::{collections::BTreeMap, sync::Arc};
+use tokio::sync::Mutex;
+
+/// Storage that keeps all records in process memory.
+///
+/// Nothing is persisted across restarts. Intended for tests and for embedding
+/// the sequencer without a database.
+#[derive(Clone, Debug, Default)]
+pub struct InMemoryStorage(Arc<Mutex<State>>);
+
+#[derive(Debug, Default)]
+struct State {
+    records:     Vec<ContributorRecord>,
+    checkpoints: BTreeMap<usize, SignedCheckpoint>,
+    audit_log:   Vec<AuditEntry>,
+}
+
+/// Buffers changes until commit, then applies them under a single lock.
+struct InMemoryTransaction {
+    state:   Arc<Mutex<State>>,
+    pending: Vec<Change>,
+}
+
+enum Change {
+    Insert {
+        session_id: SessionId,
+        identity:   Identity,
+        at:         DateTime<Utc>,
+    },
+    Finish {
+        session_id: SessionId,
+        accepted:   AcceptedContribution,
+        at:         DateTime<Utc>,
+    },
+    Expire {
+        session_id: SessionId,
+        error_code: Option<String>,
+        at:         DateTime<Utc>,
+    },
+    Checkpoint(SignedCheckpoint),
+}
+
+impl Change {
+    /// Fails if the change contradicts what is already recorded.
+    fn check(&self, state: &State) -> Result<(), StorageError> {
+        match self {
+            Self::Checkpoint(checkpoint) => {
+                let index = checkpoint.checkpoint.index;
+                match state.checkpoints.get(&index) {
+                    Some(recorded) if recorded != checkpoint => {
+                        Err(StorageError::ConflictingCheckpoint(index))
+                    }
+                    _ => Ok(()),
+                }
+            }
+            _ => Ok(()),
+        }
+    }
+
+    fn apply(self, state: &mut State) {
+        let records = &mut state.records;
+        match self {
+            Self::Insert {
+                session_id,
+                identity,
+                at,
+            } => records.push(new_record(identity, Some(session_id), at)),
+            Self::Finish {
+                session_id,
+                accepted,
+                at,
+            } => {
+                for record in open_attempts(records, &session_id) {
+                    let (receipt, receipt_signature) = accepted
+                        .receipt
+                        .clone()
+                        .map_or((None, None), |(receipt, signature)| {
+                            (Some(receipt), Some(signature))
+                        });
+                    record.finished_at = Some(at);
+                    record.transcript_index = Some(accepted.transcript_index);
+                    record.pot_pubkeys = Some(accepted.pot_pubkeys.clone());
+                    record.ecdsa_signature_valid = Some(accepted.ecdsa_signature_valid);
+                    record.bls_signatures_valid = Some(accepted.bls_signatures_valid.clone());
+                    record.receipt = receipt;
+                    record.receipt_signature = receipt_signature;
+                }
+            }
+            Self::Expire {
+                session_id,
+                error_code,
+                at,
+            } => {
+                for record in open_attempts(records, &session_id) {
+                    record.expired_at = Some(at);
+                    record.error_code = error_code.clone();
+                }
+            }
+            Self::Checkpoint(checkpoint) => {
+                state
+                    .checkpoints
+                    .entry(checkpoint.checkpoint.index)
+                    .or_insert(checkpoint);
+            }
+        }
+    }
+}
+
+fn new_record(
+    identity: Identity,
+    session_id: Option<SessionId>,
+    started_at: DateTime<Utc>,
+) -> ContributorRecord {
+    ContributorRecord {
+        uid: identity.unique_id(),
+        identity: Some(identity),
+        session_id,
+        started_at,
+        finished_at: None,
+        expired_at: None,
+        transcript_index: None,
+        pot_pubkeys: None,
+        ecdsa_signature_valid: None,
+        bls_signatures_valid: None,
+        receipt: None,
+        receipt_signature: None,
+        error_code: None,
+    }
+}
+
+fn open_attempts<'a>(
+    records: &'a mut [ContributorRecord],
+    session_id: &'a SessionId,
+) -> impl Iterator<Item = &'a mut ContributorRecord> + 'a {
+    records.iter_mut().filter(move |record| {
+        record.session_id.as_ref() == Some(session_id)
+            && record.finished_at.is_none()
+            && record.expired_at.is_none()
+    })
+}
+
+impl InMemoryStorage {
+    async fn apply(&self, change: Change) -> Result<(), StorageError> {
+        let mut state = self.0.lock().await;
+        change.check(&state)?;
+        change.apply(&mut state);
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl CeremonyStorage for InMemoryStorage {
+    async fn begin(&self) -> Result<Box<dyn StorageTransaction>, StorageError> {
+        Ok(Box::new(InMemoryTransaction {
+            state:   self.0.clone(),
+            pending: Vec::new(),
+        }))
+    }
+
+    async fn has_contributed(&self, uid: &str) -> Result<bool, StorageError> {
+        Ok(self
+            .0
+            .lock()
+            .await
+            .records
+            .iter()
+            .any(|record| record.uid == uid))
+    }
+
+    async fn insert_contributor(
+        &self,
+        session_id: &SessionId,
+        identity: &Identity,
+    ) -> Result<(), StorageError> {
+        self.apply(Change::Insert {
+            session_id: session_id.clone(),
+            identity:   identity.clone(),
+            at:         Utc::now(),
+        })
+        .await
+    }
+
+    async fn finish_contribution(
+        &self,
+        session_id: &SessionId,
+        accepted: &AcceptedContribution,
+    ) -> Result<(), StorageError> {
+        self.apply(Change::Finish {
+            session_id: session_id.clone(),
+            accepted:   accepted.clone(),
+            at:         Utc::now(),
+        })
+        .await
+    }
+
+    async fn expire_contribution(&self, session_id: &SessionId) -> Result<(), StorageError> {
+        self.apply(Change::Expire {
+            session_id: session_id.clone(),
+            error_code: None,
+            at:         Utc::now(),
+        })
+        .await
+    }
+
+    async fn reject_contribution(
+        &self,
+        session_id: &SessionId,
+        error_code: &str,
+    ) -> Result<(), StorageError> {
+        self.apply(Change::Expire {
+            session_id: session_id.clone(),
+            error_code: Some(error_code.to_string()),
+            at:         Utc::now(),
+        })
+        .await
+    }
+
+    async fn contributions_by_uid(
+        &self,
+        uid: &str,
+    ) -> Result<Vec<ContributorRecord>, StorageError> {
+        Ok(self
+            .0
+            .lock()
+            .await
+            .records
+            .iter()
+            .filter(|record| record.uid == uid)
+            .cloned()
+            .collect())
+    }
+
+    async fn contribution_by_transcript_index(
+        &self,
+        transcript_index: usize,
+    ) -> Result<Option<ContributorRecord>, StorageError> {
+        Ok(self
+            .0
+            .lock()
+            .await
+            .records
+            .iter()
+            .find(|record| record.transcript_index == Some(transcript_index))
+            .cloned())
+    }
+
+    async fn accepted_contributions(&self) -> Result<Vec<ContributorRecord>, StorageError> {
+        let mut records: Vec<_> = self
+            .0
+            .lock()
+            .await
+            .records
+            .iter()
+            .filter(|record| record.transcript_index.is_some())
+            .cloned()
+            .collect();
+        records.sort_by_key(|record| record.transcript_index);
+        Ok(records)
+    }
+
+    async fn recover_contribution(
+        &self,
+        identity: &Identity,
+        accepted: &AcceptedContribution,
+    ) -> Result<(), StorageError> {
+        let now = Utc::now();
+        let uid = identity.unique_id();
+        let mut state = self.0.lock().await;
+        let records = &mut state.records;
+        let unindexed =
+            |record: &ContributorRecord| record.uid == uid && record.transcript_index.is_none();
+        let index = records
+            .iter()
+            .position(|record| unindexed(record) && record.finished_at.is_some())
+            .or_else(|| records.iter().rposition(unindexed));
+        let index = index.unwrap_or_else(|| {
+            records.push(new_record(identity.clone(), None, now));
+            records.len() - 1
+        });
+        let record = &mut records[index];
+        record.finished_at = Some(record.finished_at.unwrap_or(now));
+        record.expired_at = None;
+        record.error_code = None;
+        record.transcript_index = Some(accepted.transcript_index);
+        record.pot_pubkeys = Some(accepted.pot_pubkeys.clone());
+        record.ecdsa_signature_valid = Some(accepted.ecdsa_signature_valid);
+        record.bls_signatures_valid = Some(accepted.bls_signatures_valid.clone());
+        Ok(())
+    }
+
+    async fn expire_open_contributions(&self) -> Result<u64, StorageError> {
+        let now = Utc::now();
+        let mut count = 0;
+        for record in &mut self.0.lock().await.records {
+            if record.finished_at.is_none() && record.expired_at.is_none() {
+                record.expired_at = Some(now);
+                count += 1;
+            }
+        }
+        Ok(count)
+    }
+
+    async fn insert_checkpoint(&self, checkpoint: &SignedCheckpoint) -> Result<(), StorageError> {
+        self.apply(Change::Checkpoint(checkpoint.clone())).await
+    }
+
+    async fn checkpoints(&self) -> Result<Vec<SignedCheckpoint>, StorageError> {
+        Ok(self.0.lock().await.checkpoints.values().cloned().collect())
+    }
+
+    async fn append_audit_entry(&self, entry: &AuditEntry) -> Result<(), StorageError> {
+        let mut state = self.0.lock().await;
+        let audit_log = &mut state.audit_log;
+        let sequence = entry.record.sequence;
+        if audit_log
+            .last()
+            .map_or(false, |last| last.record.sequence >= sequence)
+        {
+            return Err(StorageError::DuplicateAuditEntry(sequence));
+        }
+        audit_log.push(entry.clone());
+        Ok(())
+    }
+
+    async fn audit_entries(
+        &self,
+        from: u64,
+        limit: usize,
+    ) -> Result<Vec<AuditEntry>, StorageError> {
+        Ok(self
+            .0
+            .lock()
+            .await
+            .audit_log
+            .iter()
+            .filter(|entry| entry.record.sequence >= from)
+            .take(limit)
+            .cloned()
+            .collect())
+    }
+
+    async fn last_audit_entry(&self) -> Result<Option<AuditEntry>, StorageError> {
+        Ok(self.0.lock().await.audit_log.last().cloned())
+    }
+
+    async fn roll_back_contributions(
+        &self,
+        transcript_index: usize,
+        error_code: &str,
+    ) -> Result<u64, StorageError> {
+        let mut state = self.0.lock().await;
+        let mut count = 0;
+        for record in &mut state.records {
+            if record.transcript_index > Some(transcript_index) {
+                record.transcript_index = None;
+                record.error_code = Some(error_code.to_string());
+                count += 1;
+            }
+        }
+        state
+            .checkpoints
+            .retain(|index, _| *index <= transcript_index);
+        Ok(count)
+    }
+}
+
+#[async_trait]
+impl StorageTransaction for InMemoryTransaction {
+    async fn commit(self: Box<Self>) -> Result<(), StorageError> {
+        let Self { state, pending } = *self;
+        let mut state = state.lock().await;
+        for change in &pending {
+            change.check(&state)?;
+        }
+        for change in pending {
+            change.apply(&mut state);
+        }
+        Ok(())
+    }
+
+    async fn rollback(self: Box<Self>) -> Result<(), StorageError> {
+        Ok(())
+    }
+
+    async fn insert_contributor(
+        &mut self,
+        session_id: &SessionId,
+        identity: &Identity,
+    ) -> Result<(), StorageError> {
+        self.pending.push(Change::Insert {
+            session_id: session_id.clone(),
+            identity:   identity.clone(),
+            at:         Utc::now(),
+        });
+        Ok(())
+    }
+
+    async fn finish_contribution(
+        &mut self,
+        session_id: &SessionId,
+        accepted: &AcceptedContribution,
+    ) -> Result<(), StorageError> {
+        self.pending.push(Change::Finish {
+            session_id: session_id.clone(),
+            accepted:   accepted.clone(),
+            at:         Utc::now(),
+        });
+        Ok(())
+    }
+
+    async fn expire_contribution(&mut self, session_id: &SessionId) -> Result<(), StorageError> {
+        self.pending.push(Change::Expire {
+            session_id: session_id.clone(),
+            error_code: None,
+            at:         Utc::now(),
+        });
+        Ok(())
+    }
+
+    async fn reject_contribution(
+        &mut self,
+        session_id: &SessionId,
+        error_code: &str,
+    ) -> Result<(), StorageError> {
+        self.pending.push(Change::Expire {
+            session_id: session_id.clone(),
+            error_code: Some(error_code.to_string()),
+            at:         Utc::now(),
+        });
+        Ok(())
+    }
+
+    async fn insert_checkpoint(
+        &mut self,
+        checkpoint: &SignedCheckpoint,
+    ) -> Result<(), StorageError> {
+        let change = Change::Checkpoint(checkpoint.clone());
+        change.check(&*self.state.lock().await)?;
+        self.pending.push(change);
+        Ok(())
+    }
+}
@@ -0,0 +1,602 @@
+use crate::{
+    audit::{AuditEntry, AuditRecord},
+    checkpoint::{Checkpoint, SignedCheckpoint},
+    sessions::SessionId,
+    storage::{
+        AcceptedContribution, CeremonyStorage, ContributorRecord, Options, StorageError,
+        StorageTransaction,
+    },
+};
+use axum::async_trait;
+use chrono::Utc;
+use eyre::{eyre, WrapErr};
+use kzg_ceremony_crypto::signature::identity::Identity;
+use sqlx::{
+    any::{AnyConnectOptions, AnyKind, AnyPool, AnyPoolOptions, AnyRow},
+    migrate::{Migrate, MigrateDatabase, Migrator},
+    Any, Executor, Row, Transaction,
+};
+use std::str::FromStr;
+use tracing::{error, info, warn};
+
+// Statically link in migration files
+static MIGRATOR: Migrator = sqlx::migrate!("./migrations");
+
+/// Storage backed by a `SQLite` or Postgres database.
+#[derive(Clone, Debug)]
+pub struct SqlStorage(AnyPool);
+
+struct SqlTransaction(Transaction<'static, Any>);
+
+impl SqlStorage {
+    /// Connects to the database and applies migrations if requested.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the database can not be reached, or if its schema
+    /// version does not match this build.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the build contains no migrations.
+    pub async fn connect(options: &Options) -> eyre::Result<Self> {
+        info!(url = %&options.database_url, "Connecting to database");
+
+        // Create database if requested and does not exist
+        if options.database_migrate && !Any::database_exists(options.database_url.as_str()).await? {
+            warn!(url = %&options.database_url, "Database does not exist, creating database");
+            Any::create_database(options.database_url.as_str()).await?;
+        }
+
+        // Create the connection pool
+        let connect_options = AnyConnectOptions::from_str(options.database_url.as_str())?;
+        let pool = if is_in_memory(&options.database_url) {
+            // Every connection would get its own empty database, and closing the
+            // last one drops it. Keep exactly one connection alive.
+            AnyPoolOptions::new()
+                .max_connections(1)
+                .min_connections(1)
+                .idle_timeout(None)
+                .max_lifetime(None)
+        } else {
+            AnyPoolOptions::new().max_connections(options.database_max_connections)
+        }
+        .connect_with(connect_options)
+        .await?;
+        let mut connection = pool.acquire().await?;
+
+        // Log DB version to test connection.
+        let sql = match connection.kind() {
+            #[cfg(feature = "sqlite")]
+            AnyKind::Sqlite => "sqlite_version() || ' ' || sqlite_source_id()",
+
+            #[cfg(feature = "postgres")]
+            AnyKind::Postgres => "version()",
+
+            // Depending on compilation flags there may be more patterns.
+            #[allow(unreachable_patterns)]
+            _ => "'unknown'",
+        };
+        let version = connection
+            .fetch_one(format!("SELECT {sql};").as_str())
+            .await
+            .wrap_err("error getting database version")?
+            .get::<String, _>(0);
+        info!(url = %&options.database_url, kind = ?connection.kind(), ?version, "Connected to database");
+
+        // Run migrations if requested.
+        let latest = MIGRATOR.migrations.last().unwrap().version;
+        if options.database_migrate {
+            info!(url = %&options.database_url, "Running database migrations if necessary");
+            MIGRATOR.run_direct(&mut *connection).await?;
+        }
+
+        // Validate database schema version
+        #[allow(deprecated)] // HACK: No good alternative to `version()`?
+        if let Some((version, dirty)) = connection.version().await? {
+            if dirty {
+                error!(
+                    url = %&options.database_url,
+                    version,
+                    expected = latest,
+                    "Database is in incomplete migration state.",
+                );
+                return Err(eyre!("Database is in incomplete migration state."));
+            } else if version < latest {
+                error!(
+                    url = %&options.database_url,
+                    version,
+                    expected = latest,
+                    "Database is not up to date, try rerunning with --database-migrate",
+                );
+                return Err(eyre!(
+                    "Database is not up to date, try rerunning with --database-migrate"
+                ));
+            } else if version > latest {
+                error!(
+                    url = %&options.database_url,
+                    version,
+                    latest,
+                    "Database version is newer than this version of the software, please update.",
+                );
+                return Err(eyre!(
+                    "Database version is newer than this version of the software, please update."
+                ));
+            }
+            info!(
+                url = %&options.database_url,
+                version,
+                latest,
+                "Database version is up to date.",
+            );
+        } else {
+            error!(url = %&options.database_url, "Could not get database version");
+            return Err(eyre!("Could not get database version."));
+        }
+
+        drop(connection);
+        Ok(Self(pool))
+    }
+}
+
+fn is_in_memory(database_url: &str) -> bool {
+    database_url.starts_with("sqlite:")
+        && (database_url.contains(":memory:") || database_url.contains("mode=memory"))
+}
+
+#[async_trait]
+impl CeremonyStorage for SqlStorage {
+    async fn begin(&self) -> Result<Box<dyn StorageTransaction>, StorageError> {
+        Ok(Box::new(SqlTransaction(self.0.begin().await?)))
+    }
+
+    async fn has_contributed(&self, uid: &str) -> Result<bool, StorageError> {
+        let sql = "SELECT EXISTS(SELECT 1 FROM contributors WHERE uid = ?1)";
+        let result = self
+            .0
+            .fetch_one(sqlx::query(sql).bind(uid))
+            .await
+            .map(|row| row.get(0))?;
+        Ok(result)
+    }
+
+    async fn insert_contributor(
+        &self,
+        session_id: &SessionId,
+        identity: &Identity,
+    ) -> Result<(), StorageError> {
+        query::insert_contributor(&self.0, session_id, identity).await
+    }
+
+    async fn finish_contribution(
+        &self,
+        session_id: &SessionId,
+        accepted: &AcceptedContribution,
+    ) -> Result<(), StorageError> {
+        query::finish_contribution(&self.0, session_id, accepted).await
+    }
+
+    async fn expire_contribution(&self, session_id: &SessionId) -> Result<(), StorageError> {
+        query::expire_contribution(&self.0, session_id).await
+    }
+
+    async fn reject_contribution(
+        &self,
+        session_id: &SessionId,
+        error_code: &str,
+    ) -> Result<(), StorageError> {
+        query::reject_contribution(&self.0, session_id, error_code).await
+    }
+
+    async fn contributions_by_uid(
+        &self,
+        uid: &str,
+    ) -> Result<Vec<ContributorRecord>, StorageError> {
+        let sql = "SELECT * FROM contributors WHERE uid = ?1 ORDER BY id";
+        let rows = self.0.fetch_all(sqlx::query(sql).bind(uid)).await?;
+        rows.iter().map(record_from_row).collect()
+    }
+
+    async fn contribution_by_transcript_index(
+        &self,
+        transcript_index: usize,
+    ) -> Result<Option<ContributorRecord>, StorageError> {
+        let sql = "SELECT * FROM contributors WHERE transcript_index = ?1";
+        #[allow(clippy::cast_possible_wrap)] // Transcript index fits in i64
+        let transcript_index = transcript_index as i64;
+        let row = self
+            .0
+            .fetch_optional(sqlx::query(sql).bind(transcript_index))
+            .await?;
+        row.as_ref().map(record_from_row).transpose()
+    }
+
+    async fn accepted_contributions(&self) -> Result<Vec<ContributorRecord>, StorageError> {
+        let sql = "SELECT * FROM contributors WHERE transcript_index IS NOT NULL ORDER BY \
+                   transcript_index";
+        let rows = self.0.fetch_all(sql).await?;
+        rows.iter().map(record_from_row).collect()
+    }
+
+    async fn recover_contribution(
+        &self,
+        identity: &Identity,
+        accepted: &AcceptedContribution,
+    ) -> Result<(), StorageError> {
+        let pot_pubkeys = serde_json::to_string(&accepted.pot_pubkeys)?;
+        let bls_signatures_valid = serde_json::to_string(&accepted.bls_signatures_valid)?;
+        #[allow(clippy::cast_possible_wrap)] // Transcript index fits in i64
+        let transcript_index = accepted.transcript_index as i64;
+        let mut transaction = self.0.begin().await?;
+
+        let sql = "SELECT id FROM contributors WHERE uid = ?1 AND transcript_index IS NULL ORDER \
+                   BY finished_at IS NULL, CASE WHEN finished_at IS NULL THEN -id ELSE id END \
+                   LIMIT 1";
+        let id = sqlx::query(sql)
+            .bind(identity.unique_id())
+            .fetch_optional(&mut *transaction)
+            .await?
+            .map(|row| row.get::<i64, _>(0));
+
+        if let Some(id) = id {
+            let sql = "UPDATE contributors SET finished_at = COALESCE(finished_at, ?1), \
+                       expired_at = NULL, error_code = NULL, transcript_index = ?2, pot_pubkeys = \
+                       ?3, ecdsa_signature_valid = ?4, bls_signatures_valid = ?5 WHERE id = ?6";
+            sqlx::query(sql)
+                .bind(Utc::now())
+                .bind(transcript_index)
+                .bind(pot_pubkeys)
+                .bind(accepted.ecdsa_signature_valid)
+                .bind(bls_signatures_valid)
+                .bind(id)
+                .execute(&mut *transaction)
+                .await?;
+        } else {
+            let sql = "INSERT INTO contributors (uid, provider, identity, started_at, \
+                       finished_at, transcript_index, pot_pubkeys, ecdsa_signature_valid, \
+                       bls_signatures_valid) VALUES (?1, ?2, ?3, ?4, ?4, ?5, ?6, ?7, ?8)";
+            sqlx::query(sql)
+                .bind(identity.unique_id())
+                .bind(identity.provider_name())
+                .bind(identity.to_string())
+                .bind(Utc::now())
+                .bind(transcript_index)
+                .bind(pot_pubkeys)
+                .bind(accepted.ecdsa_signature_valid)
+                .bind(bls_signatures_valid)
+                .execute(&mut *transaction)
+                .await?;
+        }
+
+        transaction.commit().await?;
+        Ok(())
+    }
+
+    async fn expire_open_contributions(&self) -> Result<u64, StorageError> {
+        let sql = "UPDATE contributors SET expired_at = ?1 WHERE finished_at IS NULL AND \
+                   expired_at IS NULL";
+        let result = self.0.execute(sqlx::query(sql).bind(Utc::now())).await?;
+        Ok(result.rows_affected())
+    }
+
+    async fn insert_checkpoint(&self, checkpoint: &SignedCheckpoint) -> Result<(), StorageError> {
+        query::insert_checkpoint(&self.0, checkpoint).await
+    }
+
+    async fn checkpoints(&self) -> Result<Vec<SignedCheckpoint>, StorageError> {
+        let sql = "SELECT * FROM checkpoints ORDER BY transcript_index";
+        let rows = self.0.fetch_all(sql).await?;
+        rows.iter().map(checkpoint_from_row).collect()
+    }
+
+    async fn append_audit_entry(&self, entry: &AuditEntry) -> Result<(), StorageError> {
+        let sql = "INSERT INTO audit_log (sequence, timestamp, event, previous_hash, hash, \
+                   signature) VALUES (?1, ?2, ?3, ?4, ?5, ?6)";
+        #[allow(clippy::cast_possible_wrap)] // Sequence number fits in i64
+        let sequence = entry.record.sequence as i64;
+        self.0
+            .execute(
+                sqlx::query(sql)
+                    .bind(sequence)
+                    .bind(entry.record.timestamp)
+                    .bind(serde_json::to_string(&entry.record.event)?)
+                    .bind(&entry.record.previous_hash)
+                    .bind(&entry.hash)
+                    .bind(&entry.signature),
+            )
+            .await?;
+        Ok(())
+    }
+
+    async fn audit_entries(
+        &self,
+        from: u64,
+        limit: usize,
+    ) -> Result<Vec<AuditEntry>, StorageError> {
+        let sql = "SELECT * FROM audit_log WHERE sequence >= ?1 ORDER BY sequence LIMIT ?2";
+        #[allow(clippy::cast_possible_wrap)] // Sequence number and limit fit in i64
+        let (from, limit) = (from as i64, limit as i64);
+        let rows = self
+            .0
+            .fetch_all(sqlx::query(sql).bind(from).bind(limit))
+            .await?;
+        rows.iter().map(audit_entry_from_row).collect()
+    }
+
+    async fn last_audit_entry(&self) -> Result<Option<AuditEntry>, StorageError> {
+        let sql = "SELECT * FROM audit_log ORDER BY sequence DESC LIMIT 1";
+        let row = self.0.fetch_optional(sql).await?;
+        row.as_ref().map(audit_entry_from_row).transpose()
+    }
+
+    async fn roll_back_contributions(
+        &self,
+        transcript_index: usize,
+        error_code: &str,
+    ) -> Result<u64, StorageError> {
+        #[allow(clippy::cast_possible_wrap)] // Transcript index fits in i64
+        let transcript_index = transcript_index as i64;
+        let mut transaction = self.0.begin().await?;
+        let sql = "UPDATE contributors SET transcript_index = NULL, error_code = ?1 WHERE \
+                   transcript_index > ?2";
+        let result = sqlx::query(sql)
+            .bind(error_code)
+            .bind(transcript_index)
+            .execute(&mut *transaction)
+            .await?;
+        let sql = "DELETE FROM checkpoints WHERE transcript_index > ?1";
+        sqlx::query(sql)
+            .bind(transcript_index)
+            .execute(&mut *transaction)
+            .await?;
+        transaction.commit().await?;
+        Ok(result.rows_affected())
+    }
+}
+
+#[async_trait]
+impl StorageTransaction for SqlTransaction {
+    async fn commit(self: Box<Self>) -> Result<(), StorageError> {
+        Ok(self.0.commit().await?)
+    }
+
+    async fn rollback(self: Box<Self>) -> Result<(), StorageError> {
+        Ok(self.0.rollback().await?)
+    }
+
+    async fn insert_contributor(
+        &mut self,
+        session_id: &SessionId,
+        identity: &Identity,
+    ) -> Result<(), StorageError> {
+        query::insert_contributor(&mut *self.0, session_id, identity).await
+    }
+
+    async fn finish_contribution(
+        &mut self,
+        session_id: &SessionId,
+        accepted: &AcceptedContribution,
+    ) -> Result<(), StorageError> {
+        query::finish_contribution(&mut *self.0, session_id, accepted).await
+    }
+
+    async fn expire_contribution(&mut self, session_id: &SessionId) -> Result<(), StorageError> {
+        query::expire_contribution(&mut *self.0, session_id).await
+    }
+
+    async fn reject_contribution(
+        &mut self,
+        session_id: &SessionId,
+        error_code: &str,
+    ) -> Result<(), StorageError> {
+        query::reject_contribution(&mut *self.0, session_id, error_code).await
+    }
+
+    async fn insert_checkpoint(
+        &mut self,
+        checkpoint: &SignedCheckpoint,
+    ) -> Result<(), StorageError> {
+        query::insert_checkpoint(&mut *self.0, checkpoint).await
+    }
+}
+
+/// Write queries shared by [`SqlStorage`] and [`SqlTransaction`].
+mod query {
+    use crate::{
+        checkpoint::SignedCheckpoint,
+        sessions::SessionId,
+        storage::{AcceptedContribution, StorageError},
+    };
+    use chrono::Utc;
+    use kzg_ceremony_crypto::signature::identity::Identity;
+    use sqlx::{Any, Executor};
+
+    pub async fn insert_contributor<'e>(
+        executor: impl Executor<'e, Database = Any>,
+        session_id: &SessionId,
+        identity: &Identity,
+    ) -> Result<(), StorageError> {
+        let sql = "INSERT INTO contributors (uid, provider, identity, session_id, started_at) \
+                   VALUES (?1, ?2, ?3, ?4, ?5)";
+        executor
+            .execute(
+                sqlx::query(sql)
+                    .bind(identity.unique_id())
+                    .bind(identity.provider_name())
+                    .bind(identity.to_string())
+                    .bind(&session_id.0)
+                    .bind(Utc::now()),
+            )
+            .await?;
+        Ok(())
+    }
+
+    pub async fn finish_contribution<'e>(
+        executor: impl Executor<'e, Database = Any>,
+        session_id: &SessionId,
+        accepted: &AcceptedContribution,
+    ) -> Result<(), StorageError> {
+        let sql = "UPDATE contributors SET finished_at = ?1, transcript_index = ?2, pot_pubkeys = \
+                   ?3, ecdsa_signature_valid = ?4, bls_signatures_valid = ?5, receipt = ?6, \
+                   receipt_signature = ?7 WHERE session_id = ?8 AND finished_at IS NULL AND \
+                   expired_at IS NULL";
+        let pot_pubkeys = serde_json::to_string(&accepted.pot_pubkeys)?;
+        let bls_signatures_valid = serde_json::to_string(&accepted.bls_signatures_valid)?;
+        #[allow(clippy::cast_possible_wrap)] // Transcript index fits in i64
+        let transcript_index = accepted.transcript_index as i64;
+        let receipt = accepted.receipt.as_ref().map(|(receipt, _)| receipt);
+        let receipt_signature = accepted.receipt.as_ref().map(|(_, signature)| signature);
+        executor
+            .execute(
+                sqlx::query(sql)
+                    .bind(Utc::now())
+                    .bind(transcript_index)
+                    .bind(pot_pubkeys)
+                    .bind(accepted.ecdsa_signature_valid)
+                    .bind(bls_signatures_valid)
+                    .bind(receipt)
+                    .bind(receipt_signature)
+                    .bind(&session_id.0),
+            )
+            .await?;
+        Ok(())
+    }
+
+    pub async fn expire_contribution<'e>(
+        executor: impl Executor<'e, Database = Any>,
+        session_id: &SessionId,
+    ) -> Result<(), StorageError> {
+        let sql = "UPDATE contributors SET expired_at = ?1 WHERE session_id = ?2 AND finished_at \
+                   IS NULL AND expired_at IS NULL";
+        executor
+            .execute(sqlx::query(sql).bind(Utc::now()).bind(&session_id.0))
+            .await?;
+        Ok(())
+    }
+
+    pub async fn reject_contribution<'e>(
+        executor: impl Executor<'e, Database = Any>,
+        session_id: &SessionId,
+        error_code: &str,
+    ) -> Result<(), StorageError> {
+        let sql = "UPDATE contributors SET expired_at = ?1, error_code = ?2 WHERE session_id = ?3 \
+                   AND finished_at IS NULL AND expired_at IS NULL";
+        executor
+            .execute(
+                sqlx::query(sql)
+                    .bind(Utc::now())
+                    .bind(error_code)
+                    .bind(&session_id.0),
+            )
+            .await?;
+        Ok(())
+    }
+
+    pub async fn insert_checkpoint<'e>(
+        executor: impl Executor<'e, Database = Any>,
+        checkpoint: &SignedCheckpoint,
+    ) -> Result<(), StorageError> {
+        // An identical row is touched without change and still counts as
+        // affected, a conflicting one is left alone and does not.
+        let sql = "INSERT INTO checkpoints (transcript_index, transcript_hash, previous_hash, \
+                   signature) VALUES (?1, ?2, ?3, ?4) ON CONFLICT (transcript_index) DO UPDATE \
+                   SET transcript_index = excluded.transcript_index WHERE \
+                   checkpoints.transcript_hash = excluded.transcript_hash AND \
+                   checkpoints.previous_hash = excluded.previous_hash AND checkpoints.signature = \
+                   excluded.signature";
+        #[allow(clippy::cast_possible_wrap)] // Transcript index fits in i64
+        let transcript_index = checkpoint.checkpoint.index as i64;
+        let result = executor
+            .execute(
+                sqlx::query(sql)
+                    .bind(transcript_index)
+                    .bind(&checkpoint.checkpoint.transcript_hash)
+                    .bind(&checkpoint.checkpoint.previous_hash)
+                    .bind(&checkpoint.signature),
+            )
+            .await?;
+        if result.rows_affected() == 0 {
+            return Err(StorageError::ConflictingCheckpoint(
+                checkpoint.checkpoint.index,
+            ));
+        }
+        Ok(())
+    }
+}
+
+fn checkpoint_from_row(row: &AnyRow) -> Result<SignedCheckpoint, StorageError> {
+    #[allow(clippy::cast_sign_loss, clippy::cast_possible_truncation)]
+    let index = row.try_get::<i64, _>("transcript_index")? as usize;
+    Ok(SignedCheckpoint {
+        checkpoint: Checkpoint {
+            index,
+            transcript_hash: row.try_get("transcript_hash")?,
+            previous_hash: row.try_get("previous_hash")?,
+        },
+        signature:  row.try_get("signature")?,
+    })
+}
+
+fn audit_entry_from_row(row: &AnyRow) -> Result<AuditEntry, StorageError> {
+    #[allow(clippy::cast_sign_loss)]
+    let sequence = row.try_get::<i64, _>("sequence")? as u64;
+    Ok(AuditEntry {
+        record:    AuditRecord {
+            sequence,
+            timestamp: row.try_get("timestamp")?,
+            event: serde_json::from_str(&row.try_get::<String, _>("event")?)?,
+            previous_hash: row.try_get("previous_hash")?,
+        },
+        hash:      row.try_get("hash")?,
+        signature: row.try_get("signature")?,
+    })
+}
+
+fn record_from_row(row: &AnyRow) -> Result<ContributorRecord, StorageError> {
+    let identity = row
+        .try_get::<Option<String>, _>("identity")?
+        .and_then(|identity| identity.parse().ok());
+    let pot_pubkeys = row
+        .try_get::<Option<String>, _>("pot_pubkeys")?
+        .map(|json| serde_json::from_str(&json))
+        .transpose()?;
+    let bls_signatures_valid = row
+        .try_get::<Option<String>, _>("bls_signatures_valid")?
+        .map(|json| serde_json::from_str(&json))
+        .transpose()?;
+    #[allow(clippy::cast_sign_loss, clippy::cast_possible_truncation)]
+    let transcript_index = row
+        .try_get::<Option<i64>, _>("transcript_index")?
+        .map(|index| index as usize);
+    Ok(ContributorRecord {
+        uid: row.try_get("uid")?,
+        identity,
+        session_id: row
+            .try_get::<Option<String>, _>("session_id")?
+            .map(SessionId),
+        started_at: row.try_get("started_at")?,
+        finished_at: row.try_get("finished_at")?,
+        expired_at: row.try_get("expired_at")?,
+        transcript_index,
+        pot_pubkeys,
+        ecdsa_signature_valid: row.try_get("ecdsa_signature_valid")?,
+        bls_signatures_valid,
+        receipt: row.try_get("receipt")?,
+        receipt_signature: row.try_get("receipt_signature")?,
+        error_code: row.try_get("error_code")?,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn detects_in_memory_databases() {
+        assert!(is_in_memory("sqlite::memory:"));
+        assert!(is_in_memory("sqlite://:memory:"));
+        assert!(is_in_memory("sqlite://file.db?mode=memory"));
+        assert!(!is_in_memory("sqlite://storage.db"));
+        assert!(!is_in_memory("postgres://localhost/memory"));
+    }
+}
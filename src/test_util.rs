@@ -1,11 +1,18 @@
 #![cfg(test)]
+#![allow(clippy::missing_panics_doc)]
 
 use crate::{
+    audit::{AuditLog, SharedAuditLog},
+    keys,
+    keys::Keys,
     sessions::{IdToken, SessionInfo},
+    storage::PersistentStorage,
     Options,
 };
 use clap::Parser;
 use kzg_ceremony_crypto::signature::identity::Identity;
+use std::sync::Arc;
+use tempfile::tempdir;
 use tokio::time::Instant;
 
 #[must_use]
@@ -44,6 +51,27 @@ pub fn test_options() -> Options {
         "INVALID",
         "--database-url",
         "sqlite://:memory:",
+        "--allow-random-signing-key",
     ];
-    Options::parse_from(args)
+    let mut options = Options::parse_from(args);
+    // Keep transcripts out of the working directory, and apart between tests.
+    let dir = tempdir().unwrap().into_path();
+    options.transcript_file = dir.join("transcript.json");
+    options.transcript_in_progress_file = dir.join("transcript.json.next");
+    options.archive.transcript_archive_dir = dir.join("transcript-archive");
+    options
+}
+
+/// Keys with a random signing key.
+#[must_use]
+pub fn test_keys() -> Keys {
+    Keys::new(&keys::Options::parse_from([
+        "kzg-ceremony-sequencer",
+        "--allow-random-signing-key",
+    ]))
+    .unwrap()
+}
+
+pub async fn test_audit_log(storage: PersistentStorage) -> SharedAuditLog {
+    Arc::new(AuditLog::new(storage, Arc::new(test_keys())).await.unwrap())
 }
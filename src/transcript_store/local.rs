@@ -0,0 +1,120 @@
+use crate::{
+    io::{write_file_durably, TranscriptIoError},
+    transcript_store::{versioned_name, TranscriptStore},
+};
+use axum::{
+    async_trait,
+    body::{boxed, BoxBody, Bytes, StreamBody},
+};
+use std::{
+    io::ErrorKind,
+    path::{Path, PathBuf},
+};
+use tokio::fs::File;
+use tokio_util::io::ReaderStream;
+
+/// Keeps the transcript in a file on local disk, with versioned copies in the
+/// same directory.
+#[derive(Clone, Debug)]
+pub struct LocalTranscriptStore {
+    path:      PathBuf,
+    work_path: PathBuf,
+}
+
+impl LocalTranscriptStore {
+    /// Stores the transcript at `path`. Writes go to `work_path` first and
+    /// are then renamed over `path`.
+    #[must_use]
+    pub const fn new(path: PathBuf, work_path: PathBuf) -> Self {
+        Self { path, work_path }
+    }
+
+    /// Path of the copy of `version` of the transcript.
+    #[must_use]
+    pub fn version_path(&self, version: usize) -> PathBuf {
+        let name = self
+            .path
+            .file_name()
+            .map_or_else(String::new, |name| name.to_string_lossy().into_owned());
+        self.path.with_file_name(versioned_name(&name, version))
+    }
+
+    async fn write(
+        path: PathBuf,
+        work_path: PathBuf,
+        json: Bytes,
+    ) -> Result<(), TranscriptIoError> {
+        tokio::task::spawn_blocking(move || write_file_durably(&path, &work_path, &json))
+            .await
+            .map_err(TranscriptIoError::TaskError)?
+            .map_err(TranscriptIoError::IoError)
+    }
+}
+
+fn not_found_as_none<T>(result: std::io::Result<T>) -> Result<Option<T>, TranscriptIoError> {
+    match result {
+        Ok(value) => Ok(Some(value)),
+        Err(e) if e.kind() == ErrorKind::NotFound => Ok(None),
+        Err(e) => Err(TranscriptIoError::IoError(e)),
+    }
+}
+
+fn work_path_for(path: &Path) -> PathBuf {
+    let mut work_path = path.as_os_str().to_owned();
+    work_path.push(".next");
+    PathBuf::from(work_path)
+}
+
+#[async_trait]
+impl TranscriptStore for LocalTranscriptStore {
+    async fn load(&self) -> Result<Option<Bytes>, TranscriptIoError> {
+        Ok(not_found_as_none(tokio::fs::read(&self.path).await)?.map(Bytes::from))
+    }
+
+    async fn serve(&self) -> Result<Option<BoxBody>, TranscriptIoError> {
+        Ok(not_found_as_none(File::open(&self.path).await)?
+            .map(|file| boxed(StreamBody::new(ReaderStream::new(file)))))
+    }
+
+    async fn store(&self, json: Bytes) -> Result<(), TranscriptIoError> {
+        Self::write(self.path.clone(), self.work_path.clone(), json).await
+    }
+
+    async fn store_version(&self, version: usize, json: Bytes) -> Result<(), TranscriptIoError> {
+        let path = self.version_path(version);
+        let work_path = work_path_for(&path);
+        Self::write(path, work_path, json).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    #[tokio::test]
+    async fn stores_current_and_versioned_transcripts() {
+        let dir = tempdir().unwrap();
+        let store = LocalTranscriptStore::new(
+            dir.path().join("transcript.json"),
+            dir.path().join("transcript.json.next"),
+        );
+        assert!(store.load().await.unwrap().is_none());
+        assert!(store.serve().await.unwrap().is_none());
+
+        store.store(Bytes::from_static(b"first")).await.unwrap();
+        store.store(Bytes::from_static(b"second")).await.unwrap();
+        store
+            .store_version(2, Bytes::from_static(b"second"))
+            .await
+            .unwrap();
+
+        assert_eq!(store.load().await.unwrap().unwrap(), "second");
+        assert_eq!(
+            std::fs::read(dir.path().join("transcript-000002.json")).unwrap(),
+            b"second"
+        );
+        assert!(!dir.path().join("transcript.json.next").exists());
+        assert!(!dir.path().join("transcript-000002.json.next").exists());
+    }
+}
@@ -0,0 +1,206 @@
+//! Where the transcript is kept and published.
+//!
+//! After every accepted contribution the transcript is written to a
+//! [`TranscriptStore`], from which `/info/current_state` serves it. Besides
+//! the local filesystem, the transcript can be kept in an S3-compatible
+//! bucket, so that a CDN or read replicas can consume it directly.
+//!
+//! With `--transcript-publish-interval N`, every Nth version is additionally
+//! kept under a versioned name, e.g. `transcript-000100.json` next to
+//! `transcript.json`. Later contributions add new versioned copies; an
+//! existing copy is only replaced if the transcript is rolled back past its
+//! version and that version is published again.
+
+use crate::{io::TranscriptIoError, util::Secret, Options as CeremonyOptions, SharedTranscript};
+use axum::{
+    async_trait,
+    body::{BoxBody, Bytes},
+};
+use clap::Parser;
+use std::{fmt::Debug, sync::Arc};
+use tracing::{info, warn};
+use url::Url;
+
+mod local;
+mod s3;
+
+pub use self::{local::LocalTranscriptStore, s3::S3TranscriptStore};
+
+/// Name of the current transcript in the object store.
+pub const TRANSCRIPT_NAME: &str = "transcript.json";
+
+#[derive(Clone, Debug, PartialEq, Eq, Parser)]
+#[group(skip)]
+pub struct Options {
+    /// Keep the transcript in an S3-compatible bucket instead of at
+    /// `--transcript-file`. The url is path-style: its first path segment
+    /// names the bucket, the remainder is a prefix for the object keys.
+    ///
+    /// Example: `https://s3.us-east-1.amazonaws.com/ceremony/transcripts`
+    /// Local `MinIO`: `http://localhost:9000/ceremony`
+    #[clap(long, env)]
+    pub transcript_s3_url: Option<Url>,
+
+    /// Region the bucket is in, used to sign requests.
+    #[clap(long, env, default_value = "us-east-1")]
+    pub transcript_s3_region: String,
+
+    /// Access key id used to sign requests to the bucket.
+    #[clap(long, env, default_value = "")]
+    pub transcript_s3_access_key_id: String,
+
+    /// Secret access key used to sign requests to the bucket.
+    #[clap(long, env, default_value = "")]
+    pub transcript_s3_secret_access_key: Secret,
+
+    /// Additionally keep every Nth version of the transcript under a
+    /// versioned name. `0` disables versioned copies.
+    #[clap(long, env, default_value = "0")]
+    pub transcript_publish_interval: usize,
+}
+
+/// Shared handle to the store selected by `--transcript-s3-url`.
+pub type SharedTranscriptStore = Arc<dyn TranscriptStore>;
+
+/// Storage of the serialized transcript.
+#[async_trait]
+pub trait TranscriptStore: Debug + Send + Sync {
+    /// Returns the current transcript, or `None` if none was stored yet.
+    async fn load(&self) -> Result<Option<Bytes>, TranscriptIoError>;
+
+    /// Returns the current transcript as a response body, or `None` if none
+    /// was stored yet.
+    async fn serve(&self) -> Result<Option<BoxBody>, TranscriptIoError>;
+
+    /// Replaces the current transcript. Once this returns, the new transcript
+    /// survives a crash.
+    async fn store(&self, json: Bytes) -> Result<(), TranscriptIoError>;
+
+    /// Keeps a copy of the transcript as it was after `version`
+    /// contributions, under [`versioned_name`].
+    async fn store_version(&self, version: usize, json: Bytes) -> Result<(), TranscriptIoError>;
+}
+
+/// Returns the name under which `version` of the transcript named `name` is
+/// published, e.g. `transcript-000042.json`. Versions are zero padded so that
+/// they sort in order.
+#[must_use]
+pub fn versioned_name(name: &str, version: usize) -> String {
+    match name.rsplit_once('.') {
+        Some((stem, extension)) => format!("{stem}-{version:06}.{extension}"),
+        None => format!("{name}-{version:06}"),
+    }
+}
+
+/// Serializes the transcript and writes it to the store, publishing a
+/// versioned copy if its number of participants is a multiple of
+/// `publish_interval`.
+///
+/// The versioned copy is best-effort: once the current transcript is stored,
+/// the contribution is part of it, so failing to keep the copy is only
+/// logged.
+///
+/// # Errors
+///
+/// Returns an error if the transcript can not be serialized, or the current
+/// transcript can not be stored.
+pub async fn save_transcript(
+    store: &dyn TranscriptStore,
+    transcript: SharedTranscript,
+    publish_interval: usize,
+) -> Result<(), TranscriptIoError> {
+    let (version, json) = tokio::task::spawn_blocking(move || {
+        let transcript = transcript.blocking_read();
+        serde_json::to_vec_pretty(&*transcript)
+            .map(|json| (transcript.num_participants(), Bytes::from(json)))
+    })
+    .await
+    .map_err(TranscriptIoError::TaskError)?
+    .map_err(TranscriptIoError::SerializationError)?;
+
+    store.store(json.clone()).await?;
+    if publish_interval > 0 && version % publish_interval == 0 {
+        match store.store_version(version, json).await {
+            Ok(()) => info!(version, "Published transcript version"),
+            Err(error) => warn!(version, %error, "Failed to publish transcript version"),
+        }
+    }
+    Ok(())
+}
+
+/// Creates the store selected by the options.
+///
+/// # Errors
+///
+/// Returns an error if `--transcript-s3-url` does not name a bucket.
+pub fn transcript_store(options: &CeremonyOptions) -> eyre::Result<SharedTranscriptStore> {
+    match &options.transcript_store.transcript_s3_url {
+        Some(url) => Ok(Arc::new(S3TranscriptStore::new(
+            url,
+            &options.transcript_store,
+        )?)),
+        None => Ok(Arc::new(LocalTranscriptStore::new(
+            options.transcript_file.clone(),
+            options.transcript_in_progress_file.clone(),
+        ))),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::test_transcript;
+    use std::io;
+    use tokio::sync::{Mutex, RwLock};
+
+    /// Keeps the current transcript in memory, but can not keep versions.
+    #[derive(Debug, Default)]
+    struct UnversionedStore {
+        current: Mutex<Option<Bytes>>,
+    }
+
+    #[async_trait]
+    impl TranscriptStore for UnversionedStore {
+        async fn load(&self) -> Result<Option<Bytes>, TranscriptIoError> {
+            Ok(self.current.lock().await.clone())
+        }
+
+        async fn serve(&self) -> Result<Option<BoxBody>, TranscriptIoError> {
+            Ok(None)
+        }
+
+        async fn store(&self, json: Bytes) -> Result<(), TranscriptIoError> {
+            *self.current.lock().await = Some(json);
+            Ok(())
+        }
+
+        async fn store_version(
+            &self,
+            _version: usize,
+            _json: Bytes,
+        ) -> Result<(), TranscriptIoError> {
+            Err(TranscriptIoError::IoError(io::ErrorKind::Other.into()))
+        }
+    }
+
+    #[tokio::test]
+    async fn stores_transcript_when_version_fails() {
+        let store = UnversionedStore::default();
+        let transcript = Arc::new(RwLock::new(test_transcript()));
+        save_transcript(&store, transcript, 1).await.unwrap();
+        assert!(store.load().await.unwrap().is_some());
+    }
+
+    #[test]
+    fn names_versions() {
+        assert_eq!(
+            versioned_name("transcript.json", 42),
+            "transcript-000042.json"
+        );
+        assert_eq!(
+            versioned_name("transcript.v2.json", 1_000_000),
+            "transcript.v2-1000000.json"
+        );
+        assert_eq!(versioned_name("transcript", 0), "transcript-000000");
+    }
+}
@@ -0,0 +1,391 @@
+use crate::{
+    io::TranscriptIoError,
+    transcript_store::{versioned_name, Options, TranscriptStore, TRANSCRIPT_NAME},
+    util::Secret,
+};
+use axum::{
+    async_trait,
+    body::{boxed, BoxBody, Bytes, Full},
+};
+use chrono::{DateTime, Utc};
+use eyre::{ensure, eyre};
+use hmac::{Hmac, Mac};
+use http::{header, Method, StatusCode};
+use sha2::{Digest, Sha256};
+use url::Url;
+
+const SIGNING_ALGORITHM: &str = "AWS4-HMAC-SHA256";
+const SIGNED_HEADERS: &str = "host;x-amz-content-sha256;x-amz-date";
+
+/// Keeps the transcript in a bucket of an S3-compatible object store, such
+/// as AWS S3 or `MinIO`.
+///
+/// Objects are addressed path-style, as `<endpoint>/<bucket>/<prefix>/<name>`,
+/// and requests are authenticated with AWS signature version 4.
+#[derive(Clone, Debug)]
+pub struct S3TranscriptStore {
+    client:            reqwest::Client,
+    base_url:          Url,
+    region:            String,
+    access_key_id:     String,
+    secret_access_key: Secret,
+}
+
+impl S3TranscriptStore {
+    /// Creates a store for the bucket and prefix named by `url`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if `url` does not name a bucket.
+    pub fn new(url: &Url, options: &Options) -> eyre::Result<Self> {
+        ensure!(
+            url.path_segments()
+                .and_then(|mut segments| segments.next())
+                .map_or(false, |bucket| !bucket.is_empty()),
+            "The transcript S3 url {} does not name a bucket",
+            url
+        );
+        let mut base_url = url.clone();
+        base_url.set_query(None);
+        base_url.set_fragment(None);
+        base_url
+            .path_segments_mut()
+            .map_err(|_| eyre!("The transcript S3 url {} can not be a base", url))?
+            .pop_if_empty()
+            // Ends in an empty segment, which `object_url` replaces by the name.
+            .push("");
+        Ok(Self {
+            client: reqwest::Client::new(),
+            base_url,
+            region: options.transcript_s3_region.clone(),
+            access_key_id: options.transcript_s3_access_key_id.clone(),
+            secret_access_key: options.transcript_s3_secret_access_key.clone(),
+        })
+    }
+
+    fn object_url(&self, name: &str) -> Url {
+        let mut url = self.base_url.clone();
+        url.path_segments_mut()
+            .expect("checked in constructor")
+            .pop()
+            .push(name);
+        url
+    }
+
+    async fn request(
+        &self,
+        method: Method,
+        name: &str,
+        body: Bytes,
+    ) -> Result<reqwest::Response, TranscriptIoError> {
+        let url = self.object_url(name);
+        let payload_hash = hex::encode(Sha256::digest(&body));
+        let now = Utc::now();
+        let authorization = authorization(
+            &Credentials {
+                region:            &self.region,
+                access_key_id:     &self.access_key_id,
+                secret_access_key: self.secret_access_key.get_secret(),
+            },
+            &method,
+            &url,
+            &payload_hash,
+            now,
+        );
+        self.client
+            .request(method, url)
+            .header("x-amz-date", amz_date(now))
+            .header("x-amz-content-sha256", payload_hash)
+            .header(header::AUTHORIZATION, authorization)
+            .header(header::CONTENT_TYPE, "application/json")
+            .body(body)
+            .send()
+            .await
+            .map_err(TranscriptIoError::RequestError)
+    }
+
+    /// Fetches an object, returning `None` if it does not exist.
+    async fn get(&self, name: &str) -> Result<Option<Bytes>, TranscriptIoError> {
+        let response = self.request(Method::GET, name, Bytes::new()).await?;
+        if response.status() == StatusCode::NOT_FOUND {
+            return Ok(None);
+        }
+        let response = check_status(response).await?;
+        Ok(Some(
+            response
+                .bytes()
+                .await
+                .map_err(TranscriptIoError::RequestError)?,
+        ))
+    }
+
+    async fn put(&self, name: &str, json: Bytes) -> Result<(), TranscriptIoError> {
+        let response = self.request(Method::PUT, name, json).await?;
+        check_status(response).await?;
+        Ok(())
+    }
+}
+
+async fn check_status(response: reqwest::Response) -> Result<reqwest::Response, TranscriptIoError> {
+    let status = response.status();
+    if status.is_success() {
+        return Ok(response);
+    }
+    let body = response.text().await.unwrap_or_default();
+    Err(TranscriptIoError::ObjectStoreError { status, body })
+}
+
+#[async_trait]
+impl TranscriptStore for S3TranscriptStore {
+    async fn load(&self) -> Result<Option<Bytes>, TranscriptIoError> {
+        self.get(TRANSCRIPT_NAME).await
+    }
+
+    async fn serve(&self) -> Result<Option<BoxBody>, TranscriptIoError> {
+        Ok(self
+            .get(TRANSCRIPT_NAME)
+            .await?
+            .map(|json| boxed(Full::new(json))))
+    }
+
+    async fn store(&self, json: Bytes) -> Result<(), TranscriptIoError> {
+        self.put(TRANSCRIPT_NAME, json).await
+    }
+
+    async fn store_version(&self, version: usize, json: Bytes) -> Result<(), TranscriptIoError> {
+        self.put(&versioned_name(TRANSCRIPT_NAME, version), json)
+            .await
+    }
+}
+
+struct Credentials<'a> {
+    region:            &'a str,
+    access_key_id:     &'a str,
+    secret_access_key: &'a str,
+}
+
+fn amz_date(time: DateTime<Utc>) -> String {
+    time.format("%Y%m%dT%H%M%SZ").to_string()
+}
+
+/// Value of the `Host` header for `url`, which includes the port unless it is
+/// the default for the scheme.
+fn host(url: &Url) -> String {
+    let host = url.host_str().unwrap_or_default();
+    url.port()
+        .map_or_else(|| host.to_string(), |port| format!("{host}:{port}"))
+}
+
+fn hmac_sha256(key: &[u8], data: &str) -> Vec<u8> {
+    let mut mac = Hmac::<Sha256>::new_from_slice(key).expect("HMAC accepts keys of any size");
+    mac.update(data.as_bytes());
+    mac.finalize().into_bytes().to_vec()
+}
+
+/// Derives the key that signs requests to `service` in `region` on `date`
+/// (formatted as `YYYYMMDD`).
+fn signing_key(secret_access_key: &str, date: &str, region: &str, service: &str) -> Vec<u8> {
+    let key = hmac_sha256(format!("AWS4{secret_access_key}").as_bytes(), date);
+    let key = hmac_sha256(&key, region);
+    let key = hmac_sha256(&key, service);
+    hmac_sha256(&key, "aws4_request")
+}
+
+/// Computes the signature version 4 `Authorization` header for a request
+/// that carries exactly the `host`, `x-amz-content-sha256` and `x-amz-date`
+/// headers and no query string.
+fn authorization(
+    credentials: &Credentials,
+    method: &Method,
+    url: &Url,
+    payload_hash: &str,
+    time: DateTime<Utc>,
+) -> String {
+    let amz_date = amz_date(time);
+    let date = &amz_date[..8];
+    let scope = format!("{date}/{}/s3/aws4_request", credentials.region);
+    // The path is percent-encoded by `Url` already, S3 expects it not to be
+    // encoded twice.
+    let canonical_request = format!(
+        "{method}\n{path}\n\nhost:{host}\nx-amz-content-sha256:{payload_hash}\nx-amz-date:\
+         {amz_date}\n\n{SIGNED_HEADERS}\n{payload_hash}",
+        path = url.path(),
+        host = host(url),
+    );
+    let string_to_sign = format!(
+        "{SIGNING_ALGORITHM}\n{amz_date}\n{scope}\n{}",
+        hex::encode(Sha256::digest(canonical_request.as_bytes()))
+    );
+    let key = signing_key(
+        credentials.secret_access_key,
+        date,
+        credentials.region,
+        "s3",
+    );
+    let signature = hex::encode(hmac_sha256(&key, &string_to_sign));
+    format!(
+        "{SIGNING_ALGORITHM} Credential={}/{scope}, SignedHeaders={SIGNED_HEADERS}, \
+         Signature={signature}",
+        credentials.access_key_id
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::test_options;
+    use axum::{handler::Handler, http::HeaderMap, Router, Server};
+    use chrono::{NaiveDateTime, TimeZone};
+    use std::{collections::HashMap, net::SocketAddr, sync::Arc};
+    use tokio::sync::Mutex;
+
+    const ACCESS_KEY_ID: &str = "minioadmin";
+    const SECRET_ACCESS_KEY: &str = "minio-secret";
+
+    type Objects = Arc<Mutex<HashMap<String, Bytes>>>;
+
+    /// Serves GET and PUT of objects like an S3-compatible store would,
+    /// rejecting requests that are not signed with the test credentials.
+    fn object_store_stand_in() -> (SocketAddr, Objects) {
+        let objects = Objects::default();
+        let state = objects.clone();
+        let handler = move |method: Method, uri: http::Uri, headers: HeaderMap, body: Bytes| {
+            let objects = state.clone();
+            async move {
+                let header = |name: &str| {
+                    headers
+                        .get(name)
+                        .and_then(|value| value.to_str().ok())
+                        .unwrap_or_default()
+                        .to_string()
+                };
+                let time = NaiveDateTime::parse_from_str(&header("x-amz-date"), "%Y%m%dT%H%M%SZ")
+                    .map(|time| Utc.from_utc_datetime(&time));
+                let url = Url::parse(&format!("http://{}{}", header("host"), uri)).unwrap();
+                let payload_hash = hex::encode(Sha256::digest(&body));
+                let authorized = time.map_or(false, |time| {
+                    header("x-amz-content-sha256") == payload_hash
+                        && header("authorization")
+                            == authorization(
+                                &Credentials {
+                                    region:            "us-east-1",
+                                    access_key_id:     ACCESS_KEY_ID,
+                                    secret_access_key: SECRET_ACCESS_KEY,
+                                },
+                                &method,
+                                &url,
+                                &payload_hash,
+                                time,
+                            )
+                });
+                if !authorized {
+                    return (
+                        StatusCode::FORBIDDEN,
+                        Bytes::from_static(b"SignatureDoesNotMatch"),
+                    );
+                }
+                let mut objects = objects.lock().await;
+                match method {
+                    Method::PUT => {
+                        objects.insert(uri.path().to_string(), body);
+                        (StatusCode::OK, Bytes::new())
+                    }
+                    Method::GET => objects.get(uri.path()).map_or(
+                        (StatusCode::NOT_FOUND, Bytes::from_static(b"NoSuchKey")),
+                        |object| (StatusCode::OK, object.clone()),
+                    ),
+                    _ => (StatusCode::METHOD_NOT_ALLOWED, Bytes::new()),
+                }
+            }
+        };
+        let app = Router::new().fallback(handler.into_service());
+        let server = Server::bind(&"127.0.0.1:0".parse().unwrap()).serve(app.into_make_service());
+        let addr = server.local_addr();
+        tokio::spawn(server);
+        (addr, objects)
+    }
+
+    fn s3_store(url: &str, secret_access_key: &str) -> S3TranscriptStore {
+        let mut options = test_options().transcript_store;
+        options.transcript_s3_access_key_id = ACCESS_KEY_ID.to_string();
+        options.transcript_s3_secret_access_key = secret_access_key.parse().unwrap();
+        S3TranscriptStore::new(&url.parse().unwrap(), &options).unwrap()
+    }
+
+    #[test]
+    fn addresses_objects_path_style() {
+        let store = s3_store(
+            "http://localhost:9000/ceremony/transcripts/",
+            SECRET_ACCESS_KEY,
+        );
+        assert_eq!(
+            store.object_url("transcript.json").as_str(),
+            "http://localhost:9000/ceremony/transcripts/transcript.json"
+        );
+        let store = s3_store("https://s3.amazonaws.com/ceremony", SECRET_ACCESS_KEY);
+        assert_eq!(
+            store.object_url("transcript-000001.json").as_str(),
+            "https://s3.amazonaws.com/ceremony/transcript-000001.json"
+        );
+        assert!(S3TranscriptStore::new(
+            &"http://localhost:9000/".parse().unwrap(),
+            &test_options().transcript_store
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn derives_signing_key() {
+        // Example from the AWS signature version 4 documentation.
+        let key = signing_key(
+            "wJalrXUtnFEMI/K7MDENG+bPxRfiCYEXAMPLEKEY",
+            "20120215",
+            "us-east-1",
+            "iam",
+        );
+        assert_eq!(
+            hex::encode(key),
+            "f4780e2d9f65fa895f9c67b32ce1baf0b0d8a43505a000a1a9e090d414db404d"
+        );
+    }
+
+    #[tokio::test]
+    async fn stores_transcripts_in_bucket() {
+        let (addr, objects) = object_store_stand_in();
+        let store = s3_store(
+            &format!("http://{addr}/ceremony/mainnet"),
+            SECRET_ACCESS_KEY,
+        );
+
+        assert!(store.load().await.unwrap().is_none());
+        store.store(Bytes::from_static(b"first")).await.unwrap();
+        store.store(Bytes::from_static(b"second")).await.unwrap();
+        store
+            .store_version(2, Bytes::from_static(b"second"))
+            .await
+            .unwrap();
+
+        assert_eq!(store.load().await.unwrap().unwrap(), "second");
+        let objects = objects.lock().await;
+        assert_eq!(objects.len(), 2);
+        assert_eq!(
+            objects["/ceremony/mainnet/transcript-000002.json"],
+            "second"
+        );
+    }
+
+    #[tokio::test]
+    async fn reports_rejected_requests() {
+        let (addr, _) = object_store_stand_in();
+        let store = s3_store(&format!("http://{addr}/ceremony"), "wrong-secret");
+
+        let result = store.store(Bytes::from_static(b"first")).await;
+        assert!(matches!(
+            result,
+            Err(TranscriptIoError::ObjectStoreError {
+                status: StatusCode::FORBIDDEN,
+                ..
+            })
+        ));
+    }
+}
@@ -4,7 +4,9 @@ use crate::{
 };
 use ethers_core::types::Signature;
 use http::StatusCode;
-use kzg_ceremony_crypto::{BatchContribution, BatchTranscript, G2};
+use kzg_ceremony_crypto::{
+    signature::receipt::ContributionReceipt, BatchContribution, BatchTranscript,
+};
 use secrecy::Secret;
 use serde_json::Value;
 use std::collections::HashMap;
@@ -171,7 +173,7 @@ pub async fn request_contribute(
         .unwrap()
 }
 
-async fn get_sequencer_eth_address(harness: &Harness, http_client: &reqwest::Client) -> String {
+pub async fn get_sequencer_eth_address(harness: &Harness, http_client: &reqwest::Client) -> String {
     http_client
         .get(harness.app_path("/info/status"))
         .send()
@@ -225,35 +227,20 @@ pub async fn contribute_successfully(
         .parse()
         .unwrap();
 
-    signature
-        .verify(receipt, address)
+    let receipt = serde_json::from_str::<ContributionReceipt>(receipt)
+        .expect("receipt must be a JSON-encoded contribution receipt");
+    receipt
+        .verify(&signature, address, &harness.options.ceremony_parameters())
         .expect("must be valid signature");
-
-    let receipt_contents =
-        serde_json::from_str::<Value>(receipt).expect("receipt must be a JSON-encoded string");
+    assert_eq!(receipt.identity, user_id);
     assert_eq!(
-        receipt_contents
-            .get("identity")
-            .expect("must contain identity"),
-        user_id
-    );
-
-    let witness: Vec<G2> = serde_json::from_value(
-        receipt_contents
-            .get("witness")
-            .expect("must contain witness")
-            .clone(),
-    )
-    .expect("witness must be a vector of G2 points");
-
-    assert_eq!(
-        witness,
+        receipt.pot_pubkeys,
         contribution
             .contributions
             .iter()
             .map(|c| c.pot_pubkey)
             .collect::<Vec<_>>()
-    )
+    );
 }
 
 pub fn assert_includes_contribution(
@@ -6,7 +6,7 @@ use chrono::{DateTime, FixedOffset};
 use clap::Parser;
 use ethers_signers::LocalWallet;
 use kzg_ceremony_crypto::BatchTranscript;
-use kzg_ceremony_sequencer::{io::read_json_file, start_server, Options};
+use kzg_ceremony_sequencer::{engine::EngineChoice, io::read_json_file, start_server, Options};
 use rand::thread_rng;
 use std::{path::PathBuf, time::Duration};
 use tempfile::{tempdir, TempDir};
@@ -40,6 +40,7 @@ fn test_options() -> Options {
         "INVALID",
         "--database-url",
         "sqlite::memory:",
+        "--allow-random-signing-key",
     ];
     Options::parse_from(args)
 }
@@ -81,6 +82,10 @@ impl Harness {
             .await
     }
 
+    pub async fn rename_gh_user(&self, user: &TestUser, name: String) -> TestUser {
+        self.auth_state.rename_gh_user(user, name).await
+    }
+
     pub async fn create_eth_user(&self) -> TestUser {
         let wallet = LocalWallet::new(&mut thread_rng());
         let nonce = 42;
@@ -171,6 +176,7 @@ impl Harness {
         let transcript_wip = temp_dir.path().join("transcript.json.next");
         options.transcript_file = transcript;
         options.transcript_in_progress_file = transcript_wip;
+        options.archive.transcript_archive_dir = temp_dir.path().join("archive");
         let (app_shutdown_sender, _) = broadcast::channel::<()>(1);
         let (auth_shutdown_sender, _) = broadcast::channel::<()>(1);
         let auth_state = AuthState::default();
@@ -238,6 +244,28 @@ impl Builder {
         self
     }
 
+    #[allow(dead_code)]
+    pub fn require_signatures(mut self) -> Self {
+        self.options.require_bls_signatures = true;
+        self.options.require_ecdsa_signatures = true;
+        self
+    }
+
+    #[allow(dead_code)]
+    pub fn set_engine(mut self, engine: EngineChoice) -> Self {
+        self.options.engine = engine;
+        self
+    }
+
+    #[allow(dead_code)]
+    pub fn rehearsal(mut self) -> Self {
+        self.options.eip712_name = "Ethereum KZG Ceremony Rehearsal".to_string();
+        self.options.eip712_chain_id = 5;
+        self.options.bls_cipher_suite =
+            "BLS_SIG_BLS12381G1_XMD:SHA-256_SSWU_RO_POP_REHEARSAL_".to_string();
+        self
+    }
+
     pub fn set_max_sessions_count(mut self, size: usize) -> Self {
         self.options.lobby.max_sessions_count = size;
         self
@@ -259,6 +287,12 @@ impl Builder {
         self
     }
 
+    #[allow(dead_code)]
+    pub fn set_transcript_publish_interval(mut self, interval: usize) -> Self {
+        self.options.transcript_store.transcript_publish_interval = interval;
+        self
+    }
+
     #[allow(dead_code)]
     pub fn set_db_url(mut self, db_url: String) -> Self {
         self.options.storage.database_url = db_url;
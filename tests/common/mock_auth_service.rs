@@ -129,6 +129,19 @@ impl AuthState {
         }
     }
 
+    pub async fn rename_gh_user(&self, user: &TestUser, name: String) -> TestUser {
+        let mut state = self.github_users.write().await;
+        let gh_user = state
+            .users
+            .get_mut(&user.id)
+            .expect("user must be registered");
+        gh_user.name = name;
+        TestUser {
+            id:   user.id,
+            user: AnyTestUser::Gh(gh_user.clone()),
+        }
+    }
+
     pub async fn register_eth_user(&self, user: EthUser) -> TestUser {
         let id = self.eth_users.write().await.register(user.clone());
         TestUser {
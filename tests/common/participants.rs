@@ -58,13 +58,17 @@ pub async fn well_behaved(
         .add_entropy::<BLST>(
             &entropy_from_str(&user.identity().to_string()),
             &user.identity(),
+            &harness.options.ceremony_parameters(),
         )
         .expect("Adding entropy must be possible");
 
     if let AnyTestUser::Eth(EthUser { wallet, .. }) = &user.user {
         contribution.ecdsa_signature = EcdsaSignature(Some(
             wallet
-                .sign_typed_data(&signature::ContributionTypedData::from(&contribution))
+                .sign_typed_data(&signature::ContributionTypedData::new(
+                    &contribution,
+                    &harness.options.ceremony_parameters(),
+                ))
                 .await
                 .unwrap(),
         ));
@@ -101,6 +105,7 @@ pub async fn wrong_ecdsa(
         .add_entropy::<DefaultEngine>(
             &entropy_from_str(&user.identity().to_string()),
             &user.identity(),
+            &harness.options.ceremony_parameters(),
         )
         .expect("Adding entropy must be possible");
 
@@ -137,6 +142,7 @@ pub async fn wrong_bls(
         .add_entropy::<DefaultEngine>(
             &entropy_from_str(&user.identity().to_string()),
             &user.identity(),
+            &harness.options.ceremony_parameters(),
         )
         .expect("Adding entropy must be possible");
 
@@ -170,6 +176,7 @@ pub async fn slow_compute(
         .add_entropy::<Arkworks>(
             &entropy_from_str(&user.identity().to_string()),
             &user.identity(),
+            &harness.options.ceremony_parameters(),
         )
         .expect("Adding entropy must be possible");
     let response = actions::request_contribute(harness, client, &session_id, &contribution).await;
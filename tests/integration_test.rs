@@ -12,7 +12,15 @@ use ethers_signers::{LocalWallet, Signer};
 use http::StatusCode;
 use kzg_ceremony_crypto::{
     signature::{BlsSignature, ContributionTypedData, EcdsaSignature},
-    Arkworks, DefaultEngine, G1,
+    Arkworks, BatchTranscript, CeremonyParameters, DefaultEngine, G1,
+};
+use kzg_ceremony_sequencer::{
+    archive::{transcript_hash, ContributionArchive},
+    audit::{verify_audit_log, AuditEntry, AuditEvent},
+    checkpoint::SignedCheckpoint,
+    engine::EngineChoice,
+    io::read_json_file,
+    transcript_store::LocalTranscriptStore,
 };
 use rand::thread_rng;
 use secrecy::Secret;
@@ -252,7 +260,11 @@ async fn test_gh_contribution_happy_path() {
         .unwrap();
     let entropy = Secret::new(entropy);
     contribution
-        .add_entropy::<Arkworks>(&entropy, &user.identity())
+        .add_entropy::<Arkworks>(
+            &entropy,
+            &user.identity(),
+            &harness.options.ceremony_parameters(),
+        )
         .expect("Adding entropy must be possible");
 
     actions::contribute_successfully(
@@ -321,7 +333,7 @@ async fn test_double_contribution() {
         .unwrap();
     let entropy = Secret::new(entropy);
     contribution
-        .add_entropy::<DefaultEngine>(&entropy, &user_id)
+        .add_entropy::<DefaultEngine>(&entropy, &user_id, &harness.options.ceremony_parameters())
         .expect("Adding entropy must be possible");
 
     // First, successful contribution;
@@ -370,6 +382,257 @@ async fn test_double_contribution() {
     )
 }
 
+#[tokio::test]
+async fn test_double_contribution_after_gh_rename() {
+    let harness = run_test_harness().await;
+    let http_client = reqwest::Client::new();
+
+    let (user, session_id) =
+        actions::create_and_login_gh_user(&harness, &http_client, "kustosz".to_string()).await;
+    let mut contribution = actions::try_contribute(&harness, &http_client, &session_id).await;
+    contribution
+        .add_entropy::<DefaultEngine>(
+            &actions::entropy_from_str("such an unguessable string, wow!"),
+            &user.identity(),
+            &harness.options.ceremony_parameters(),
+        )
+        .expect("Adding entropy must be possible");
+    actions::contribute_successfully(
+        &harness,
+        &http_client,
+        &session_id,
+        &contribution,
+        &user.identity().to_string(),
+    )
+    .await;
+
+    // Renaming the Github account must not allow contributing again.
+    let renamed = harness
+        .rename_gh_user(&user, "kustosz-renamed".to_string())
+        .await;
+    assert_ne!(user.identity().to_string(), renamed.identity().to_string());
+    let csrf = actions::get_and_validate_csrf_token(&harness, None).await;
+    let response = actions::request_auth_callback(&harness, &http_client, &renamed, &csrf).await;
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    assert!(response
+        .text()
+        .await
+        .unwrap()
+        .contains("AuthErrorPayload::UserAlreadyContributed"));
+
+    // The transcript keeps the username the participant contributed with.
+    let transcript = harness.read_transcript_file().await;
+    actions::assert_includes_contribution(&transcript, &contribution, &user, false, true);
+}
+
+#[tokio::test]
+async fn test_contributions_are_archived() {
+    let harness = run_test_harness().await;
+    let http_client = reqwest::Client::new();
+
+    let (user, session_id) =
+        actions::create_and_login_gh_user(&harness, &http_client, "kustosz".to_string()).await;
+    let mut contribution = actions::try_contribute(&harness, &http_client, &session_id).await;
+    contribution
+        .add_entropy::<DefaultEngine>(
+            &actions::entropy_from_str("such an unguessable string, wow!"),
+            &user.identity(),
+            &harness.options.ceremony_parameters(),
+        )
+        .expect("Adding entropy must be possible");
+    actions::contribute_successfully(
+        &harness,
+        &http_client,
+        &session_id,
+        &contribution,
+        &user.identity().to_string(),
+    )
+    .await;
+
+    let archive = ContributionArchive::new(harness.options.archive.transcript_archive_dir.clone());
+    let entries = archive.entries(1).unwrap();
+    assert_eq!(entries[0].contribution, contribution);
+    assert_eq!(entries[0].identity, user.identity());
+
+    let transcript = harness.read_transcript_file().await;
+    let replayed = archive
+        .replay(
+            &harness.options.ceremony_sizes,
+            &harness.options.ceremony_parameters(),
+            1,
+        )
+        .unwrap();
+    assert_eq!(replayed, transcript);
+    assert_eq!(
+        entries[0].transcript_hash,
+        transcript_hash(&transcript).unwrap()
+    );
+}
+
+#[tokio::test]
+async fn test_checkpoints_commit_to_transcript() {
+    let harness = run_test_harness().await;
+    let http_client = reqwest::Client::new();
+
+    let (user, session_id) =
+        actions::create_and_login_gh_user(&harness, &http_client, "kustosz".to_string()).await;
+    let mut contribution = actions::try_contribute(&harness, &http_client, &session_id).await;
+    contribution
+        .add_entropy::<DefaultEngine>(
+            &actions::entropy_from_str("such an unguessable string, wow!"),
+            &user.identity(),
+            &harness.options.ceremony_parameters(),
+        )
+        .expect("Adding entropy must be possible");
+    actions::contribute_successfully(
+        &harness,
+        &http_client,
+        &session_id,
+        &contribution,
+        &user.identity().to_string(),
+    )
+    .await;
+
+    let checkpoints = http_client
+        .get(harness.app_path("info/checkpoints"))
+        .send()
+        .await
+        .unwrap()
+        .json::<Vec<SignedCheckpoint>>()
+        .await
+        .unwrap();
+    assert_eq!(checkpoints.len(), 1);
+    let address: Address = actions::get_sequencer_eth_address(&harness, &http_client)
+        .await
+        .parse()
+        .unwrap();
+    checkpoints[0]
+        .verify(address)
+        .expect("must be signed by the sequencer");
+
+    let transcript = actions::get_transcript(&harness, &http_client).await;
+    let checkpoint = &checkpoints[0].checkpoint;
+    assert_eq!(checkpoint.index, 1);
+    assert_eq!(
+        checkpoint.transcript_hash,
+        transcript_hash(&transcript).unwrap()
+    );
+    assert_eq!(
+        checkpoint.previous_hash,
+        transcript_hash(
+            &harness
+                .options
+                .ceremony_sizes
+                .empty_transcript(&harness.options.ceremony_parameters())
+        )
+        .unwrap()
+    );
+}
+
+#[tokio::test]
+async fn test_audit_log_records_contribution() {
+    let harness = run_test_harness().await;
+    let http_client = reqwest::Client::new();
+
+    let (user, session_id) =
+        actions::create_and_login_gh_user(&harness, &http_client, "kustosz".to_string()).await;
+    let mut contribution = actions::try_contribute(&harness, &http_client, &session_id).await;
+    contribution
+        .add_entropy::<DefaultEngine>(
+            &actions::entropy_from_str("such an unguessable string, wow!"),
+            &user.identity(),
+            &harness.options.ceremony_parameters(),
+        )
+        .expect("Adding entropy must be possible");
+    actions::contribute_successfully(
+        &harness,
+        &http_client,
+        &session_id,
+        &contribution,
+        &user.identity().to_string(),
+    )
+    .await;
+
+    let entries = http_client
+        .get(harness.app_path("info/audit_log"))
+        .send()
+        .await
+        .unwrap()
+        .json::<Vec<AuditEntry>>()
+        .await
+        .unwrap();
+    let address: Address = actions::get_sequencer_eth_address(&harness, &http_client)
+        .await
+        .parse()
+        .unwrap();
+    verify_audit_log(&entries, address).expect("audit log must verify");
+
+    let uid = user.identity().unique_id();
+    let transcript = actions::get_transcript(&harness, &http_client).await;
+    let events = entries
+        .into_iter()
+        .map(|entry| entry.record.event)
+        .collect::<Vec<_>>();
+    assert_eq!(events, vec![
+        AuditEvent::Login { uid: uid.clone() },
+        AuditEvent::LobbyEntry { uid: uid.clone() },
+        AuditEvent::SlotGranted { uid: uid.clone() },
+        AuditEvent::Accepted {
+            uid,
+            transcript_index: 1,
+            transcript_hash: transcript_hash(&transcript).unwrap(),
+        },
+    ]);
+
+    let page = http_client
+        .get(harness.app_path("info/audit_log?from=3&limit=10"))
+        .send()
+        .await
+        .unwrap()
+        .json::<Vec<AuditEntry>>()
+        .await
+        .unwrap();
+    assert_eq!(page.len(), 1);
+    assert_eq!(page[0].record.sequence, 3);
+}
+
+#[tokio::test]
+async fn test_publishes_transcript_versions() {
+    let harness = harness::Builder::new()
+        .set_transcript_publish_interval(1)
+        .run()
+        .await;
+    let http_client = reqwest::Client::new();
+
+    let (user, session_id) =
+        actions::create_and_login_gh_user(&harness, &http_client, "kustosz".to_string()).await;
+    let mut contribution = actions::try_contribute(&harness, &http_client, &session_id).await;
+    contribution
+        .add_entropy::<DefaultEngine>(
+            &actions::entropy_from_str("such an unguessable string, wow!"),
+            &user.identity(),
+            &harness.options.ceremony_parameters(),
+        )
+        .expect("Adding entropy must be possible");
+    actions::contribute_successfully(
+        &harness,
+        &http_client,
+        &session_id,
+        &contribution,
+        &user.identity().to_string(),
+    )
+    .await;
+
+    let store = LocalTranscriptStore::new(
+        harness.options.transcript_file.clone(),
+        harness.options.transcript_in_progress_file.clone(),
+    );
+    let genesis: BatchTranscript = read_json_file(store.version_path(0)).await.unwrap();
+    assert_eq!(genesis.num_participants(), 0);
+    let published: BatchTranscript = read_json_file(store.version_path(1)).await.unwrap();
+    assert_eq!(published, harness.read_transcript_file().await);
+}
+
 #[tokio::test]
 async fn test_double_contribution_when_allowed() {
     let harness = harness::Builder::new()
@@ -394,6 +657,7 @@ async fn test_double_contribution_when_allowed() {
         .add_entropy::<DefaultEngine>(
             &actions::entropy_from_str("such an unguessable string, wow!"),
             &user_id,
+            &harness.options.ceremony_parameters(),
         )
         .expect("Adding entropy must be possible");
 
@@ -417,6 +681,7 @@ async fn test_double_contribution_when_allowed() {
         .add_entropy::<DefaultEngine>(
             &actions::entropy_from_str("another unguessable string, wow!"),
             &user_id,
+            &harness.options.ceremony_parameters(),
         )
         .expect("Adding entropy must be possible");
 
@@ -561,7 +826,11 @@ async fn test_contribution_after_lobby_cleanup() {
         .unwrap();
     let entropy = Secret::new(entropy);
     contribution
-        .add_entropy::<Arkworks>(&entropy, &user.identity())
+        .add_entropy::<Arkworks>(
+            &entropy,
+            &user.identity(),
+            &harness.options.ceremony_parameters(),
+        )
         .expect("Adding entropy must be possible");
 
     tokio::time::sleep(Duration::from_millis(300)).await;
@@ -586,11 +855,18 @@ async fn test_wrong_ecdsa_signature() {
     let mut contribution = actions::try_contribute(&harness, &http_client, &session_id).await;
     let entropy = actions::entropy_from_str("foo bar baz");
     contribution
-        .add_entropy::<DefaultEngine>(&entropy, &user.identity())
+        .add_entropy::<DefaultEngine>(
+            &entropy,
+            &user.identity(),
+            &harness.options.ceremony_parameters(),
+        )
         .expect("Adding entropy must be possible");
     contribution.ecdsa_signature = EcdsaSignature(Some(
         other_wallet
-            .sign_typed_data(&ContributionTypedData::from(&contribution))
+            .sign_typed_data(&ContributionTypedData::new(
+                &contribution,
+                &harness.options.ceremony_parameters(),
+            ))
             .await
             .unwrap(),
     ));
@@ -618,7 +894,11 @@ async fn test_wrong_bls_signature() {
     let mut contribution = actions::try_contribute(&harness, &http_client, &session_id).await;
     let entropy = actions::entropy_from_str("foo bar baz");
     contribution
-        .add_entropy::<DefaultEngine>(&entropy, &user.identity())
+        .add_entropy::<DefaultEngine>(
+            &entropy,
+            &user.identity(),
+            &harness.options.ceremony_parameters(),
+        )
         .expect("Adding entropy must be possible");
 
     contribution.contributions.iter_mut().for_each(|c| {
@@ -639,6 +919,128 @@ async fn test_wrong_bls_signature() {
     actions::assert_includes_contribution(&transcript, &contribution, &user, false, false)
 }
 
+#[tokio::test]
+async fn test_strict_signatures_reject_invalid_signatures() {
+    let other_wallet = LocalWallet::new(&mut thread_rng());
+    let harness = harness::Builder::new().require_signatures().run().await;
+    let http_client = reqwest::Client::new();
+    let user = harness.create_eth_user().await;
+    let session_id = actions::login(&harness, &http_client, &user).await;
+    let mut contribution = actions::try_contribute(&harness, &http_client, &session_id).await;
+    contribution
+        .add_entropy::<DefaultEngine>(
+            &actions::entropy_from_str("baz bar foo"),
+            &user.identity(),
+            &harness.options.ceremony_parameters(),
+        )
+        .expect("Adding entropy must be possible");
+    contribution.ecdsa_signature = EcdsaSignature(Some(
+        other_wallet
+            .sign_typed_data(&ContributionTypedData::new(
+                &contribution,
+                &harness.options.ceremony_parameters(),
+            ))
+            .await
+            .unwrap(),
+    ));
+
+    let response =
+        actions::request_contribute(&harness, &http_client, &session_id, &contribution).await;
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    let body = response.json::<serde_json::Value>().await.unwrap();
+    assert_eq!(body["code"], "CeremoniesError::InvalidEcdsaSignature");
+
+    let user = harness.create_eth_user().await;
+    let session_id = actions::login(&harness, &http_client, &user).await;
+    let mut contribution = actions::try_contribute(&harness, &http_client, &session_id).await;
+    contribution
+        .add_entropy::<DefaultEngine>(
+            &actions::entropy_from_str("foo bar baz"),
+            &user.identity(),
+            &harness.options.ceremony_parameters(),
+        )
+        .expect("Adding entropy must be possible");
+    contribution.contributions[0].bls_signature = BlsSignature(Some(G1::one()));
+
+    let response =
+        actions::request_contribute(&harness, &http_client, &session_id, &contribution).await;
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    let body = response.json::<serde_json::Value>().await.unwrap();
+    assert_eq!(body["code"], "CeremonyError::InvalidBlsSignature");
+}
+
+#[tokio::test]
+async fn test_rehearsal_rejects_mainnet_signatures() {
+    let harness = harness::Builder::new()
+        .require_signatures()
+        .rehearsal()
+        .run()
+        .await;
+    let http_client = reqwest::Client::new();
+    let mainnet = CeremonyParameters::mainnet();
+    let rehearsal = harness.options.ceremony_parameters();
+
+    // BLS signature for mainnet
+    let user = harness.create_gh_user("kustosz".to_string()).await;
+    let session_id = actions::login(&harness, &http_client, &user).await;
+    let mut contribution = actions::try_contribute(&harness, &http_client, &session_id).await;
+    contribution
+        .add_entropy::<DefaultEngine>(
+            &actions::entropy_from_str("baz bar foo"),
+            &user.identity(),
+            &mainnet,
+        )
+        .expect("Adding entropy must be possible");
+    let response =
+        actions::request_contribute(&harness, &http_client, &session_id, &contribution).await;
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    let body = response.json::<serde_json::Value>().await.unwrap();
+    assert_eq!(body["code"], "CeremonyError::InvalidBlsSignature");
+
+    // ECDSA signature for mainnet, and then for the rehearsal
+    for (parameters, accepted) in [(&mainnet, false), (&rehearsal, true)] {
+        let user = harness.create_eth_user().await;
+        let session_id = actions::login(&harness, &http_client, &user).await;
+        let mut contribution = actions::try_contribute(&harness, &http_client, &session_id).await;
+        contribution
+            .add_entropy::<DefaultEngine>(
+                &actions::entropy_from_str(&user.identity().to_string()),
+                &user.identity(),
+                &rehearsal,
+            )
+            .expect("Adding entropy must be possible");
+        if let AnyTestUser::Eth(user) = &user.user {
+            contribution.ecdsa_signature = EcdsaSignature(Some(
+                user.wallet
+                    .sign_typed_data(&ContributionTypedData::new(&contribution, parameters))
+                    .await
+                    .unwrap(),
+            ));
+        }
+        if accepted {
+            actions::contribute_successfully(
+                &harness,
+                &http_client,
+                &session_id,
+                &contribution,
+                &user.identity().to_string(),
+            )
+            .await;
+        } else {
+            let response =
+                actions::request_contribute(&harness, &http_client, &session_id, &contribution)
+                    .await;
+            assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+            let body = response.json::<serde_json::Value>().await.unwrap();
+            assert_eq!(body["code"], "CeremoniesError::InvalidEcdsaSignature");
+        }
+    }
+
+    let transcript = harness.read_transcript_file().await;
+    assert_eq!(transcript.parameters, rehearsal);
+    assert_eq!(transcript.num_participants(), 1);
+}
+
 #[tokio::test]
 async fn test_graceful_restart() {
     let harness = Arc::new(RwLock::new(run_test_harness().await));
@@ -701,3 +1103,42 @@ async fn test_graceful_restart() {
         .map(|r| r.expect("must terminate successfully"))
         .for_each(|check| check(&final_transcript));
 }
+
+#[tokio::test]
+async fn test_single_engine() {
+    let harness = harness::Builder::new()
+        .set_engine(EngineChoice::Blst)
+        .run()
+        .await;
+    let http_client = reqwest::Client::new();
+
+    let status = http_client
+        .get(harness.app_path("/info/status"))
+        .send()
+        .await
+        .unwrap()
+        .json::<serde_json::Value>()
+        .await
+        .unwrap();
+    assert_eq!(status["engine"], "blst");
+
+    let (user, session_id) =
+        actions::create_and_login_gh_user(&harness, &http_client, "kustosz".to_string()).await;
+    let mut contribution = actions::try_contribute(&harness, &http_client, &session_id).await;
+    contribution
+        .add_entropy::<DefaultEngine>(
+            &actions::entropy_from_str("foo bar baz"),
+            &user.identity(),
+            &harness.options.ceremony_parameters(),
+        )
+        .expect("Adding entropy must be possible");
+    actions::contribute_successfully(
+        &harness,
+        &http_client,
+        &session_id,
+        &contribution,
+        &user.identity().to_string(),
+    )
+    .await;
+    assert_eq!(harness.read_transcript_file().await.num_participants(), 1);
+}